@@ -22,6 +22,9 @@ pub const COMM_DEFAULT: [i8; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
 
 pub type Nodename = Buffer<65>;
 
+/// value of loginuid and sessionid when they are not set
+pub const AUDIT_UNSET: u32 = u32::MAX;
+
 #[derive(BpfError, Clone, Copy)]
 pub enum Error {
     #[error("flags field is missing")]
@@ -73,6 +76,10 @@ pub enum Type {
     Prctl,
     #[str("kill")]
     Kill,
+    #[str("execve_failed")]
+    ExecveFailed,
+    #[str("process_access")]
+    ProcessAccess,
 
     // stuff loaded in kernel
     #[str("init_module")]
@@ -81,6 +88,14 @@ pub enum Type {
     BpfProgLoad,
     #[str("bpf_socket_filter")]
     BpfSocketFilter,
+    #[str("delete_module")]
+    DeleteModule,
+    #[str("kernel_tamper")]
+    KernelTamper,
+    #[str("bpf_prog_attach")]
+    BpfProgAttach,
+    #[str("bpf_link_create")]
+    BpfLinkCreate,
     //#[str("bpf_socket_prog")]
     //BpfSocketProg,
 
@@ -89,6 +104,8 @@ pub enum Type {
     MprotectExec = 40,
     #[str("mmap_exec")]
     MmapExec,
+    #[str("memfd_create")]
+    MemfdCreate,
 
     // networking events
     #[str("connect")]
@@ -97,6 +114,8 @@ pub enum Type {
     DnsQuery,
     #[str("send_data")]
     SendData,
+    #[str("socket_create")]
+    SocketCreate,
 
     // filesystem events
     // mount event was id=80
@@ -112,6 +131,38 @@ pub enum Type {
     FileRename,
     #[str("file_unlink")]
     FileUnlink,
+    #[str("file_chmod")]
+    FileChmod,
+    #[str("file_chown")]
+    FileChown,
+    #[str("file_xattr")]
+    FileXattr,
+    #[str("file_times")]
+    FileTimes,
+    #[str("file_link")]
+    FileLink,
+    #[str("file_symlink")]
+    FileSymlink,
+    // generated in userland by file integrity monitoring
+    #[str("file_integrity")]
+    FileIntegrity,
+    // generated in userland from changes of authentication files
+    #[str("account_added")]
+    AccountAdded,
+    #[str("account_modified")]
+    AccountModified,
+    #[str("account_deleted")]
+    AccountDeleted,
+    #[str("sudoers_changed")]
+    SudoersChanged,
+    #[str("ssh_key_added")]
+    SshKeyAdded,
+
+    // io_uring events
+    #[str("io_uring_setup")]
+    IoUringSetup = 100,
+    #[str("io_uring_op")]
+    IoUringOp,
 
     // Materialize end of possible events
     #[str("end_event")]
@@ -126,6 +177,8 @@ pub enum Type {
     Error,
     #[str("syscore_resume")]
     SyscoreResume,
+    #[str("execve_argv")]
+    ExecveArgv,
 
     // !!! all new event types must be put before max
     #[str("max")]
@@ -161,6 +214,13 @@ pub struct TaskInfo {
     pub comm: [u8; COMM_SIZE],
     pub uid: u32,
     pub gid: u32,
+    pub euid: u32,
+    pub suid: u32,
+    pub fsuid: u32,
+    // audit login uid and session id, AUDIT_UNSET
+    // if not set or not supported by the kernel
+    pub loginuid: u32,
+    pub sessionid: u32,
     // task group id
     // when program is single threaded tgid == pid
     pub tgid: i32,
@@ -188,6 +248,16 @@ impl TaskInfo {
         self.tg_uuid.random = rand;
     }
 
+    #[inline(always)]
+    pub fn loginuid(&self) -> Option<u32> {
+        (self.loginuid != AUDIT_UNSET).then_some(self.loginuid)
+    }
+
+    #[inline(always)]
+    pub fn sessionid(&self) -> Option<u32> {
+        (self.sessionid != AUDIT_UNSET).then_some(self.sessionid)
+    }
+
     not_bpf_target_code! {
         #[inline(always)]
         pub fn comm_string(&self) -> std::string::String {
@@ -4,6 +4,7 @@ use super::EventInfo;
 use super::Namespaces;
 use super::TaskInfo;
 use super::Type;
+use super::AUDIT_UNSET;
 use crate::co_re::core_read_kernel;
 use crate::co_re::task_struct;
 use crate::uuid::Uuid;
@@ -72,8 +73,16 @@ impl TaskInfo {
         self.comm = task.comm_array().ok_or(Error::CommMissing)?;
 
         // if task_struct is valid cannot be null
-        self.uid = task.cred().ok_or(Error::CredFieldMissing)?.uid();
-        self.gid = task.cred().ok_or(Error::CredFieldMissing)?.gid();
+        let cred = task.cred().ok_or(Error::CredFieldMissing)?;
+        self.uid = cred.uid();
+        self.gid = cred.gid();
+        self.euid = cred.euid();
+        self.suid = cred.suid();
+        self.fsuid = cred.fsuid();
+
+        // audit information
+        self.loginuid = task.loginuid().unwrap_or(AUDIT_UNSET);
+        self.sessionid = task.sessionid().unwrap_or(AUDIT_UNSET);
 
         if let Some(nsproxy) = core_read_kernel!(task, nsproxy) {
             // it may happen that under some very specific conditions nsproxy
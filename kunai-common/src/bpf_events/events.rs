@@ -10,14 +10,22 @@ mod clone;
 pub use clone::*;
 mod mmap;
 pub use mmap::*;
+mod memfd;
+pub use memfd::*;
 mod mprotect;
 pub use mprotect::*;
 mod dns_query;
 pub use dns_query::*;
 mod send_entropy;
 pub use send_entropy::*;
+mod socket;
+pub use socket::*;
 mod init_module;
 pub use init_module::*;
+mod delete_module;
+pub use delete_module::*;
+mod kernel_tamper;
+pub use kernel_tamper::*;
 mod exit;
 pub use exit::*;
 mod fs;
@@ -36,6 +44,10 @@ mod syscore_resume;
 pub use syscore_resume::*;
 mod kill;
 pub use kill::*;
+mod process_access;
+pub use process_access::*;
+mod io_uring;
+pub use io_uring::*;
 
 // prevent using correlation event in bpf code
 not_bpf_target_code! {
@@ -60,28 +72,53 @@ const fn max_bpf_event_size() -> usize {
             break;
         }
         let size = match variants[i] {
-            Type::Execve | Type::ExecveScript => ExecveEvent::size_of(),
+            Type::Execve | Type::ExecveScript | Type::ExecveFailed => ExecveEvent::size_of(),
             Type::TaskSched => ScheduleEvent::size_of(),
             Type::Exit | Type::ExitGroup => ExitEvent::size_of(),
             Type::Clone => CloneEvent::size_of(),
             Type::Prctl => PrctlEvent::size_of(),
             Type::Kill => KillEvent::size_of(),
             Type::InitModule => InitModuleEvent::size_of(),
+            Type::DeleteModule => DeleteModuleEvent::size_of(),
+            Type::ProcessAccess => ProcessAccessEvent::size_of(),
+            Type::FileChmod => FileChmodEvent::size_of(),
+            Type::FileChown => FileChownEvent::size_of(),
+            Type::FileXattr => FileXattrEvent::size_of(),
+            Type::FileTimes => FileTimesEvent::size_of(),
+            Type::FileLink => FileLinkEvent::size_of(),
+            Type::FileSymlink => FileSymlinkEvent::size_of(),
             Type::BpfProgLoad => BpfProgLoadEvent::size_of(),
             Type::BpfSocketFilter => BpfSocketFilterEvent::size_of(),
+            Type::BpfProgAttach | Type::BpfLinkCreate => BpfProgAttachEvent::size_of(),
+            Type::KernelTamper => KernelTamperEvent::size_of(),
             Type::MprotectExec => MprotectEvent::size_of(),
             Type::MmapExec => MmapExecEvent::size_of(),
+            Type::MemfdCreate => MemfdCreateEvent::size_of(),
             Type::Connect => ConnectEvent::size_of(),
             Type::DnsQuery => DnsQueryEvent::size_of(),
             Type::SendData => SendEntropyEvent::size_of(),
+            Type::SocketCreate => SocketCreateEvent::size_of(),
             Type::Read | Type::ReadConfig | Type::Write | Type::WriteConfig => {
                 ConfigEvent::size_of()
             }
             Type::FileRename => FileRenameEvent::size_of(),
             Type::FileUnlink => UnlinkEvent::size_of(),
-            Type::Unknown | Type::EndEvents | Type::Correlation | Type::CacheHash | Type::Max => 0,
+            Type::IoUringSetup => IoUringSetupEvent::size_of(),
+            Type::IoUringOp => IoUringOpEvent::size_of(),
+            Type::Unknown
+            | Type::EndEvents
+            | Type::Correlation
+            | Type::CacheHash
+            | Type::FileIntegrity
+            | Type::AccountAdded
+            | Type::AccountModified
+            | Type::AccountDeleted
+            | Type::SudoersChanged
+            | Type::SshKeyAdded
+            | Type::Max => 0,
             Type::Error => ErrorEvent::size_of(),
             Type::SyscoreResume => SysCoreResumeEvent::size_of(),
+            Type::ExecveArgv => ExecveArgvEvent::size_of(),
             // never handle _ pattern otherwise this function loses all interest
         };
         if size > max {
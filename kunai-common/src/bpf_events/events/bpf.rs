@@ -1,5 +1,5 @@
 use crate::bpf_events::Event;
-use crate::{buffer::Buffer, net::SocketInfo, string::String};
+use crate::{buffer::Buffer, net::SocketInfo, path::Path, string::String};
 
 pub const KSYM_NAME_LEN: usize = 512;
 pub const BPF_OBJ_NAME_LEN: usize = 16;
@@ -17,6 +17,28 @@ pub struct ProgHashes {
     pub size: usize,
 }
 
+pub const MAX_PROG_HELPERS: usize = 32;
+pub const MAX_PROG_MAPS: usize = 16;
+
+#[repr(C)]
+pub struct ProgMap {
+    pub id: u32,
+    pub map_type: u32,
+    pub name: String<BPF_OBJ_NAME_LEN>,
+}
+
+/// Analysis of the program instructions, done in userland
+/// right after the program got loaded
+#[repr(C)]
+pub struct ProgInsns {
+    pub count: u32,
+    // immediate values of helper calls
+    pub helpers: [i32; MAX_PROG_HELPERS],
+    pub helpers_len: usize,
+    pub maps: [ProgMap; MAX_PROG_MAPS],
+    pub maps_len: usize,
+}
+
 #[repr(C)]
 pub struct BpfProgData {
     pub id: u32,
@@ -27,6 +49,7 @@ pub struct BpfProgData {
     pub prog_type: u32,
     pub attach_type: u32,
     pub hashes: Option<ProgHashes>,
+    pub insns: Option<ProgInsns>,
     pub verified_insns: Option<u32>,
     pub loaded: bool,
 }
@@ -40,3 +63,63 @@ pub struct BpfSocketFilter {
     pub filter_len: u16,
     pub attached: bool,
 }
+
+// bpf syscall commands attaching programs
+pub const BPF_PROG_ATTACH: u32 = 8;
+pub const BPF_LINK_CREATE: u32 = 28;
+
+/// Returns true if the target of an attach type (enum bpf_attach_type) is a
+/// file descriptor (cgroup, sockmap, netns, lirc device or perf event). For
+/// the other types it is an interface index or it is not used.
+#[inline(always)]
+pub const fn attach_target_is_fd(attach_type: u32) -> bool {
+    matches!(
+        attach_type,
+        // cgroup, sockmap, lirc and flow dissector types
+        0..=22
+            // cgroup getpeername/getsockname, inet_sock_release, sk_lookup,
+            // sk_skb_verdict, perf_event and lsm_cgroup
+            | 29..=32 | 34 | 36 | 38 | 41 | 43
+            // cgroup unix socket types
+            | 49..=53
+    )
+}
+
+/// Beginning of the BPF_PROG_ATTACH command attributes of union bpf_attr
+/// (include/uapi/linux/bpf.h)
+#[repr(C)]
+pub struct BpfProgAttachAttr {
+    pub target_fd: u32,
+    pub attach_bpf_fd: u32,
+    pub attach_type: u32,
+    pub attach_flags: u32,
+}
+
+/// Beginning of the BPF_LINK_CREATE command attributes of union bpf_attr
+/// (include/uapi/linux/bpf.h)
+#[repr(C)]
+pub struct BpfLinkCreateAttr {
+    pub prog_fd: u32,
+    // target_ifindex for XDP programs
+    pub target_fd: u32,
+    pub attach_type: u32,
+    pub flags: u32,
+}
+
+/// Event used for both bpf_prog_attach and bpf_link_create
+pub type BpfProgAttachEvent = Event<BpfProgAttachData>;
+
+#[repr(C)]
+pub struct BpfProgAttachData {
+    pub prog_id: u32,
+    pub prog_type: u32,
+    pub prog_name: String<BPF_OBJ_NAME_LEN>,
+    pub attached_func_name: String<512>,
+    pub attach_type: u32,
+    pub flags: u32,
+    // target file descriptor or interface index
+    pub target_fd: u32,
+    // file behind target_fd (cgroup, perf event ...)
+    pub target: Path,
+    pub rc: i64,
+}
@@ -1,4 +1,8 @@
-use crate::{bpf_events::Event, net::IpPort};
+use crate::macros::not_bpf_target_code;
+use crate::{bpf_events::Event, buffer::Buffer, net::IpPort};
+
+/// Size of sun_path member of struct sockaddr_un
+pub const UNIX_PATH_MAX: usize = 108;
 
 pub type ConnectEvent = Event<ConnectData>;
 
@@ -6,5 +10,42 @@ pub type ConnectEvent = Event<ConnectData>;
 pub struct ConnectData {
     pub family: u32,
     pub ip_port: IpPort,
+    // sun_path of AF_UNIX sockets, abstract
+    // socket names start with a NULL byte
+    pub sun_path: Buffer<UNIX_PATH_MAX>,
+    // pid of the task listening on the AF_UNIX socket
+    // connected to, zero if unknown
+    pub peer_pid: u32,
     pub connected: bool,
+    // connection done by an io_uring operation
+    pub io_uring: bool,
+}
+
+not_bpf_target_code! {
+    impl ConnectData {
+        /// Returns true if connection is made to an abstract AF_UNIX socket
+        #[inline]
+        pub fn is_abstract_unix(&self) -> bool {
+            self.sun_path.as_slice().first() == Some(&0)
+        }
+
+        /// Returns the path of the AF_UNIX socket connected to. Abstract
+        /// socket names are prefixed with '@' (NULL bytes in the name are
+        /// also replaced by '@') as done by ss and other tools.
+        pub fn unix_path(&self) -> String {
+            let path = self.sun_path.as_slice();
+
+            if self.is_abstract_unix() {
+                let name = path
+                    .iter()
+                    .map(|&b| if b == 0 { b'@' } else { b })
+                    .collect::<Vec<u8>>();
+                return String::from_utf8_lossy(&name).into();
+            }
+
+            // pathname sockets may be given with trailing NULL bytes
+            let end = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+            String::from_utf8_lossy(&path[..end]).into()
+        }
+    }
 }
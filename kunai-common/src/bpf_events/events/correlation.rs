@@ -3,7 +3,7 @@ use crate::bpf_events::{Event, Nodename, Type};
 use crate::path::Path;
 use crate::{buffer::Buffer, cgroup::Cgroup};
 
-use super::MAX_ARGV_SIZE;
+use super::MAX_EXECVE_ARGV_SIZE;
 
 /// CorrelationEvent is a very specific type of event
 /// that is not supposed to be used in eBPF. It is
@@ -20,7 +20,7 @@ pub type CorrelationEvent = Event<CorrelationData>;
 // to a byte array, it should not contain any pointers
 pub struct CorrelationData {
     pub origin: Type, // event type it is comming from
-    pub argv: Buffer<MAX_ARGV_SIZE>,
+    pub argv: Buffer<MAX_EXECVE_ARGV_SIZE>,
     pub exe: Path,
     pub paths: [Option<Path>; 1],
     pub cgroup: Cgroup,
@@ -64,7 +64,7 @@ impl From<&CloneEvent> for CorrelationEvent {
             info: value.info,
             data: CorrelationData {
                 origin: value.ty(),
-                argv: value.data.argv,
+                argv: value.data.argv.resize(),
                 exe: value.data.executable,
                 paths: [None],
                 cgroup: value.data.cgroup,
@@ -89,7 +89,7 @@ impl From<&ScheduleEvent> for CorrelationEvent {
             info: value.info,
             data: CorrelationData {
                 origin: value.ty(),
-                argv: value.data.argv,
+                argv: value.data.argv.resize(),
                 exe: value.data.exe,
                 paths: [None],
                 cgroup: value.data.cgroup,
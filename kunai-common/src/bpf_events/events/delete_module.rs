@@ -0,0 +1,15 @@
+use crate::bpf_events::Event;
+use crate::string::String;
+
+// MODULE_NAME_LEN in kernel
+pub const MODULE_NAME_LEN: usize = 56;
+
+pub type DeleteModuleEvent = Event<DeleteModuleData>;
+
+#[repr(C)]
+pub struct DeleteModuleData {
+    pub name: String<MODULE_NAME_LEN>,
+    pub flags: u32,
+    // zero on success or negative errno
+    pub rc: i64,
+}
@@ -1,18 +1,162 @@
+use kunai_macros::StrEnum;
+
 use crate::bpf_events::{Event, Nodename};
 use crate::buffer::Buffer;
 use crate::cgroup::Cgroup;
+use crate::net::{IpPort, SocketInfo};
 use crate::path::Path;
+use crate::string::String;
 
 pub const MAX_ARGV_SIZE: usize = 512;
+// execve events need to carry the full command line
+pub const MAX_EXECVE_ARGV_SIZE: usize = 4096;
+// command line not fitting in execve events is streamed by chunks
+pub const ARGV_CHUNK_SIZE: usize = 4096;
+// allows command lines up to 128KiB (MAX_ARG_STRLEN)
+pub const MAX_ARGV_CHUNKS: usize = 31;
+pub const MAX_ENV_SIZE: usize = 4096;
+pub const TTY_NAME_LEN: usize = 64;
+
+// file type bits of inode i_mode
+const S_IFMT: u16 = 0o170000;
+const S_IFSOCK: u16 = 0o140000;
+const S_IFLNK: u16 = 0o120000;
+const S_IFREG: u16 = 0o100000;
+const S_IFBLK: u16 = 0o060000;
+const S_IFDIR: u16 = 0o040000;
+const S_IFCHR: u16 = 0o020000;
+const S_IFIFO: u16 = 0o010000;
+
+/// Type of file a file descriptor points to
+#[repr(u8)]
+#[derive(StrEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FdType {
+    // file descriptor is not open
+    #[str("none")]
+    None = 0,
+    #[str("file")]
+    File,
+    #[str("directory")]
+    Directory,
+    #[str("symlink")]
+    Symlink,
+    #[str("char_device")]
+    CharDevice,
+    #[str("block_device")]
+    BlockDevice,
+    #[str("pipe")]
+    Pipe,
+    #[str("socket")]
+    Socket,
+    #[str("unknown")]
+    Unknown,
+}
+
+impl FdType {
+    #[inline(always)]
+    pub const fn from_mode(mode: u16) -> Self {
+        match mode & S_IFMT {
+            S_IFREG => Self::File,
+            S_IFDIR => Self::Directory,
+            S_IFLNK => Self::Symlink,
+            S_IFCHR => Self::CharDevice,
+            S_IFBLK => Self::BlockDevice,
+            S_IFIFO => Self::Pipe,
+            S_IFSOCK => Self::Socket,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Information about a standard file descriptor (stdin, stdout, stderr)
+#[repr(C)]
+pub struct StdFd {
+    /// must be a [FdType] value
+    pub ty: u8,
+    /// path of the file (not set for pipes and sockets)
+    pub path: Path,
+    pub socket: SocketInfo,
+    /// peer address of INET sockets
+    pub peer: IpPort,
+    /// pid of the peer of AF_UNIX sockets, 0 if unknown
+    pub peer_pid: u32,
+}
 
 pub type ExecveEvent = Event<ExecveData>;
 
+/// Part of a command line not fitting in [ExecveData::argv]. Chunks
+/// are piped before the execve event they belong to.
+#[repr(C)]
+pub struct ExecveArgvData {
+    /// offset of the chunk in the command line
+    pub offset: u32,
+    pub chunk: Buffer<ARGV_CHUNK_SIZE>,
+}
+
+pub type ExecveArgvEvent = Event<ExecveArgvData>;
+
+/// Arguments of execve syscall
+#[repr(C)]
+pub struct Execve {
+    pub filename: u64,
+    pub argv: u64,
+    pub envp: u64,
+}
+
+/// Arguments of execveat syscall
+#[repr(C)]
+pub struct ExecveAt {
+    pub fd: u64,
+    pub filename: u64,
+    pub argv: u64,
+    pub envp: u64,
+    pub flags: u64,
+}
+
+/// User pointers we need to report failed executions. As a
+/// failed execution returns to the caller, they are still
+/// valid at syscall exit.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ExecveArgs {
+    pub filename: u64,
+    pub argv: u64,
+}
+
+impl From<Execve> for ExecveArgs {
+    fn from(value: Execve) -> Self {
+        Self {
+            filename: value.filename,
+            argv: value.argv,
+        }
+    }
+}
+
+impl From<ExecveAt> for ExecveArgs {
+    fn from(value: ExecveAt) -> Self {
+        Self {
+            filename: value.filename,
+            argv: value.argv,
+        }
+    }
+}
+
 #[repr(C)]
 pub struct ExecveData {
     pub executable: Path,
+    /// FILELESS_* flags of the executable
+    pub fileless: u8,
     pub interpreter: Path,
-    pub argv: Buffer<MAX_ARGV_SIZE>,
+    pub argv: Buffer<MAX_EXECVE_ARGV_SIZE>,
+    // set when command line does not fit in argv and streamed chunks
+    pub argv_truncated: bool,
+    // raw environment block, variables are filtered in userland
+    pub env: Buffer<MAX_ENV_SIZE>,
+    pub env_truncated: bool,
     pub cgroup: Cgroup,
     pub nodename: Nodename,
+    pub cwd: Path,
+    pub tty: String<TTY_NAME_LEN>,
+    pub stdio: [StdFd; 3],
     pub rc: i32,
 }
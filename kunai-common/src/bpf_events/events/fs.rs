@@ -1,11 +1,15 @@
 use crate::bpf_events::Event;
-use crate::path::Path;
+use crate::path::{Path, MAX_PATH_LEN};
+use crate::string::String;
+use crate::time::Time;
 
 pub type ConfigEvent = Event<ConfigData>;
 
 #[repr(C)]
 pub struct ConfigData {
     pub path: Path,
+    // access done by an io_uring operation
+    pub io_uring: bool,
 }
 
 pub type FileRenameEvent = Event<FileRenameData>;
@@ -23,3 +27,84 @@ pub struct UnlinkData {
     pub path: Path,
     pub success: bool,
 }
+
+pub type FileChmodEvent = Event<FileChmodData>;
+
+#[repr(C)]
+pub struct FileChmodData {
+    pub path: Path,
+    pub old_mode: u16,
+    pub new_mode: u16,
+    // syscall return code
+    pub rc: i64,
+}
+
+// value of kuid_t/kgid_t when owner or group is not changed
+pub const INVALID_ID: u32 = u32::MAX;
+
+pub type FileChownEvent = Event<FileChownData>;
+
+#[repr(C)]
+pub struct FileChownData {
+    pub path: Path,
+    pub old_uid: u32,
+    pub old_gid: u32,
+    pub new_uid: u32,
+    pub new_gid: u32,
+    // syscall return code
+    pub rc: i64,
+}
+
+// maximum length of an extended attribute name
+pub const XATTR_NAME_MAX: usize = 255;
+
+pub const XATTR_OP_SET: u8 = 1;
+pub const XATTR_OP_REMOVE: u8 = 2;
+
+pub type FileXattrEvent = Event<FileXattrData>;
+
+#[repr(C)]
+pub struct FileXattrData {
+    pub path: Path,
+    pub name: String<XATTR_NAME_MAX>,
+    pub op: u8,
+    // size of the value set
+    pub size: u64,
+    // syscall return code
+    pub rc: i64,
+}
+
+// special values of tv_nsec given to utimensat
+pub const UTIME_NOW: i64 = (1 << 30) - 1;
+pub const UTIME_OMIT: i64 = (1 << 30) - 2;
+
+pub type FileTimesEvent = Event<FileTimesData>;
+
+/// Old times are found in path metadata
+#[repr(C)]
+pub struct FileTimesData {
+    pub path: Path,
+    pub atime: Time,
+    pub mtime: Time,
+    // syscall return code
+    pub rc: i64,
+}
+
+pub type FileLinkEvent = Event<FileLinkData>;
+
+/// Hard link creation, target is the existing file
+#[repr(C)]
+pub struct FileLinkData {
+    pub target: Path,
+    pub link: Path,
+}
+
+pub type FileSymlinkEvent = Event<FileSymlinkData>;
+
+/// Symlink creation, target is the raw string given
+/// by userland and may be a relative path
+#[repr(C)]
+pub struct FileSymlinkData {
+    pub target: String<MAX_PATH_LEN>,
+    pub link: Path,
+}
@@ -1,6 +1,10 @@
 use crate::bpf_events::Event;
+use crate::path::Path;
 use crate::string::String;
 
+/// finit_module flag telling the kernel to decompress the module
+pub const MODULE_INIT_COMPRESSED_FILE: u64 = 4;
+
 pub type InitModuleEvent = Event<InitModuleData>;
 
 #[repr(C)]
@@ -33,6 +37,14 @@ impl InitModuleArgs {
 }
 
 impl InitModuleArgs {
+    /// Returns true if finit_module is asked to load a compressed file
+    pub fn is_compressed(&self) -> bool {
+        match self {
+            Self::Init(_) => false,
+            Self::FInit(a) => a.flags & MODULE_INIT_COMPRESSED_FILE == MODULE_INIT_COMPRESSED_FILE,
+        }
+    }
+
     pub fn uargs(&self) -> u64 {
         match self {
             Self::Init(a) => a.uargs,
@@ -58,5 +70,7 @@ pub struct InitModuleData {
     pub name: String<256>,
     pub args: InitModuleArgs,
     pub uargs: String<256>,
+    // path of the module file loaded with finit_module
+    pub path: Path,
     pub loaded: bool,
 }
@@ -0,0 +1,206 @@
+use crate::bpf_events::Event;
+use crate::path::{Path, MAX_PATH_LEN};
+use crate::string::String;
+use kunai_macros::StrEnum;
+
+/// Beginning of struct io_uring_params (include/uapi/linux/io_uring.h)
+#[repr(C)]
+pub struct IoUringParams {
+    pub sq_entries: u32,
+    pub cq_entries: u32,
+    pub flags: u32,
+    pub sq_thread_cpu: u32,
+    pub sq_thread_idle: u32,
+}
+
+/// io_uring_setup flag making a kernel thread poll the submission queue
+pub const IORING_SETUP_SQPOLL: u32 = 1 << 1;
+
+pub type IoUringSetupEvent = Event<IoUringSetupData>;
+
+#[repr(C)]
+pub struct IoUringSetupData {
+    pub entries: u32,
+    pub sq_entries: u32,
+    pub cq_entries: u32,
+    pub flags: u32,
+    pub sq_thread_cpu: u32,
+    pub sq_thread_idle: u32,
+    // file descriptor returned or negative errno
+    pub rc: i64,
+}
+
+pub type IoUringOpEvent = Event<IoUringOpData>;
+
+#[repr(C)]
+pub struct IoUringOpData {
+    pub opcode: u8,
+    // file the operation is issued on, if any
+    pub path: Path,
+    // path given to open operations
+    pub name: String<MAX_PATH_LEN>,
+}
+
+#[repr(u8)]
+#[derive(StrEnum, Debug, Clone, Copy, PartialEq, Eq)]
+/// io_uring operations (enum io_uring_op in include/uapi/linux/io_uring.h)
+pub enum IoUringOpcode {
+    #[str("nop")]
+    Nop = 0,
+    #[str("readv")]
+    Readv,
+    #[str("writev")]
+    Writev,
+    #[str("fsync")]
+    Fsync,
+    #[str("read_fixed")]
+    ReadFixed,
+    #[str("write_fixed")]
+    WriteFixed,
+    #[str("poll_add")]
+    PollAdd,
+    #[str("poll_remove")]
+    PollRemove,
+    #[str("sync_file_range")]
+    SyncFileRange,
+    #[str("sendmsg")]
+    Sendmsg,
+    #[str("recvmsg")]
+    Recvmsg,
+    #[str("timeout")]
+    Timeout,
+    #[str("timeout_remove")]
+    TimeoutRemove,
+    #[str("accept")]
+    Accept,
+    #[str("async_cancel")]
+    AsyncCancel,
+    #[str("link_timeout")]
+    LinkTimeout,
+    #[str("connect")]
+    Connect,
+    #[str("fallocate")]
+    Fallocate,
+    #[str("openat")]
+    Openat,
+    #[str("close")]
+    Close,
+    #[str("files_update")]
+    FilesUpdate,
+    #[str("statx")]
+    Statx,
+    #[str("read")]
+    Read,
+    #[str("write")]
+    Write,
+    #[str("fadvise")]
+    Fadvise,
+    #[str("madvise")]
+    Madvise,
+    #[str("send")]
+    Send,
+    #[str("recv")]
+    Recv,
+    #[str("openat2")]
+    Openat2,
+    #[str("epoll_ctl")]
+    EpollCtl,
+    #[str("splice")]
+    Splice,
+    #[str("provide_buffers")]
+    ProvideBuffers,
+    #[str("remove_buffers")]
+    RemoveBuffers,
+    #[str("tee")]
+    Tee,
+    #[str("shutdown")]
+    Shutdown,
+    #[str("renameat")]
+    Renameat,
+    #[str("unlinkat")]
+    Unlinkat,
+    #[str("mkdirat")]
+    Mkdirat,
+    #[str("symlinkat")]
+    Symlinkat,
+    #[str("linkat")]
+    Linkat,
+    #[str("msg_ring")]
+    MsgRing,
+    #[str("fsetxattr")]
+    Fsetxattr,
+    #[str("setxattr")]
+    Setxattr,
+    #[str("fgetxattr")]
+    Fgetxattr,
+    #[str("getxattr")]
+    Getxattr,
+    #[str("socket")]
+    Socket,
+    #[str("uring_cmd")]
+    UringCmd,
+    #[str("send_zc")]
+    SendZc,
+    #[str("sendmsg_zc")]
+    SendmsgZc,
+    #[str("read_multishot")]
+    ReadMultishot,
+    #[str("waitid")]
+    Waitid,
+    #[str("futex_wait")]
+    FutexWait,
+    #[str("futex_wake")]
+    FutexWake,
+    #[str("futex_waitv")]
+    FutexWaitv,
+    #[str("fixed_fd_install")]
+    FixedFdInstall,
+    #[str("ftruncate")]
+    Ftruncate,
+    #[str("bind")]
+    Bind,
+    #[str("listen")]
+    Listen,
+}
+
+impl IoUringOpcode {
+    /// Returns true if the operation is reported as an io_uring_op
+    /// event. Operations reported with their equivalent event (file
+    /// reads and writes, connect) and operations not touching files
+    /// or sockets (timeouts, polling ...) are not.
+    #[inline(always)]
+    pub fn is_reported(&self) -> bool {
+        matches!(
+            self,
+            Self::Sendmsg
+                | Self::Recvmsg
+                | Self::Accept
+                | Self::Openat
+                | Self::Send
+                | Self::Recv
+                | Self::Openat2
+                | Self::Splice
+                | Self::Tee
+                | Self::Renameat
+                | Self::Unlinkat
+                | Self::Mkdirat
+                | Self::Symlinkat
+                | Self::Linkat
+                | Self::Fsetxattr
+                | Self::Setxattr
+                | Self::Socket
+                | Self::UringCmd
+                | Self::SendZc
+                | Self::SendmsgZc
+                | Self::Ftruncate
+                | Self::Bind
+                | Self::Listen
+        )
+    }
+
+    /// Returns true for operations opening files
+    #[inline(always)]
+    pub fn is_open(&self) -> bool {
+        matches!(self, Self::Openat | Self::Openat2)
+    }
+}
@@ -0,0 +1,42 @@
+use crate::bpf_events::Event;
+use crate::buffer::Buffer;
+use crate::path::Path;
+use kunai_macros::StrEnum;
+
+pub const TAMPER_DATA_LEN: usize = 256;
+
+#[repr(u8)]
+#[derive(StrEnum, Debug, Clone, Copy, PartialEq, Eq)]
+/// Ways of tampering with the running kernel
+pub enum TamperKind {
+    /// kprobe created through perf_event_open
+    #[str("perf_kprobe")]
+    PerfKprobe,
+    /// uprobe created through perf_event_open
+    #[str("perf_uprobe")]
+    PerfUprobe,
+    /// write to tracefs or to a sensitive sysctl
+    #[str("file_write")]
+    FileWrite,
+    #[str("kexec_load")]
+    KexecLoad,
+    #[str("kexec_file_load")]
+    KexecFileLoad,
+}
+
+pub type KernelTamperEvent = Event<KernelTamperData>;
+
+#[repr(C)]
+pub struct KernelTamperData {
+    pub kind: TamperKind,
+    // file written to or kernel image loaded
+    pub path: Path,
+    // probed symbol or binary path, data written
+    // to a file or kexec command line
+    pub data: Buffer<TAMPER_DATA_LEN>,
+    // probed address or offset
+    pub addr: u64,
+    // kexec flags
+    pub flags: u64,
+    pub retprobe: bool,
+}
@@ -0,0 +1,27 @@
+use crate::bpf_events::Event;
+use crate::string::String;
+
+// maximum length of the name given to memfd_create (without "memfd:" prefix)
+pub const MFD_NAME_MAX_LEN: usize = 249;
+
+// name prefix of the dentry of memfd files
+pub const MEMFD_PREFIX: &str = "/memfd:";
+
+// flags describing files with no (or no more) link on a filesystem,
+// used to detect fileless executions
+/// file created by memfd_create
+pub const FILELESS_MEMFD: u8 = 1 << 0;
+/// file opened with O_TMPFILE and never linked
+pub const FILELESS_TMPFILE: u8 = 1 << 1;
+/// file unlinked from the filesystem
+pub const FILELESS_DELETED: u8 = 1 << 2;
+
+pub type MemfdCreateEvent = Event<MemfdCreateData>;
+
+#[repr(C)]
+pub struct MemfdCreateData {
+    pub name: String<MFD_NAME_MAX_LEN>,
+    pub flags: u32,
+    // file descriptor returned or negative errno
+    pub rc: i64,
+}
@@ -5,5 +5,12 @@ pub type MmapExecEvent = Event<MmapExecData>;
 
 #[repr(C)]
 pub struct MmapExecData {
+    /// empty for anonymous mappings
     pub filename: Path,
+    pub fd: i32,
+    /// FILELESS_* flags of the mapped file
+    pub fileless: u8,
+    pub len: u64,
+    pub prot: u64,
+    pub flags: u64,
 }
@@ -1,11 +1,35 @@
 use crate::bpf_events::Event;
+use crate::path::Path;
+use kunai_macros::StrEnum;
 
 pub type MprotectEvent = Event<MprotectData>;
 
+#[repr(u8)]
+#[derive(StrEnum, Debug, Clone, Copy, PartialEq, Eq)]
+/// What backs a memory area, named as in /proc/pid/maps
+pub enum VmaKind {
+    #[str("[file]")]
+    File,
+    #[str("[anon]")]
+    Anon,
+    #[str("[heap]")]
+    Heap,
+    #[str("[stack]")]
+    Stack,
+}
+
 #[repr(C)]
 pub struct MprotectData {
-    pub section: [u8; 16],
     pub start: u64,
     pub len: u64,
     pub prot: u64,
+    // false if mprotect failed before memory area was known
+    pub has_vma: bool,
+    // memory area protection is changed on
+    pub vma_start: u64,
+    pub vma_end: u64,
+    // VM_* flags before the change
+    pub vma_flags: u64,
+    pub vma_kind: VmaKind,
+    pub vma_file: Path,
 }
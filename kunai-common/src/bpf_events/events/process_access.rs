@@ -0,0 +1,65 @@
+use kunai_macros::StrEnum;
+
+use crate::{
+    bpf_events::{Event, TaskInfo},
+    macros::not_bpf_target_code,
+};
+
+pub type ProcessAccessEvent = Event<ProcessAccessData>;
+
+/// Ways a task can access the memory or the state of another task
+#[repr(u8)]
+#[derive(StrEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    #[str("ptrace")]
+    Ptrace = 1,
+    #[str("process_vm_writev")]
+    ProcessVmWritev,
+    #[str("proc_mem_write")]
+    ProcMemWrite,
+}
+
+#[repr(C)]
+pub struct ProcessAccessData {
+    pub target: TaskInfo,
+    pub kind: u8,
+    // ptrace request, only relevant for AccessKind::Ptrace
+    pub ptrace_request: i64,
+    // set once target has been resolved
+    pub has_target: bool,
+    // syscall return code
+    pub rc: i64,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(StrEnum, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// ptrace requests giving control over the target task
+pub enum PtraceRequest {
+    PTRACE_POKETEXT = 4,
+    PTRACE_POKEDATA = 5,
+    PTRACE_POKEUSR = 6,
+    PTRACE_SETREGS = 13,
+    PTRACE_SETFPREGS = 15,
+    PTRACE_ATTACH = 16,
+    PTRACE_SETREGSET = 0x4205,
+    PTRACE_SEIZE = 0x4206,
+}
+
+impl PtraceRequest {
+    /// Returns true if request must be tracked
+    #[inline(always)]
+    pub const fn is_tracked(request: i64) -> bool {
+        matches!(request, 4 | 5 | 6 | 13 | 15 | 16 | 0x4205 | 0x4206)
+    }
+}
+
+not_bpf_target_code! {
+    impl PtraceRequest {
+        pub fn from_uint_to_string<T: Into<u64>>(u: T) -> String {
+            let u: u64 = u.into();
+            PtraceRequest::try_from_uint(u)
+                .map(|o| o.as_str().into())
+                .unwrap_or(format!("PTRACE({})", u))
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::bpf_events::{Event, Nodename};
 
+use super::MAX_ARGV_SIZE;
 use crate::buffer::Buffer;
 use crate::cgroup::Cgroup;
 use crate::path::Path;
@@ -8,7 +9,7 @@ pub type ScheduleEvent = Event<ScheduleData>;
 
 pub struct ScheduleData {
     pub exe: Path,
-    pub argv: Buffer<512>,
+    pub argv: Buffer<MAX_ARGV_SIZE>,
     pub nodename: Nodename,
     pub cgroup: Cgroup,
 }
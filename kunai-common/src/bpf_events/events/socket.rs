@@ -0,0 +1,12 @@
+use crate::bpf_events::Event;
+use crate::net::SocketInfo;
+
+pub type SocketCreateEvent = Event<SocketCreateData>;
+
+#[repr(C)]
+pub struct SocketCreateData {
+    pub socket_info: SocketInfo,
+    // protocol as passed to socket, in network byte order
+    // for AF_PACKET sockets
+    pub protocol: u16,
+}
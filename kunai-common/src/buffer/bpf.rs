@@ -6,6 +6,9 @@ use aya_ebpf::helpers::{gen, *};
 
 use super::{Buffer, Error};
 
+// size of the chunks used to read big user buffers
+const USER_CHUNK_SIZE: usize = 512;
+
 impl<const N: usize> Buffer<N> {
     #[inline(always)]
     pub unsafe fn fill_from_iov_iter<const MAX_NR_SEGS: usize>(
@@ -147,6 +150,77 @@ impl<const N: usize> Buffer<N> {
         Ok(())
     }
 
+    /// Reads user memory in chunks of USER_CHUNK_SIZE. Reading stops at the first
+    /// chunk we fail to read (i.e. memory paged out) so that we keep what has
+    /// already been read. An error is returned only if nothing could be read.
+    /// N must be a multiple of USER_CHUNK_SIZE.
+    #[inline(always)]
+    pub unsafe fn read_user_chunks_at<P>(
+        &mut self,
+        from: *const P,
+        size: u32,
+    ) -> Result<(), Error> {
+        let size = min(size as usize, N);
+        self.len = 0;
+
+        for i in 0..N / USER_CHUNK_SIZE {
+            let off = i * USER_CHUNK_SIZE;
+            if off >= size {
+                break;
+            }
+
+            let chunk = min(USER_CHUNK_SIZE, size - off) as i64;
+
+            if check_bounds_signed(off as i64, 0, (N - USER_CHUNK_SIZE) as i64)
+                && check_bounds_signed(chunk, 1, USER_CHUNK_SIZE as i64)
+            {
+                if gen::bpf_probe_read_user(
+                    self.buf[off..N].as_mut_ptr() as *mut _,
+                    chunk as u32,
+                    (from as *const u8).add(off) as *const _,
+                ) != 0
+                {
+                    break;
+                }
+
+                self.len += chunk as usize;
+            }
+        }
+
+        if size > 0 && self.len == 0 {
+            return Err(Error::FailedToRead);
+        }
+
+        Ok(())
+    }
+
+    /// Appends a NULL terminated user string, NULL byte included.
+    /// If the string does not fit in the buffer it is truncated.
+    #[inline(always)]
+    pub unsafe fn append_user_str<P>(&mut self, src: *const P) -> Result<(), Error> {
+        let len = self.len as i64;
+        let left = N as i64 - len;
+
+        if left <= 0 {
+            return Err(Error::BufferFull);
+        }
+
+        if check_bounds_signed(len, 0, N as i64 - 1) && check_bounds_signed(left, 1, N as i64) {
+            let ret = gen::bpf_probe_read_user_str(
+                self.buf[len as usize..N].as_mut_ptr() as *mut _,
+                left as u32,
+                src as *const _,
+            );
+            if ret <= 0 {
+                return Err(Error::FailedToRead);
+            }
+
+            self.len += ret as usize;
+        }
+
+        Ok(())
+    }
+
     #[inline(always)]
     pub unsafe fn read_kernel_at<P>(&mut self, from: *const P, size: u32) -> Result<(), Error> {
         let size = (size as i64).clamp(0, N as i64);
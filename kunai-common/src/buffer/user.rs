@@ -1,3 +1,5 @@
+use core::cmp::min;
+
 use crate::utils::cstr_to_string;
 
 use super::Buffer;
@@ -14,4 +16,14 @@ impl<const N: usize> Buffer<N> {
             .filter(|s| !s.is_empty())
             .collect()
     }
-}
\ No newline at end of file
+
+    /// copies the content of the buffer into a buffer of another size,
+    /// data not fitting into the destination buffer is truncated
+    pub fn resize<const M: usize>(&self) -> Buffer<M> {
+        let mut out = Buffer::<M>::new();
+        let len = min(self.len(), M);
+        out.buf[..len].copy_from_slice(&self.buf[..len]);
+        out.len = len;
+        out
+    }
+}
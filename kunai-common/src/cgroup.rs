@@ -10,7 +10,9 @@ bpf_target_code! {
     mod bpf;
 }
 
-const CGROUP_PATH_MAX: usize = 128;
+// big enough to hold kubernetes cgroup paths such as
+// kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod<uid>.slice/cri-containerd-<id>.scope
+const CGROUP_PATH_MAX: usize = 256;
 
 const CGROUP_STRING_LEN: usize = CGROUP_PATH_MAX * 2;
 
@@ -23,6 +25,8 @@ pub struct Cgroup {
 
 #[derive(BpfError, Debug, Clone, Copy)]
 pub enum Error {
+    #[error("failed to read task.cgroups")]
+    CssSet,
     #[error("failed to read cgroup.kn")]
     Kn,
     #[error("failed to read kn.name")]
@@ -1,9 +1,14 @@
-use crate::co_re::{self, core_read_kernel};
+use crate::co_re::{self, core_read_kernel, CgroupSubsys};
 
 use super::{Cgroup, Error};
 
 const MAX_CGROUP_DEPTH: usize = 32;
 
+// order matters, memory and cpu controllers are the ones
+// container engines always set up on cgroup v1
+const CGROUP_V1_SUBSYS: [CgroupSubsys; 3] =
+    [CgroupSubsys::Memory, CgroupSubsys::Cpu, CgroupSubsys::Pids];
+
 impl Cgroup {
     /// Resolve the cgroup path. The algorithm resolves the path in reverse order
     /// to minimize the number of instructions.
@@ -38,4 +43,41 @@ impl Cgroup {
 
         Ok(())
     }
+
+    /// Resolve the cgroup path of a task. The cgroup of the unified (v2) hierarchy
+    /// is taken first. If the task sits at the root of the unified hierarchy, which
+    /// is the case on cgroup v1 and hybrid setups, we look for the cgroup in the v1
+    /// controller hierarchies. Named v1 hierarchies (i.e. name=systemd) are mirrored
+    /// by the unified hierarchy on hybrid setups so they do not need special handling.
+    #[inline(always)]
+    pub unsafe fn resolve_task(&mut self, ts: co_re::task_struct) -> Result<(), Error> {
+        let css_set = match core_read_kernel!(ts, cgroups) {
+            Some(css_set) => css_set,
+            None => {
+                self.error = Some(Error::CssSet);
+                return Err(Error::CssSet);
+            }
+        };
+
+        if let Some(dfl) = core_read_kernel!(css_set, dfl_cgrp) {
+            if !dfl.is_null() && !dfl.is_root().unwrap_or(true) {
+                return self.resolve(dfl);
+            }
+        }
+
+        for subsys in CGROUP_V1_SUBSYS {
+            if let Some(cgrp) = css_set.subsys(subsys).and_then(|css| css.cgroup()) {
+                if !cgrp.is_null() && !cgrp.is_root().unwrap_or(true) {
+                    return self.resolve(cgrp);
+                }
+            }
+        }
+
+        // the task is at the root of every hierarchy we know about
+        // so we fallback to the cgroup of the task group
+        match core_read_kernel!(ts, sched_task_group, css, cgroup) {
+            Some(cgroup) => self.resolve(cgroup),
+            None => Ok(()),
+        }
+    }
 }
@@ -49,9 +49,18 @@ pub use core_clone_args::*;
 mod core_files_struct;
 pub use core_files_struct::*;
 
+mod core_tty;
+pub use core_tty::*;
+
 mod core_page;
 pub use core_page::*;
 
+mod core_io_uring;
+pub use core_io_uring::*;
+
+mod core_perf;
+pub use core_perf::*;
+
 #[derive(Clone, Copy)]
 pub struct CoRe<P> {
     ptr: *const P,
@@ -108,10 +108,16 @@ struct cred
 {
 	struct kuid_t uid;
 	struct kgid_t gid;
+	struct kuid_t suid;
+	struct kuid_t euid;
+	struct kuid_t fsuid;
 } __attribute__((preserve_access_index));
 
 _SHIM_GETTER_BPF_CORE_READ(uid_t, shim_cred_uid(struct cred *pcred), pcred, uid.val);
 _SHIM_GETTER_BPF_CORE_READ(gid_t, shim_cred_gid(struct cred *pcred), pcred, gid.val);
+_SHIM_GETTER_BPF_CORE_READ(uid_t, shim_cred_suid(struct cred *pcred), pcred, suid.val);
+_SHIM_GETTER_BPF_CORE_READ(uid_t, shim_cred_euid(struct cred *pcred), pcred, euid.val);
+_SHIM_GETTER_BPF_CORE_READ(uid_t, shim_cred_fsuid(struct cred *pcred), pcred, fsuid.val);
 
 struct qstr
 {
@@ -155,9 +161,11 @@ __attribute__((always_inline)) struct mount *shim_mount_from_vfsmount(struct vfs
 
 struct super_block
 {
+	unsigned int s_dev;
 	struct dentry *s_root;
 } __attribute__((preserve_access_index));
 
+SHIM(super_block, s_dev);
 SHIM(super_block, s_root);
 
 struct dentry
@@ -206,9 +214,14 @@ typedef __kernel_loff_t loff_t;
 struct inode
 {
 	umode_t i_mode;
+	struct kuid_t i_uid;
+	struct kgid_t i_gid;
 	unsigned long i_ino;
 	struct super_block *i_sb;
 	loff_t i_size;
+	unsigned int i_nlink;
+	// unsigned long before 6.12, we only need low bits
+	unsigned int i_state;
 	union {
 		struct timespec64 i_atime;
 		struct timespec64 __i_atime;
@@ -225,8 +238,12 @@ struct inode
 
 SHIM(inode, i_ino);
 SHIM(inode, i_mode);
+_SHIM_GETTER_BPF_CORE_READ(uid_t, shim_inode_i_uid(struct inode *inode), inode, i_uid.val);
+_SHIM_GETTER_BPF_CORE_READ(gid_t, shim_inode_i_gid(struct inode *inode), inode, i_gid.val);
 SHIM(inode, i_sb);
 SHIM(inode, i_size);
+SHIM(inode, i_nlink);
+SHIM(inode, i_state);
 SHIM(inode, i_atime);
 SHIM(inode, __i_atime);
 SHIM(inode, i_mtime);
@@ -239,11 +256,13 @@ struct file
 	struct inode *f_inode;
 	struct path f_path;
 	void *private_data;
+	void *f_op;
 } __attribute__((preserve_access_index));
 
 SHIM_REF(file, f_path);
 SHIM(file, f_inode);
 SHIM(file, private_data);
+SHIM(file, f_op);
 
 struct fd
 {
@@ -258,12 +277,40 @@ struct mm_struct
 {
 	unsigned long arg_start;
 	unsigned long arg_end;
+	unsigned long env_start;
+	unsigned long env_end;
+	unsigned long start_brk;
+	unsigned long brk;
+	unsigned long start_stack;
 	struct file *exe_file;
+	struct task_struct *owner;
 } __attribute__((preserve_access_index));
 
 SHIM(mm_struct, arg_start);
 SHIM(mm_struct, arg_end);
+SHIM(mm_struct, env_start);
+SHIM(mm_struct, env_end);
+SHIM(mm_struct, start_brk);
+SHIM(mm_struct, brk);
+SHIM(mm_struct, start_stack);
 SHIM(mm_struct, exe_file);
+SHIM(mm_struct, owner);
+
+struct vm_area_struct
+{
+	unsigned long vm_start;
+	unsigned long vm_end;
+	struct mm_struct *vm_mm;
+	// since 6.3 vm_flags is in an anonymous union
+	unsigned long vm_flags;
+	struct file *vm_file;
+} __attribute__((preserve_access_index));
+
+SHIM(vm_area_struct, vm_start);
+SHIM(vm_area_struct, vm_end);
+SHIM(vm_area_struct, vm_mm);
+SHIM(vm_area_struct, vm_flags);
+SHIM(vm_area_struct, vm_file);
 
 // Defining shim for task_struct
 // We just need to define the fields we need to access
@@ -355,6 +402,30 @@ struct task_group
 
 SHIM_REF(task_group, css);
 
+// cgroup v1 controllers we rely on to resolve a task cgroup
+// when it is not attached to the unified hierarchy
+enum cgroup_subsys_id
+{
+	cpu_cgrp_id,
+	memory_cgrp_id,
+	pids_cgrp_id,
+};
+
+SHIM_ENUM_VALUE(cgroup_subsys_id, cpu_cgrp_id);
+SHIM_ENUM_VALUE(cgroup_subsys_id, memory_cgrp_id);
+SHIM_ENUM_VALUE(cgroup_subsys_id, pids_cgrp_id);
+
+#define CGROUP_SUBSYS_COUNT 16
+
+struct css_set
+{
+	struct cgroup_subsys_state *subsys[CGROUP_SUBSYS_COUNT];
+	struct cgroup *dfl_cgrp; /* cgroup of the default (v2) hierarchy */
+} __attribute__((preserve_access_index));
+
+ARRAY_SHIM(css_set, subsys);
+SHIM(css_set, dfl_cgrp);
+
 struct fdtable
 {
 	unsigned int max_fds;
@@ -373,6 +444,29 @@ struct files_struct
 ARRAY_SHIM(files_struct, fd_array);
 SHIM(files_struct, fdt);
 
+struct fs_struct
+{
+	struct path pwd;
+} __attribute__((preserve_access_index));
+
+SHIM_REF(fs_struct, pwd);
+
+#define TTY_NAME_LEN 64
+
+struct tty_struct
+{
+	char name[TTY_NAME_LEN];
+} __attribute__((preserve_access_index));
+
+ARRAY_SHIM(tty_struct, name);
+
+struct signal_struct
+{
+	struct tty_struct *tty;
+} __attribute__((preserve_access_index));
+
+SHIM(signal_struct, tty);
+
 struct task_struct
 {
 	unsigned int flags;
@@ -390,8 +484,17 @@ struct task_struct
 	struct task_struct *group_leader;
 	struct mm_struct *mm;
 	struct files_struct *files;
+	struct fs_struct *fs;
+	struct signal_struct *signal;
 	struct nsproxy *nsproxy;
 	struct task_group *sched_task_group;
+	struct css_set *cgroups;
+	// only available with CONFIG_AUDIT
+	struct kuid_t loginuid;
+	unsigned int sessionid;
+	// only available with CONFIG_IO_URING, we just need
+	// to know if it is NULL so we don't define the type
+	void *io_uring;
 } __attribute__((preserve_access_index));
 
 SHIM(task_struct, flags);
@@ -406,8 +509,15 @@ SHIM(task_struct, group_leader);
 SHIM(task_struct, real_parent);
 SHIM(task_struct, mm);
 SHIM(task_struct, files);
+SHIM(task_struct, fs);
+SHIM(task_struct, signal);
 SHIM(task_struct, nsproxy);
 SHIM(task_struct, sched_task_group);
+SHIM(task_struct, cgroups);
+_SHIM_GETTER_BPF_CORE_READ(uid_t, shim_task_struct_loginuid(struct task_struct *task_struct), task_struct, loginuid.val);
+_FIELD_EXISTS_DEF(task_struct, loginuid, loginuid);
+SHIM(task_struct, sessionid);
+SHIM(task_struct, io_uring);
 
 #define KSYM_NAME_LEN 512
 
@@ -568,6 +678,15 @@ SHIM(sockaddr_in6, sin6_family);
 SHIM(sockaddr_in6, sin6_port);
 SHIM_REF(sockaddr_in6, sin6_addr);
 
+struct sockaddr_un
+{
+	__kernel_sa_family_t sun_family;
+	char sun_path[108];
+} __attribute__((preserve_access_index));
+
+SHIM(sockaddr_un, sun_family);
+ARRAY_SHIM(sockaddr_un, sun_path);
+
 struct sock_common
 {
 	union {
@@ -623,18 +742,32 @@ SHIM(sk_buff_head, prev);
 SHIM_REF(sk_buff_head, list);
 SHIM(sk_buff_head, qlen);
 
+struct upid
+{
+	int nr;
+} __attribute__((preserve_access_index));
+
+struct pid
+{
+	struct upid numbers[1];
+} __attribute__((preserve_access_index));
+
+SHIM_WITH_NAME(pid, numbers[0].nr, nr);
+
 struct sock
 {
 	struct sock_common __sk_common;
 	__u8 sk_protocol;
 	__u16 sk_type;
 	struct sk_buff_head sk_receive_queue;
+	struct pid *sk_peer_pid;
 } __attribute__((preserve_access_index));
 
 SHIM_REF(sock, __sk_common);
 SHIM_BITFIELD(sock, sk_protocol);
 SHIM_BITFIELD(sock, sk_type);
 SHIM_REF(sock, sk_receive_queue)
+SHIM(sock, sk_peer_pid);
 
 struct socket
 {
@@ -746,3 +879,49 @@ struct kernel_clone_args
 } __attribute__((preserve_access_index));
 
 SHIM(kernel_clone_args, flags);
+
+struct filename
+{
+	const char *name;
+} __attribute__((preserve_access_index));
+
+SHIM(filename, name);
+
+struct io_kiocb
+{
+	struct file *file;
+	u8 opcode;
+} __attribute__((preserve_access_index));
+
+SHIM(io_kiocb, file);
+SHIM(io_kiocb, opcode);
+
+// command data of open operations, found at
+// the beginning of struct io_kiocb
+struct io_open
+{
+	struct file *file;
+	int dfd;
+	struct filename *filename;
+} __attribute__((preserve_access_index));
+
+SHIM(io_open, dfd);
+SHIM(io_open, filename);
+
+struct perf_event_attr
+{
+	// kprobe_func or uprobe_path for probe events
+	__u64 config1;
+	// kprobe_addr or probe_offset for probe events
+	__u64 config2;
+} __attribute__((preserve_access_index));
+
+SHIM(perf_event_attr, config1);
+SHIM(perf_event_attr, config2);
+
+struct perf_event
+{
+	struct perf_event_attr attr;
+} __attribute__((preserve_access_index));
+
+SHIM_REF(perf_event, attr);
@@ -1,11 +1,19 @@
+use aya_ebpf::helpers::bpf_probe_read_kernel;
+
 use super::gen::{self, *};
-use super::{kernfs_node, rust_shim_kernel_impl, CoRe};
+use super::{core_read_kernel, kernfs_node, rust_shim_kernel_impl, CoRe};
 
 #[allow(non_camel_case_types)]
 pub type cgroup = CoRe<gen::cgroup>;
 
 impl cgroup {
     rust_shim_kernel_impl!(cgroup, kn, kernfs_node);
+
+    /// returns true if the cgroup is the root of its hierarchy
+    #[inline(always)]
+    pub unsafe fn is_root(&self) -> Option<bool> {
+        Some(core_read_kernel!(self, kn, parent)?.is_null())
+    }
 }
 
 #[allow(non_camel_case_types)]
@@ -21,3 +29,51 @@ pub type task_group = CoRe<gen::task_group>;
 impl task_group {
     rust_shim_kernel_impl!(task_group, css, cgroup_subsys_state);
 }
+
+/// cgroup v1 controllers, the actual subsystem ids
+/// depend on the kernel configuration
+#[derive(Debug, Clone, Copy)]
+pub enum CgroupSubsys {
+    Cpu,
+    Memory,
+    Pids,
+}
+
+impl CgroupSubsys {
+    #[inline(always)]
+    pub unsafe fn id(&self) -> Option<u32> {
+        match self {
+            Self::Cpu if shim_cgroup_subsys_id_cpu_cgrp_id_exists() => {
+                Some(shim_cgroup_subsys_id_cpu_cgrp_id())
+            }
+            Self::Memory if shim_cgroup_subsys_id_memory_cgrp_id_exists() => {
+                Some(shim_cgroup_subsys_id_memory_cgrp_id())
+            }
+            Self::Pids if shim_cgroup_subsys_id_pids_cgrp_id_exists() => {
+                Some(shim_cgroup_subsys_id_pids_cgrp_id())
+            }
+            _ => None,
+        }
+    }
+}
+
+const CGROUP_SUBSYS_COUNT: u32 = 16;
+
+#[allow(non_camel_case_types)]
+pub type css_set = CoRe<gen::css_set>;
+
+impl css_set {
+    rust_shim_kernel_impl!(css_set, dfl_cgrp, cgroup);
+    rust_shim_kernel_impl!(pub(self), _subsys, css_set, subsys, *mut *mut gen::cgroup_subsys_state);
+
+    /// gets the cgroup_subsys_state of a given controller
+    #[inline(always)]
+    pub unsafe fn subsys(&self, subsys: CgroupSubsys) -> Option<cgroup_subsys_state> {
+        let id = subsys.id()?;
+        if id < CGROUP_SUBSYS_COUNT {
+            let ptr = bpf_probe_read_kernel(self._subsys()?.add(id as usize)).ok()?;
+            return Some(ptr.into());
+        }
+        None
+    }
+}
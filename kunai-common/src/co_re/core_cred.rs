@@ -14,4 +14,19 @@ impl cred {
     pub unsafe fn gid(&self) -> u32 {
         shim_cred_gid(self.as_ptr_mut())
     }
+
+    #[inline(always)]
+    pub unsafe fn suid(&self) -> u32 {
+        shim_cred_suid(self.as_ptr_mut())
+    }
+
+    #[inline(always)]
+    pub unsafe fn euid(&self) -> u32 {
+        shim_cred_euid(self.as_ptr_mut())
+    }
+
+    #[inline(always)]
+    pub unsafe fn fsuid(&self) -> u32 {
+        shim_cred_fsuid(self.as_ptr_mut())
+    }
 }
@@ -10,11 +10,33 @@ const S_IFMT: u16 = 0o00170000;
 const S_IFREG: u16 = 0o0100000;
 const S_IFSOCK: u16 = 0o0140000;
 
+// inode state of O_TMPFILE files which can still be linked
+const I_LINKABLE: u32 = 1 << 10;
+
 impl inode {
     rust_shim_kernel_impl!(inode, i_ino, u64);
     rust_shim_kernel_impl!(inode, i_mode, u16);
+
+    #[inline(always)]
+    pub unsafe fn i_uid(&self) -> u32 {
+        shim_inode_i_uid(self.as_ptr_mut())
+    }
+
+    #[inline(always)]
+    pub unsafe fn i_gid(&self) -> u32 {
+        shim_inode_i_gid(self.as_ptr_mut())
+    }
+
     rust_shim_kernel_impl!(inode, i_sb, super_block);
     rust_shim_kernel_impl!(inode, i_size, i64);
+    rust_shim_kernel_impl!(inode, i_nlink, u32);
+    rust_shim_kernel_impl!(inode, i_state, u32);
+
+    /// returns true if inode is an O_TMPFILE file never linked
+    #[inline(always)]
+    pub unsafe fn is_tmpfile(&self) -> Option<bool> {
+        Some(self.i_nlink()? == 0 && self.i_state()? & I_LINKABLE == I_LINKABLE)
+    }
 
     // handle i_atime member (renamed in 6.7)
     rust_shim_kernel_impl!(pub(self),_i_atime, inode, i_atime, timespec64);
@@ -48,6 +70,26 @@ impl inode {
     pub unsafe fn is_sock(&self) -> Option<bool> {
         Some(self.i_mode()? & S_IFMT == S_IFSOCK)
     }
+
+    /// returns the file type bits of i_mode
+    #[inline(always)]
+    pub unsafe fn file_type(&self) -> Option<u16> {
+        Some(self.i_mode()? & S_IFMT)
+    }
+}
+
+#[allow(non_camel_case_types)]
+pub type fs_struct = CoRe<gen::fs_struct>;
+
+impl fs_struct {
+    rust_shim_kernel_impl!(pub, fs_struct, pwd, path);
+}
+
+#[allow(non_camel_case_types)]
+pub type filename = CoRe<gen::filename>;
+
+impl filename {
+    rust_shim_kernel_impl!(pub, filename, name, *const i8);
 }
 
 #[allow(non_camel_case_types)]
@@ -68,6 +110,7 @@ impl file {
     }
 
     rust_shim_kernel_impl!(pub, file, private_data, *mut c_void);
+    rust_shim_kernel_impl!(pub, file, f_op, *mut c_void);
 }
 
 #[allow(non_camel_case_types)]
@@ -132,6 +175,7 @@ impl dentry {
 pub type super_block = CoRe<gen::super_block>;
 
 impl super_block {
+    rust_shim_kernel_impl!(pub, super_block, s_dev, u32);
     rust_shim_kernel_impl!(pub, super_block, s_root, dentry);
 }
 
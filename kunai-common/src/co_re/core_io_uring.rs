@@ -0,0 +1,25 @@
+use super::gen::{self, *};
+use super::{file, filename, rust_shim_kernel_impl, CoRe};
+
+#[allow(non_camel_case_types)]
+pub type io_kiocb = CoRe<gen::io_kiocb>;
+
+impl io_kiocb {
+    rust_shim_kernel_impl!(pub, io_kiocb, file, file);
+    rust_shim_kernel_impl!(pub, io_kiocb, opcode, u8);
+
+    /// Returns the command data of an open request. This must be
+    /// called only for open operations (openat, openat2).
+    #[inline(always)]
+    pub unsafe fn open_cmd(&self) -> io_open {
+        io_open::from_ptr(self.as_ptr() as *const _)
+    }
+}
+
+#[allow(non_camel_case_types)]
+pub type io_open = CoRe<gen::io_open>;
+
+impl io_open {
+    rust_shim_kernel_impl!(pub, io_open, dfd, i32);
+    rust_shim_kernel_impl!(pub, io_open, filename, filename);
+}
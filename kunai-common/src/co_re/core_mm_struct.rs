@@ -1,5 +1,5 @@
 use super::gen::{self, *};
-use super::{file, rust_shim_kernel_impl, CoRe};
+use super::{file, rust_shim_kernel_impl, task_struct, CoRe};
 
 #[allow(non_camel_case_types)]
 pub type mm_struct = CoRe<gen::mm_struct>;
@@ -22,5 +22,51 @@ impl mm_struct {
     }
 
     rust_shim_kernel_impl!(mm_struct, arg_end, u64);
+
+    rust_shim_kernel_impl!(mm_struct, env_start, u64);
+
+    #[inline(always)]
+    pub unsafe fn env_len(&self) -> Option<u64> {
+        let start = self.env_start()?;
+        let end = self.env_end()?;
+        Some({
+            if end == 0 || start >= end {
+                0
+            } else {
+                end - start
+            }
+        })
+    }
+
+    rust_shim_kernel_impl!(mm_struct, env_end, u64);
+    rust_shim_kernel_impl!(mm_struct, start_brk, u64);
+    rust_shim_kernel_impl!(mm_struct, brk, u64);
+    rust_shim_kernel_impl!(mm_struct, start_stack, u64);
     rust_shim_kernel_impl!(mm_struct, exe_file, file);
+    // only available with CONFIG_MEMCG
+    rust_shim_kernel_impl!(pub, mm_struct, owner, task_struct);
+}
+
+#[allow(non_camel_case_types)]
+pub type vm_area_struct = CoRe<gen::vm_area_struct>;
+
+impl vm_area_struct {
+    rust_shim_kernel_impl!(pub, vm_area_struct, vm_start, u64);
+    rust_shim_kernel_impl!(pub, vm_area_struct, vm_end, u64);
+    rust_shim_kernel_impl!(pub, vm_area_struct, vm_mm, mm_struct);
+    rust_shim_kernel_impl!(pub, vm_area_struct, vm_flags, u64);
+    rust_shim_kernel_impl!(pub, vm_area_struct, vm_file, file);
+
+    #[inline(always)]
+    // inspired from: https://elixir.bootlin.com/linux/v6.6.13/source/fs/proc/task_mmu.c#L292
+    pub unsafe fn is_heap(&self) -> Option<bool> {
+        let mm = self.vm_mm()?;
+        Some(self.vm_start()? <= mm.brk()? && self.vm_end()? >= mm.start_brk()?)
+    }
+
+    #[inline(always)]
+    pub unsafe fn is_stack(&self) -> Option<bool> {
+        let start_stack = self.vm_mm()?.start_stack()?;
+        Some(self.vm_start()? <= start_stack && self.vm_end()? >= start_stack)
+    }
 }
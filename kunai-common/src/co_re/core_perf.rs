@@ -0,0 +1,17 @@
+use super::gen::{self, *};
+use super::{rust_shim_kernel_impl, CoRe};
+
+#[allow(non_camel_case_types)]
+pub type perf_event_attr = CoRe<gen::perf_event_attr>;
+
+impl perf_event_attr {
+    rust_shim_kernel_impl!(pub, perf_event_attr, config1, u64);
+    rust_shim_kernel_impl!(pub, perf_event_attr, config2, u64);
+}
+
+#[allow(non_camel_case_types)]
+pub type perf_event = CoRe<gen::perf_event>;
+
+impl perf_event {
+    rust_shim_kernel_impl!(pub, perf_event, attr, perf_event_attr);
+}
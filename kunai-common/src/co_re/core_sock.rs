@@ -65,6 +65,7 @@ impl sock {
     rust_shim_kernel_impl!(pub, sock, sk_type, u16);
     rust_shim_kernel_impl!(pub, sock, sk_protocol, u8);
     rust_shim_kernel_impl!(pub, sock, sk_receive_queue, sk_buff_head);
+    rust_shim_kernel_impl!(pub, sock, sk_peer_pid, pid);
 }
 
 #[allow(non_camel_case_types)]
@@ -234,3 +235,29 @@ impl sockaddr_in6 {
     rust_shim_kernel_impl!(pub, sockaddr_in6, sin6_addr, in6_addr);
     rust_shim_user_impl!(pub, sockaddr_in6, sin6_addr, in6_addr);
 }
+
+#[allow(non_camel_case_types)]
+pub type sockaddr_un = CoRe<gen::sockaddr_un>;
+
+impl From<sockaddr> for sockaddr_un {
+    #[inline(always)]
+    fn from(value: sockaddr) -> Self {
+        Self::from_ptr(value.as_ptr() as *const _)
+    }
+}
+
+impl sockaddr_un {
+    rust_shim_kernel_impl!(pub, sockaddr_un, sun_family, u32);
+    rust_shim_user_impl!(pub, sockaddr_un, sun_family, u32);
+
+    rust_shim_kernel_impl!(pub, sockaddr_un, sun_path, *mut i8);
+    rust_shim_user_impl!(pub, sockaddr_un, sun_path, *mut i8);
+}
+
+#[allow(non_camel_case_types)]
+pub type pid = CoRe<gen::pid>;
+
+impl pid {
+    // pid number in the initial pid namespace
+    rust_shim_kernel_impl!(pub, pid, nr, i32);
+}
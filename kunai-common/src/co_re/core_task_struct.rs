@@ -1,3 +1,4 @@
+use aya_ebpf::cty::c_void;
 use aya_ebpf::helpers::{
     bpf_get_current_pid_tgid, bpf_get_current_task, bpf_get_current_task_btf,
     bpf_probe_read_kernel_buf,
@@ -7,8 +8,8 @@ use crate::string::String;
 
 use super::gen::{self, *};
 use super::{
-    core_read_kernel, cred, file, files_struct, mm_struct, nsproxy, rust_shim_kernel_impl,
-    task_group, CoRe,
+    core_read_kernel, cred, css_set, file, files_struct, fs_struct, mm_struct, nsproxy,
+    rust_shim_kernel_impl, signal_struct, task_group, CoRe,
 };
 
 #[allow(non_camel_case_types)]
@@ -79,9 +80,26 @@ impl task_struct {
     rust_shim_kernel_impl!(pub, task_struct, real_parent, Self);
 
     rust_shim_kernel_impl!(task_struct, files, files_struct);
+    rust_shim_kernel_impl!(pub, task_struct, fs, fs_struct);
+    rust_shim_kernel_impl!(pub, task_struct, signal, signal_struct);
     rust_shim_kernel_impl!(pub, task_struct, nsproxy, nsproxy);
 
     rust_shim_kernel_impl!(task_struct, sched_task_group, task_group);
+    rust_shim_kernel_impl!(task_struct, cgroups, css_set);
+
+    /// audit login uid, not available if kernel is compiled without CONFIG_AUDIT
+    #[inline(always)]
+    pub unsafe fn loginuid(&self) -> Option<u32> {
+        if !self.is_null() && shim_task_struct_loginuid_exists(self.as_ptr_mut()) {
+            return Some(shim_task_struct_loginuid(self.as_ptr_mut()));
+        }
+        None
+    }
+
+    rust_shim_kernel_impl!(pub, task_struct, sessionid, u32);
+
+    // io_uring context, not available if kernel is compiled without CONFIG_IO_URING
+    rust_shim_kernel_impl!(pub, task_struct, io_uring, *mut c_void);
 
     #[inline(always)]
     /// this is a shortcut function to easily get a file from its fd
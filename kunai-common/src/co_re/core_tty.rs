@@ -0,0 +1,16 @@
+use super::gen::{self, *};
+use super::{rust_shim_kernel_impl, CoRe};
+
+#[allow(non_camel_case_types)]
+pub type tty_struct = CoRe<gen::tty_struct>;
+
+impl tty_struct {
+    rust_shim_kernel_impl!(pub, tty_struct, name, *mut i8);
+}
+
+#[allow(non_camel_case_types)]
+pub type signal_struct = CoRe<gen::signal_struct>;
+
+impl signal_struct {
+    rust_shim_kernel_impl!(pub, signal_struct, tty, tty_struct);
+}
@@ -36,6 +36,9 @@ pub struct kuid_t {
 pub struct cred {
     pub uid: kuid_t,
     pub gid: kgid_t,
+    pub suid: kuid_t,
+    pub euid: kuid_t,
+    pub fsuid: kuid_t,
 }
 extern "C" {
     pub fn shim_cred_uid(pcred: *mut cred) -> uid_t;
@@ -43,6 +46,21 @@ extern "C" {
 extern "C" {
     pub fn shim_cred_gid(pcred: *mut cred) -> gid_t;
 }
+extern "C" {
+    pub fn shim_inode_i_uid(inode: *mut inode) -> uid_t;
+}
+extern "C" {
+    pub fn shim_inode_i_gid(inode: *mut inode) -> gid_t;
+}
+extern "C" {
+    pub fn shim_cred_suid(pcred: *mut cred) -> uid_t;
+}
+extern "C" {
+    pub fn shim_cred_euid(pcred: *mut cred) -> uid_t;
+}
+extern "C" {
+    pub fn shim_cred_fsuid(pcred: *mut cred) -> uid_t;
+}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct qstr {
@@ -131,8 +149,18 @@ extern "C" {
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct super_block {
+    pub s_dev: ::core::ffi::c_uint,
     pub s_root: *mut dentry,
 }
+extern "C" {
+    pub fn shim_super_block_s_dev(super_block: *mut super_block) -> ::core::ffi::c_uint;
+}
+extern "C" {
+    pub fn shim_super_block_s_dev_user(super_block: *mut super_block) -> ::core::ffi::c_uint;
+}
+extern "C" {
+    pub fn shim_super_block_s_dev_exists(super_block: *mut super_block) -> bool;
+}
 extern "C" {
     pub fn shim_super_block_s_root(super_block: *mut super_block) -> *mut dentry;
 }
@@ -247,9 +275,13 @@ pub type loff_t = __kernel_loff_t;
 #[derive(Copy, Clone)]
 pub struct inode {
     pub i_mode: umode_t,
+    pub i_uid: kuid_t,
+    pub i_gid: kgid_t,
     pub i_ino: ::core::ffi::c_ulong,
     pub i_sb: *mut super_block,
     pub i_size: loff_t,
+    pub i_nlink: ::core::ffi::c_uint,
+    pub i_state: ::core::ffi::c_uint,
     pub __bindgen_anon_1: inode__bindgen_ty_1,
     pub __bindgen_anon_2: inode__bindgen_ty_2,
     pub __bindgen_anon_3: inode__bindgen_ty_3,
@@ -308,6 +340,24 @@ extern "C" {
 extern "C" {
     pub fn shim_inode_i_size_exists(inode: *mut inode) -> bool;
 }
+extern "C" {
+    pub fn shim_inode_i_nlink(inode: *mut inode) -> ::core::ffi::c_uint;
+}
+extern "C" {
+    pub fn shim_inode_i_nlink_user(inode: *mut inode) -> ::core::ffi::c_uint;
+}
+extern "C" {
+    pub fn shim_inode_i_nlink_exists(inode: *mut inode) -> bool;
+}
+extern "C" {
+    pub fn shim_inode_i_state(inode: *mut inode) -> ::core::ffi::c_uint;
+}
+extern "C" {
+    pub fn shim_inode_i_state_user(inode: *mut inode) -> ::core::ffi::c_uint;
+}
+extern "C" {
+    pub fn shim_inode_i_state_exists(inode: *mut inode) -> bool;
+}
 extern "C" {
     pub fn shim_inode_i_atime(inode: *mut inode) -> timespec64;
 }
@@ -368,6 +418,7 @@ pub struct file {
     pub f_inode: *mut inode,
     pub f_path: path,
     pub private_data: *mut ::core::ffi::c_void,
+    pub f_op: *mut ::core::ffi::c_void,
 }
 extern "C" {
     pub fn shim_file_f_path(file: *mut file) -> *mut path;
@@ -396,6 +447,15 @@ extern "C" {
 extern "C" {
     pub fn shim_file_private_data_exists(file: *mut file) -> bool;
 }
+extern "C" {
+    pub fn shim_file_f_op(file: *mut file) -> *mut ::core::ffi::c_void;
+}
+extern "C" {
+    pub fn shim_file_f_op_user(file: *mut file) -> *mut ::core::ffi::c_void;
+}
+extern "C" {
+    pub fn shim_file_f_op_exists(file: *mut file) -> bool;
+}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct fd {
@@ -425,7 +485,13 @@ extern "C" {
 pub struct mm_struct {
     pub arg_start: ::core::ffi::c_ulong,
     pub arg_end: ::core::ffi::c_ulong,
+    pub env_start: ::core::ffi::c_ulong,
+    pub env_end: ::core::ffi::c_ulong,
+    pub start_brk: ::core::ffi::c_ulong,
+    pub brk: ::core::ffi::c_ulong,
+    pub start_stack: ::core::ffi::c_ulong,
     pub exe_file: *mut file,
+    pub owner: *mut task_struct,
 }
 extern "C" {
     pub fn shim_mm_struct_arg_start(mm_struct: *mut mm_struct) -> ::core::ffi::c_ulong;
@@ -445,6 +511,51 @@ extern "C" {
 extern "C" {
     pub fn shim_mm_struct_arg_end_exists(mm_struct: *mut mm_struct) -> bool;
 }
+extern "C" {
+    pub fn shim_mm_struct_env_start(mm_struct: *mut mm_struct) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_mm_struct_env_start_user(mm_struct: *mut mm_struct) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_mm_struct_env_start_exists(mm_struct: *mut mm_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_mm_struct_env_end(mm_struct: *mut mm_struct) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_mm_struct_env_end_user(mm_struct: *mut mm_struct) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_mm_struct_env_end_exists(mm_struct: *mut mm_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_mm_struct_start_brk(mm_struct: *mut mm_struct) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_mm_struct_start_brk_user(mm_struct: *mut mm_struct) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_mm_struct_start_brk_exists(mm_struct: *mut mm_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_mm_struct_brk(mm_struct: *mut mm_struct) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_mm_struct_brk_user(mm_struct: *mut mm_struct) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_mm_struct_brk_exists(mm_struct: *mut mm_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_mm_struct_start_stack(mm_struct: *mut mm_struct) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_mm_struct_start_stack_user(mm_struct: *mut mm_struct) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_mm_struct_start_stack_exists(mm_struct: *mut mm_struct) -> bool;
+}
 extern "C" {
     pub fn shim_mm_struct_exe_file(mm_struct: *mut mm_struct) -> *mut file;
 }
@@ -454,6 +565,79 @@ extern "C" {
 extern "C" {
     pub fn shim_mm_struct_exe_file_exists(mm_struct: *mut mm_struct) -> bool;
 }
+extern "C" {
+    pub fn shim_mm_struct_owner(mm_struct: *mut mm_struct) -> *mut task_struct;
+}
+extern "C" {
+    pub fn shim_mm_struct_owner_user(mm_struct: *mut mm_struct) -> *mut task_struct;
+}
+extern "C" {
+    pub fn shim_mm_struct_owner_exists(mm_struct: *mut mm_struct) -> bool;
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct vm_area_struct {
+    pub vm_start: ::core::ffi::c_ulong,
+    pub vm_end: ::core::ffi::c_ulong,
+    pub vm_mm: *mut mm_struct,
+    pub vm_flags: ::core::ffi::c_ulong,
+    pub vm_file: *mut file,
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_start(
+        vm_area_struct: *mut vm_area_struct,
+    ) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_start_user(
+        vm_area_struct: *mut vm_area_struct,
+    ) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_start_exists(vm_area_struct: *mut vm_area_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_end(vm_area_struct: *mut vm_area_struct) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_end_user(
+        vm_area_struct: *mut vm_area_struct,
+    ) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_end_exists(vm_area_struct: *mut vm_area_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_mm(vm_area_struct: *mut vm_area_struct) -> *mut mm_struct;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_mm_user(vm_area_struct: *mut vm_area_struct) -> *mut mm_struct;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_mm_exists(vm_area_struct: *mut vm_area_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_flags(
+        vm_area_struct: *mut vm_area_struct,
+    ) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_flags_user(
+        vm_area_struct: *mut vm_area_struct,
+    ) -> ::core::ffi::c_ulong;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_flags_exists(vm_area_struct: *mut vm_area_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_file(vm_area_struct: *mut vm_area_struct) -> *mut file;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_file_user(vm_area_struct: *mut vm_area_struct) -> *mut file;
+}
+extern "C" {
+    pub fn shim_vm_area_struct_vm_file_exists(vm_area_struct: *mut vm_area_struct) -> bool;
+}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct ns_common {
@@ -695,6 +879,48 @@ extern "C" {
 extern "C" {
     pub fn shim_task_group_css_exists(task_group: *mut task_group) -> bool;
 }
+extern "C" {
+    pub fn shim_cgroup_subsys_id_cpu_cgrp_id() -> ::core::ffi::c_uint;
+}
+extern "C" {
+    pub fn shim_cgroup_subsys_id_cpu_cgrp_id_exists() -> bool;
+}
+extern "C" {
+    pub fn shim_cgroup_subsys_id_memory_cgrp_id() -> ::core::ffi::c_uint;
+}
+extern "C" {
+    pub fn shim_cgroup_subsys_id_memory_cgrp_id_exists() -> bool;
+}
+extern "C" {
+    pub fn shim_cgroup_subsys_id_pids_cgrp_id() -> ::core::ffi::c_uint;
+}
+extern "C" {
+    pub fn shim_cgroup_subsys_id_pids_cgrp_id_exists() -> bool;
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct css_set {
+    pub subsys: [*mut cgroup_subsys_state; 16usize],
+    pub dfl_cgrp: *mut cgroup,
+}
+extern "C" {
+    pub fn shim_css_set_subsys(css_set: *mut css_set) -> *mut *mut cgroup_subsys_state;
+}
+extern "C" {
+    pub fn shim_css_set_subsys_user(css_set: *mut css_set) -> *mut *mut cgroup_subsys_state;
+}
+extern "C" {
+    pub fn shim_css_set_subsys_exists(css_set: *mut css_set) -> bool;
+}
+extern "C" {
+    pub fn shim_css_set_dfl_cgrp(css_set: *mut css_set) -> *mut cgroup;
+}
+extern "C" {
+    pub fn shim_css_set_dfl_cgrp_user(css_set: *mut css_set) -> *mut cgroup;
+}
+extern "C" {
+    pub fn shim_css_set_dfl_cgrp_exists(css_set: *mut css_set) -> bool;
+}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct fdtable {
@@ -744,6 +970,48 @@ extern "C" {
     pub fn shim_files_struct_fdt_exists(files_struct: *mut files_struct) -> bool;
 }
 #[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct fs_struct {
+    pub pwd: path,
+}
+extern "C" {
+    pub fn shim_fs_struct_pwd(fs_struct: *mut fs_struct) -> *mut path;
+}
+extern "C" {
+    pub fn shim_fs_struct_pwd_user(fs_struct: *mut fs_struct) -> *mut path;
+}
+extern "C" {
+    pub fn shim_fs_struct_pwd_exists(fs_struct: *mut fs_struct) -> bool;
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct tty_struct {
+    pub name: [::core::ffi::c_char; 64usize],
+}
+extern "C" {
+    pub fn shim_tty_struct_name(tty_struct: *mut tty_struct) -> *mut ::core::ffi::c_char;
+}
+extern "C" {
+    pub fn shim_tty_struct_name_user(tty_struct: *mut tty_struct) -> *mut ::core::ffi::c_char;
+}
+extern "C" {
+    pub fn shim_tty_struct_name_exists(tty_struct: *mut tty_struct) -> bool;
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct signal_struct {
+    pub tty: *mut tty_struct,
+}
+extern "C" {
+    pub fn shim_signal_struct_tty(signal_struct: *mut signal_struct) -> *mut tty_struct;
+}
+extern "C" {
+    pub fn shim_signal_struct_tty_user(signal_struct: *mut signal_struct) -> *mut tty_struct;
+}
+extern "C" {
+    pub fn shim_signal_struct_tty_exists(signal_struct: *mut signal_struct) -> bool;
+}
+#[repr(C)]
 #[derive(Copy, Clone)]
 pub struct task_struct {
     pub flags: ::core::ffi::c_uint,
@@ -757,8 +1025,14 @@ pub struct task_struct {
     pub group_leader: *mut task_struct,
     pub mm: *mut mm_struct,
     pub files: *mut files_struct,
+    pub fs: *mut fs_struct,
+    pub signal: *mut signal_struct,
     pub nsproxy: *mut nsproxy,
     pub sched_task_group: *mut task_group,
+    pub cgroups: *mut css_set,
+    pub loginuid: kuid_t,
+    pub sessionid: ::core::ffi::c_uint,
+    pub io_uring: *mut ::core::ffi::c_void,
 }
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -884,6 +1158,24 @@ extern "C" {
 extern "C" {
     pub fn shim_task_struct_files_exists(task_struct: *mut task_struct) -> bool;
 }
+extern "C" {
+    pub fn shim_task_struct_fs(task_struct: *mut task_struct) -> *mut fs_struct;
+}
+extern "C" {
+    pub fn shim_task_struct_fs_user(task_struct: *mut task_struct) -> *mut fs_struct;
+}
+extern "C" {
+    pub fn shim_task_struct_fs_exists(task_struct: *mut task_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_task_struct_signal(task_struct: *mut task_struct) -> *mut signal_struct;
+}
+extern "C" {
+    pub fn shim_task_struct_signal_user(task_struct: *mut task_struct) -> *mut signal_struct;
+}
+extern "C" {
+    pub fn shim_task_struct_signal_exists(task_struct: *mut task_struct) -> bool;
+}
 extern "C" {
     pub fn shim_task_struct_nsproxy(task_struct: *mut task_struct) -> *mut nsproxy;
 }
@@ -903,6 +1195,41 @@ extern "C" {
 extern "C" {
     pub fn shim_task_struct_sched_task_group_exists(task_struct: *mut task_struct) -> bool;
 }
+extern "C" {
+    pub fn shim_task_struct_cgroups(task_struct: *mut task_struct) -> *mut css_set;
+}
+extern "C" {
+    pub fn shim_task_struct_cgroups_user(task_struct: *mut task_struct) -> *mut css_set;
+}
+extern "C" {
+    pub fn shim_task_struct_cgroups_exists(task_struct: *mut task_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_task_struct_loginuid(task_struct: *mut task_struct) -> uid_t;
+}
+extern "C" {
+    pub fn shim_task_struct_loginuid_exists(task_struct: *mut task_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_task_struct_sessionid(task_struct: *mut task_struct) -> ::core::ffi::c_uint;
+}
+extern "C" {
+    pub fn shim_task_struct_sessionid_user(task_struct: *mut task_struct) -> ::core::ffi::c_uint;
+}
+extern "C" {
+    pub fn shim_task_struct_sessionid_exists(task_struct: *mut task_struct) -> bool;
+}
+extern "C" {
+    pub fn shim_task_struct_io_uring(task_struct: *mut task_struct) -> *mut ::core::ffi::c_void;
+}
+extern "C" {
+    pub fn shim_task_struct_io_uring_user(
+        task_struct: *mut task_struct,
+    ) -> *mut ::core::ffi::c_void;
+}
+extern "C" {
+    pub fn shim_task_struct_io_uring_exists(task_struct: *mut task_struct) -> bool;
+}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct bpf_ksym {
@@ -1313,6 +1640,33 @@ extern "C" {
     pub fn shim_sockaddr_in6_sin6_addr_exists(sockaddr_in6: *mut sockaddr_in6) -> bool;
 }
 #[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct sockaddr_un {
+    pub sun_family: __kernel_sa_family_t,
+    pub sun_path: [::core::ffi::c_char; 108usize],
+}
+extern "C" {
+    pub fn shim_sockaddr_un_sun_family(sockaddr_un: *mut sockaddr_un) -> ::core::ffi::c_ushort;
+}
+extern "C" {
+    pub fn shim_sockaddr_un_sun_family_user(sockaddr_un: *mut sockaddr_un)
+        -> ::core::ffi::c_ushort;
+}
+extern "C" {
+    pub fn shim_sockaddr_un_sun_family_exists(sockaddr_un: *mut sockaddr_un) -> bool;
+}
+extern "C" {
+    pub fn shim_sockaddr_un_sun_path(sockaddr_un: *mut sockaddr_un) -> *mut ::core::ffi::c_char;
+}
+extern "C" {
+    pub fn shim_sockaddr_un_sun_path_user(
+        sockaddr_un: *mut sockaddr_un,
+    ) -> *mut ::core::ffi::c_char;
+}
+extern "C" {
+    pub fn shim_sockaddr_un_sun_path_exists(sockaddr_un: *mut sockaddr_un) -> bool;
+}
+#[repr(C)]
 #[derive(Copy, Clone)]
 pub struct sock_common {
     pub __bindgen_anon_1: sock_common__bindgen_ty_1,
@@ -1474,12 +1828,32 @@ extern "C" {
     pub fn shim_sk_buff_head_qlen_exists(sk_buff_head: *mut sk_buff_head) -> bool;
 }
 #[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct upid {
+    pub nr: ::core::ffi::c_int,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct pid {
+    pub numbers: [upid; 1usize],
+}
+extern "C" {
+    pub fn shim_pid_nr(pid: *mut pid) -> ::core::ffi::c_int;
+}
+extern "C" {
+    pub fn shim_pid_nr_user(pid: *mut pid) -> ::core::ffi::c_int;
+}
+extern "C" {
+    pub fn shim_pid_nr_exists(pid: *mut pid) -> bool;
+}
+#[repr(C)]
 #[derive(Copy, Clone)]
 pub struct sock {
     pub __sk_common: sock_common,
     pub sk_protocol: __u8,
     pub sk_type: __u16,
     pub sk_receive_queue: sk_buff_head,
+    pub sk_peer_pid: *mut pid,
 }
 extern "C" {
     pub fn shim_sock___sk_common(sock: *mut sock) -> *mut sock_common;
@@ -1511,6 +1885,15 @@ extern "C" {
 extern "C" {
     pub fn shim_sock_sk_receive_queue_exists(sock: *mut sock) -> bool;
 }
+extern "C" {
+    pub fn shim_sock_sk_peer_pid(sock: *mut sock) -> *mut pid;
+}
+extern "C" {
+    pub fn shim_sock_sk_peer_pid_user(sock: *mut sock) -> *mut pid;
+}
+extern "C" {
+    pub fn shim_sock_sk_peer_pid_exists(sock: *mut sock) -> bool;
+}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct socket {
@@ -1827,3 +2210,112 @@ extern "C" {
 extern "C" {
     pub fn shim_kernel_clone_args_flags_exists(kernel_clone_args: *mut kernel_clone_args) -> bool;
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct filename {
+    pub name: *const ::core::ffi::c_char,
+}
+extern "C" {
+    pub fn shim_filename_name(filename: *mut filename) -> *const ::core::ffi::c_char;
+}
+extern "C" {
+    pub fn shim_filename_name_user(filename: *mut filename) -> *const ::core::ffi::c_char;
+}
+extern "C" {
+    pub fn shim_filename_name_exists(filename: *mut filename) -> bool;
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct io_kiocb {
+    pub file: *mut file,
+    pub opcode: u8_,
+}
+extern "C" {
+    pub fn shim_io_kiocb_file(io_kiocb: *mut io_kiocb) -> *mut file;
+}
+extern "C" {
+    pub fn shim_io_kiocb_file_user(io_kiocb: *mut io_kiocb) -> *mut file;
+}
+extern "C" {
+    pub fn shim_io_kiocb_file_exists(io_kiocb: *mut io_kiocb) -> bool;
+}
+extern "C" {
+    pub fn shim_io_kiocb_opcode(io_kiocb: *mut io_kiocb) -> ::core::ffi::c_uchar;
+}
+extern "C" {
+    pub fn shim_io_kiocb_opcode_user(io_kiocb: *mut io_kiocb) -> ::core::ffi::c_uchar;
+}
+extern "C" {
+    pub fn shim_io_kiocb_opcode_exists(io_kiocb: *mut io_kiocb) -> bool;
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct io_open {
+    pub file: *mut file,
+    pub dfd: ::core::ffi::c_int,
+    pub filename: *mut filename,
+}
+extern "C" {
+    pub fn shim_io_open_dfd(io_open: *mut io_open) -> ::core::ffi::c_int;
+}
+extern "C" {
+    pub fn shim_io_open_dfd_user(io_open: *mut io_open) -> ::core::ffi::c_int;
+}
+extern "C" {
+    pub fn shim_io_open_dfd_exists(io_open: *mut io_open) -> bool;
+}
+extern "C" {
+    pub fn shim_io_open_filename(io_open: *mut io_open) -> *mut filename;
+}
+extern "C" {
+    pub fn shim_io_open_filename_user(io_open: *mut io_open) -> *mut filename;
+}
+extern "C" {
+    pub fn shim_io_open_filename_exists(io_open: *mut io_open) -> bool;
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct perf_event_attr {
+    pub config1: __u64,
+    pub config2: __u64,
+}
+extern "C" {
+    pub fn shim_perf_event_attr_config1(
+        perf_event_attr: *mut perf_event_attr,
+    ) -> ::core::ffi::c_ulonglong;
+}
+extern "C" {
+    pub fn shim_perf_event_attr_config1_user(
+        perf_event_attr: *mut perf_event_attr,
+    ) -> ::core::ffi::c_ulonglong;
+}
+extern "C" {
+    pub fn shim_perf_event_attr_config1_exists(perf_event_attr: *mut perf_event_attr) -> bool;
+}
+extern "C" {
+    pub fn shim_perf_event_attr_config2(
+        perf_event_attr: *mut perf_event_attr,
+    ) -> ::core::ffi::c_ulonglong;
+}
+extern "C" {
+    pub fn shim_perf_event_attr_config2_user(
+        perf_event_attr: *mut perf_event_attr,
+    ) -> ::core::ffi::c_ulonglong;
+}
+extern "C" {
+    pub fn shim_perf_event_attr_config2_exists(perf_event_attr: *mut perf_event_attr) -> bool;
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct perf_event {
+    pub attr: perf_event_attr,
+}
+extern "C" {
+    pub fn shim_perf_event_attr(perf_event: *mut perf_event) -> *mut perf_event_attr;
+}
+extern "C" {
+    pub fn shim_perf_event_attr_user(perf_event: *mut perf_event) -> *mut perf_event_attr;
+}
+extern "C" {
+    pub fn shim_perf_event_attr_exists(perf_event: *mut perf_event) -> bool;
+}
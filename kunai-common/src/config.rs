@@ -1,4 +1,4 @@
-use crate::{bpf_events, macros::bpf_target_code, macros::not_bpf_target_code};
+use crate::{bpf_events, macros::bpf_target_code, macros::not_bpf_target_code, path::Path};
 
 not_bpf_target_code! {
     mod user;
@@ -54,10 +54,252 @@ impl Filter {
     }
 }
 
+pub const MAX_PATH_PATTERNS: usize = 8;
+pub const MAX_PATTERN_LEN: usize = 64;
+// maximum number of steps done to match a path against a pattern
+const MAX_MATCH_STEPS: usize = 256;
+
+/// Path pattern matching the beginning of a path. A `*` matches
+/// any sequence of characters but `/`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PathPattern {
+    len: u8,
+    pattern: [u8; MAX_PATTERN_LEN],
+}
+
+impl PathPattern {
+    const EMPTY: Self = Self {
+        len: 0,
+        pattern: [0; MAX_PATTERN_LEN],
+    };
+
+    /// Creates a new pattern, None is returned if pattern is empty or too long
+    pub fn new<T: AsRef<[u8]>>(pattern: T) -> Option<Self> {
+        let pattern = pattern.as_ref();
+        if pattern.is_empty() || pattern.len() > MAX_PATTERN_LEN {
+            return None;
+        }
+        let mut p = Self::EMPTY;
+        p.pattern[..pattern.len()].copy_from_slice(pattern);
+        p.len = pattern.len() as u8;
+        Some(p)
+    }
+
+    #[inline(always)]
+    fn get(&self, i: usize) -> Option<u8> {
+        if i < self.len as usize && i < MAX_PATTERN_LEN {
+            return Some(self.pattern[i]);
+        }
+        None
+    }
+
+    /// Returns true if path starts with pattern
+    // Not inlined so that the matching loop is emitted once as a BPF
+    // subprogram rather than copied for every pattern of every filter in
+    // vfs_read, vfs_write and rw_verify_area. The worst case is
+    // MAX_MATCH_STEPS steps for each of the 3 * MAX_PATH_PATTERNS patterns
+    // (include, exclude and tamper) checked by a program, that is
+    // 6144 loop iterations, kept well below the 1M processed instructions
+    // budget (BPF_COMPLEXITY_LIMIT_INSNS) of the kernels supporting bounded loops.
+    #[inline(never)]
+    pub fn matches(&self, path: &Path) -> bool {
+        let (mut i, mut j) = (0, 0);
+        // position of the last star met in pattern
+        let mut star = None;
+        // position in path where the last star started to match
+        let mut mark = 0;
+
+        for _ in 0..MAX_MATCH_STEPS {
+            let Some(pc) = self.get(i) else {
+                // all the pattern has been consumed
+                return true;
+            };
+
+            if pc == b'*' {
+                star = Some(i);
+                mark = j;
+                i += 1;
+                continue;
+            }
+
+            if j < path.len() && path.get_byte(j) == Ok(pc) {
+                i += 1;
+                j += 1;
+                continue;
+            }
+
+            // we extend the star match by one character but
+            // star is not allowed to match a path separator
+            match star {
+                Some(s) if mark < path.len() && path.get_byte(mark).is_ok_and(|c| c != b'/') => {
+                    mark += 1;
+                    j = mark;
+                    i = s + 1;
+                }
+                _ => return false,
+            }
+        }
+
+        false
+    }
+}
+
+/// Filter used to classify file accesses. Paths matching
+/// one of the include patterns are configuration files,
+/// other paths matching one of the read (resp. write) exclude
+/// patterns do not generate read (resp. write) events.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PathFilter {
+    include_len: u8,
+    include: [PathPattern; MAX_PATH_PATTERNS],
+    exclude_read_len: u8,
+    exclude_read: [PathPattern; MAX_PATH_PATTERNS],
+    exclude_write_len: u8,
+    exclude_write: [PathPattern; MAX_PATH_PATTERNS],
+}
+
+impl Default for PathFilter {
+    fn default() -> Self {
+        Self {
+            include_len: 0,
+            include: [PathPattern::EMPTY; MAX_PATH_PATTERNS],
+            exclude_read_len: 0,
+            exclude_read: [PathPattern::EMPTY; MAX_PATH_PATTERNS],
+            exclude_write_len: 0,
+            exclude_write: [PathPattern::EMPTY; MAX_PATH_PATTERNS],
+        }
+    }
+}
+
+impl PathFilter {
+    fn push<T: AsRef<[u8]>>(
+        patterns: &mut [PathPattern; MAX_PATH_PATTERNS],
+        len: &mut u8,
+        pattern: T,
+    ) -> bool {
+        let i = *len as usize;
+        match PathPattern::new(pattern) {
+            Some(p) if i < MAX_PATH_PATTERNS => {
+                patterns[i] = p;
+                *len += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Adds an include pattern, returns false if it cannot be added
+    pub fn include<T: AsRef<[u8]>>(&mut self, pattern: T) -> bool {
+        Self::push(&mut self.include, &mut self.include_len, pattern)
+    }
+
+    /// Adds a pattern excluded from read events, returns false if it cannot be added
+    pub fn exclude_read<T: AsRef<[u8]>>(&mut self, pattern: T) -> bool {
+        Self::push(&mut self.exclude_read, &mut self.exclude_read_len, pattern)
+    }
+
+    /// Adds a pattern excluded from write events, returns false if it cannot be added
+    pub fn exclude_write<T: AsRef<[u8]>>(&mut self, pattern: T) -> bool {
+        Self::push(
+            &mut self.exclude_write,
+            &mut self.exclude_write_len,
+            pattern,
+        )
+    }
+
+    #[inline(always)]
+    fn any_match(patterns: &[PathPattern; MAX_PATH_PATTERNS], len: u8, path: &Path) -> bool {
+        for (i, p) in patterns.iter().enumerate() {
+            if i >= len as usize {
+                break;
+            }
+            if p.matches(path) {
+                return true;
+            }
+        }
+        false
+    }
+
+    #[inline(always)]
+    pub fn is_included(&self, path: &Path) -> bool {
+        Self::any_match(&self.include, self.include_len, path)
+    }
+
+    #[inline(always)]
+    pub fn is_read_excluded(&self, path: &Path) -> bool {
+        Self::any_match(&self.exclude_read, self.exclude_read_len, path)
+    }
+
+    #[inline(always)]
+    pub fn is_write_excluded(&self, path: &Path) -> bool {
+        Self::any_match(&self.exclude_write, self.exclude_write_len, path)
+    }
+}
+
 /// Structure holding configuration to use in eBPF programs
 #[derive(Debug, Clone, Copy)]
 pub struct BpfConfig {
     pub loader: Loader,
     pub filter: Filter,
     pub send_data_min_len: u64,
+    // capture environment of executed processes
+    pub capture_env: bool,
+    // classification of file read/write events
+    pub config_files: PathFilter,
+    // files whose writes generate kernel_tamper events
+    pub tamper_files: PathFilter,
+    // address of bpf_prog_fops used to recognize bpf program
+    // files, programs are not resolved when it is zero
+    pub bpf_prog_fops: u64,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::path::Mode;
+
+    fn path(s: &str) -> Path {
+        let mut p = Path::default();
+        p.copy_from_str(s, Mode::Prepend).unwrap();
+        p
+    }
+
+    #[test]
+    fn test_path_pattern() {
+        let p = PathPattern::new("/etc/").unwrap();
+        assert!(p.matches(&path("/etc/passwd")));
+        assert!(!p.matches(&path("/etc")));
+        assert!(!p.matches(&path("/tmp/etc/passwd")));
+
+        let p = PathPattern::new("/home/*/.ssh/authorized_keys").unwrap();
+        assert!(p.matches(&path("/home/user/.ssh/authorized_keys")));
+        assert!(p.matches(&path("/home/user/.ssh/authorized_keys2")));
+        assert!(!p.matches(&path("/home/user/other/.ssh/authorized_keys")));
+        assert!(!p.matches(&path("/home/user/.ssh/known_hosts")));
+
+        let p = PathPattern::new("/etc/*.conf").unwrap();
+        assert!(p.matches(&path("/etc/a.b.conf")));
+        assert!(!p.matches(&path("/etc/sub/a.conf")));
+
+        assert!(PathPattern::new("").is_none());
+        assert!(PathPattern::new([b'a'; MAX_PATTERN_LEN + 1]).is_none());
+    }
+
+    #[test]
+    fn test_path_filter() {
+        let mut f = PathFilter::default();
+        assert!(f.include("/etc/"));
+        assert!(f.exclude_read("/proc/"));
+        assert!(f.is_included(&path("/etc/shadow")));
+        assert!(!f.is_included(&path("/proc/self/status")));
+        assert!(f.is_read_excluded(&path("/proc/self/status")));
+        assert!(!f.is_write_excluded(&path("/proc/self/status")));
+
+        for _ in 1..MAX_PATH_PATTERNS {
+            assert!(f.include("/x"));
+        }
+        assert!(!f.include("/x"));
+    }
 }
@@ -1,3 +1,4 @@
+pub const AF_UNIX: u32 = 1;
 pub const AF_INET: u32 = 2;
 pub const AF_INET6: u32 = 10;
 
@@ -6,3 +7,6 @@ pub const PROT_READ: u32 = 1;
 pub const PROT_WRITE: u32 = 2;
 pub const PROT_EXEC: u32 = 4;
 pub const PROT_NONE: u32 = 0;
+
+// flag constants from mman.h
+pub const MAP_ANONYMOUS: u32 = 0x20;
@@ -1,6 +1,6 @@
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
-use super::{IpPort, IpType, SaFamily, SockType, SocketInfo};
+use super::{IpPort, IpType, NetlinkProtocol, SaFamily, SockType, SocketInfo};
 
 impl From<IpPort> for IpAddr {
     fn from(value: IpPort) -> Self {
@@ -30,3 +30,12 @@ impl SocketInfo {
         }
     }
 }
+
+impl NetlinkProtocol {
+    pub fn protocol_to_string(proto: u16) -> String {
+        match Self::try_from_uint(proto) {
+            Ok(p) => p.as_str().into(),
+            Err(_) => format!("unknown({})", proto),
+        }
+    }
+}
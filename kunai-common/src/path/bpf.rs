@@ -42,14 +42,31 @@ impl Path {
             return Ok(());
         }
 
-        let mut entry = p.dentry().ok_or(Error::DentryMissing)?;
+        let entry = p.dentry().ok_or(Error::DentryMissing)?;
+        let mnt = p.mnt().ok_or(Error::RFPathMnt)?;
+
+        self.core_resolve_dentry(&mnt, entry, max_depth)
+    }
+
+    /// Resolves the path of a dentry found in mnt. It is useful
+    /// when a kernel function gets a dentry without its path.
+    #[inline(always)]
+    pub unsafe fn core_resolve_dentry(
+        &mut self,
+        mnt: &co_re::vfsmount,
+        mut entry: co_re::dentry,
+        max_depth: u16,
+    ) -> Result<()> {
+        if mnt.is_null() || entry.is_null() {
+            return Ok(());
+        }
+
         let d_inode = core_read_kernel!(entry, d_inode).ok_or(Error::DentryDinode)?;
 
         // initialization
         self.mode = Mode::Prepend;
         self.init_from_inode(&d_inode)?;
 
-        let mnt = p.mnt().ok_or(Error::RFPathMnt)?;
         let mut mount = mnt.mount();
 
         let mut mnt_parent = mount.mnt_parent().ok_or(Error::MntParentMissing)?;
@@ -25,13 +25,18 @@ mod execve;
 mod exit;
 mod fs;
 mod init_module;
+mod io_uring;
+mod kernel_tamper;
 mod kill;
 mod lsm;
+mod memfd;
 mod mmap;
 mod mprotect;
 mod prctl;
+mod process_access;
 mod schedule;
 mod send_data;
+mod socket;
 mod syscore_resume;
 
 /// macro to track ignored results
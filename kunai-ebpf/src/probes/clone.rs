@@ -84,14 +84,13 @@ unsafe fn try_enter_wake_up_new_task(ctx: &ProbeContext) -> ProbeResult<()> {
                 event
                     .data
                     .argv
-                    .read_user_at(arg_start as *const u8, arg_len as u32),
+                    .read_user_chunks_at(arg_start as *const u8, arg_len as u32),
                 |_| warn_msg!(ctx, "failed to read argv")
             ));
         }
 
         // cgroup parsing
-        let cgroup = core_read_kernel!(new_task, sched_task_group, css, cgroup)?;
-        ignore_result!(event.data.cgroup.resolve(cgroup));
+        ignore_result!(event.data.cgroup.resolve_task(new_task));
 
         pipe_event(ctx, event);
     }
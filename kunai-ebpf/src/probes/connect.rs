@@ -1,11 +1,16 @@
+use super::io_uring::in_io_uring;
 use super::*;
 
-use aya_ebpf::programs::ProbeContext;
+use aya_ebpf::{maps::LruHashMap, programs::ProbeContext};
 use kunai_common::{
     kprobe::{KProbeEntryContext, ProbeFn},
     net::IpPort,
 };
 
+// pid of the task listening on the AF_UNIX socket a task connects to
+#[map]
+static mut UNIX_PEERS: LruHashMap<u64, u32> = LruHashMap::with_max_entries(1024, 0);
+
 #[kprobe(function = "__sys_connect")]
 pub fn net_enter_sys_connect(ctx: ProbeContext) -> u32 {
     unsafe { ignore_result!(ProbeFn::net_sys_connect.save_ctx(&ctx)) }
@@ -30,8 +35,66 @@ pub fn net_exit_sys_connect(ctx: ProbeContext) -> u32 {
     rc
 }
 
+const EAGAIN: i32 = 11;
 const EINPROGRESS: i32 = 115;
 
+// offset of sun_path in struct sockaddr_un
+const SUN_PATH_OFFSET: i32 = 2;
+
+/// Returns the length of sun_path given the length of the address
+/// passed to connect. For abstract sockets it is the only way to
+/// know the length of the name as it may contain NULL bytes.
+#[inline(always)]
+fn sun_path_len(addrlen: i32) -> u32 {
+    (addrlen - SUN_PATH_OFFSET).clamp(0, UNIX_PATH_MAX as i32) as u32
+}
+
+/// Returns the pid of the task listening on the AF_UNIX socket the
+/// current task connected to, or 0 if it is unknown (i.e. connection
+/// failed or datagram socket).
+#[inline(always)]
+unsafe fn take_unix_peer() -> u32 {
+    let key = bpf_task_tracking_id();
+    let pid = UNIX_PEERS.get(&key).copied().unwrap_or_default();
+    // cleanup no need to handle failure
+    ignore_result!(UNIX_PEERS.remove(&key));
+    pid
+}
+
+// called by unix_stream_connect once the listening socket is found
+#[kprobe(function = "security_unix_stream_connect")]
+pub fn net_security_unix_stream_connect(ctx: ProbeContext) -> u32 {
+    match unsafe { try_security_unix_stream_connect(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_security_unix_stream_connect(ctx: &ProbeContext) -> ProbeResult<()> {
+    // peer credentials of a listening socket are the ones of
+    // the task which called listen
+    let other = co_re::sock::from_ptr(kprobe_arg!(ctx, 1)?);
+    let peer = core_read_kernel!(other, sk_peer_pid)?;
+
+    if peer.is_null() {
+        return Ok(());
+    }
+
+    UNIX_PEERS
+        .insert(
+            &bpf_task_tracking_id(),
+            &(core_read_kernel!(peer, nr)? as u32),
+            0,
+        )
+        .map_err(|_| MapError::InsertFailure)?;
+
+    Ok(())
+}
+
 unsafe fn try_exit_connect(
     entry_ctx: &mut KProbeEntryContext,
     exit_ctx: &ProbeContext,
@@ -47,13 +110,13 @@ unsafe fn try_exit_connect(
 
     event.init_from_current_task(Type::Connect)?;
 
-    let ip_port = match sa_family {
+    match sa_family {
         AF_INET => {
             let in_addr: co_re::sockaddr_in = addr.into();
             let ip = core_read_user!(in_addr, s_addr)?.to_be();
             let port = core_read_user!(in_addr, sin_port)?.to_be();
 
-            IpPort::new_v4_from_be(ip, port)
+            event.data.ip_port = IpPort::new_v4_from_be(ip, port);
         }
         AF_INET6 => {
             let in6_addr: co_re::sockaddr_in6 = addr.into();
@@ -61,14 +124,115 @@ unsafe fn try_exit_connect(
             let port = core_read_user!(in6_addr, sin6_port)?.to_be();
             // in theory we don't need to reverse addr for ipv6 as we read
             // data which is already big endian
-            IpPort::new_v6_from_be(core_read_user!(ip, addr32)?, port)
+            event.data.ip_port = IpPort::new_v6_from_be(core_read_user!(ip, addr32)?, port);
+        }
+        AF_UNIX => {
+            let un_addr: co_re::sockaddr_un = addr.into();
+            let addrlen: i32 = kprobe_arg!(entry_ctx, 2)?;
+
+            event
+                .data
+                .sun_path
+                .read_user_at(core_read_user!(un_addr, sun_path)?, sun_path_len(addrlen))?;
+            event.data.peer_pid = take_unix_peer();
+        }
+        _ => return Ok(()),
+    };
+
+    event.data.family = sa_family;
+    event.data.connected = rc == 0 || rc == -EINPROGRESS;
+
+    pipe_event(exit_ctx, event);
+
+    Ok(())
+}
+
+// io_uring connect operations do not go through __sys_connect
+// but call __sys_connect_file with an address copied in kernel
+#[kprobe(function = "__sys_connect_file")]
+pub fn net_enter_sys_connect_file(ctx: ProbeContext) -> u32 {
+    unsafe {
+        // connect syscalls are handled by __sys_connect probes
+        if in_io_uring() {
+            ignore_result!(ProbeFn::net_sys_connect_file.save_ctx(&ctx))
+        }
+    }
+    0
+}
+
+#[kretprobe(function = "__sys_connect_file")]
+pub fn net_exit_sys_connect_file(ctx: ProbeContext) -> u32 {
+    if !unsafe { in_io_uring() } {
+        return 0;
+    }
+
+    let rc = match unsafe {
+        ProbeFn::net_sys_connect_file
+            .restore_ctx()
+            .map_err(ProbeError::from)
+            .and_then(|ent_ctx| try_exit_connect_file(ent_ctx, &ctx))
+    } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    };
+    ignore_result!(unsafe { ProbeFn::net_sys_connect_file.clean_ctx() });
+    rc
+}
+
+unsafe fn try_exit_connect_file(
+    entry_ctx: &mut KProbeEntryContext,
+    exit_ctx: &ProbeContext,
+) -> ProbeResult<()> {
+    let rc = exit_ctx.ret().unwrap_or(-1);
+
+    // io_uring retries operations returning -EAGAIN so
+    // we will get another chance to report the connection
+    if rc == -EAGAIN {
+        return Ok(());
+    }
+
+    let entry_ctx = &entry_ctx.probe_context();
+    let addr = co_re::sockaddr::from_ptr(kprobe_arg!(entry_ctx, 1)?);
+    let sa_family = core_read_kernel!(addr, sa_family)?;
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<ConnectEvent>()?;
+
+    event.init_from_current_task(Type::Connect)?;
+
+    match sa_family {
+        AF_INET => {
+            let in_addr: co_re::sockaddr_in = addr.into();
+            let ip = core_read_kernel!(in_addr, s_addr)?.to_be();
+            let port = core_read_kernel!(in_addr, sin_port)?.to_be();
+
+            event.data.ip_port = IpPort::new_v4_from_be(ip, port);
+        }
+        AF_INET6 => {
+            let in6_addr: co_re::sockaddr_in6 = addr.into();
+            let ip = core_read_kernel!(in6_addr, sin6_addr)?;
+            let port = core_read_kernel!(in6_addr, sin6_port)?.to_be();
+            event.data.ip_port = IpPort::new_v6_from_be(core_read_kernel!(ip, addr32)?, port);
+        }
+        AF_UNIX => {
+            let un_addr: co_re::sockaddr_un = addr.into();
+            let addrlen: i32 = kprobe_arg!(entry_ctx, 2)?;
+
+            event
+                .data
+                .sun_path
+                .read_kernel_at(core_read_kernel!(un_addr, sun_path)?, sun_path_len(addrlen))?;
+            event.data.peer_pid = take_unix_peer();
         }
         _ => return Ok(()),
     };
 
     event.data.family = sa_family;
-    event.data.ip_port = ip_port;
     event.data.connected = rc == 0 || rc == -EINPROGRESS;
+    event.data.io_uring = true;
 
     pipe_event(exit_ctx, event);
 
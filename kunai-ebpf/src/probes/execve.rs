@@ -1,13 +1,18 @@
+use super::memfd::fileless_flags;
 use super::*;
 
+use core::cmp::min;
+
+use aya_ebpf::helpers::bpf_probe_read_user;
 use aya_ebpf::maps::LruHashMap;
 use aya_ebpf::programs::{ProbeContext, TracePointContext};
 use aya_ebpf::EbpfContext;
 use co_re::task_struct;
-use kunai_common::syscalls::SysExitArgs;
+use kunai_common::net::{IpPort, SaFamily, SocketInfo};
+use kunai_common::syscalls::{SysEnterArgs, SysExitArgs};
 
 #[map]
-static mut EXECVE_TRACKING: LruHashMap<u128, ExecveEvent> = LruHashMap::with_max_entries(4096, 0);
+static mut EXECVE_TRACKING: LruHashMap<u128, ExecveEvent> = LruHashMap::with_max_entries(1024, 0);
 
 // this guy gives us the real executable path (i.e. a script for instance)
 // we need to hook at another point in order to get the interpreter. For
@@ -48,8 +53,17 @@ unsafe fn try_security_bprm_check(ctx: &ProbeContext) -> ProbeResult<()> {
             .data
             .executable
             .core_resolve_file(&file, MAX_PATH_DEPTH)?;
+
+        event.data.fileless = fileless_flags(&file, &event.data.executable).unwrap_or(0);
     }
 
+    // cwd, tty and stdio are inherited by the new program so we resolve
+    // them here rather than at exit to split the work across probes
+    ignore_result!(inspect_err!(
+        resolve_exec_context(event, ts),
+        |_| warn_msg!(ctx, "failed to resolve execution context")
+    ));
+
     EXECVE_TRACKING
         .insert(&task_uuid, event, 0)
         .map_err(|_| MapError::InsertFailure)?;
@@ -57,6 +71,67 @@ unsafe fn try_security_bprm_check(ctx: &ProbeContext) -> ProbeResult<()> {
     Ok(())
 }
 
+#[inline(always)]
+unsafe fn resolve_exec_context(event: &mut ExecveEvent, ts: task_struct) -> ProbeResult<()> {
+    let pwd = core_read_kernel!(ts, fs, pwd)?;
+    event.data.cwd.core_resolve(&pwd, MAX_PATH_DEPTH)?;
+
+    // tty is null for processes without controlling terminal
+    if let Some(tty) = ts.signal().and_then(|s| s.tty()).filter(|t| !t.is_null()) {
+        event
+            .data
+            .tty
+            .read_kernel_str_bytes(core_read_kernel!(tty, name)?)?;
+    }
+
+    // a failure on one descriptor must not prevent resolving the others
+    for fd in 0..event.data.stdio.len() {
+        ignore_result!(resolve_std_fd(&mut event.data.stdio[fd], ts, fd));
+    }
+
+    Ok(())
+}
+
+#[inline(always)]
+unsafe fn resolve_std_fd(std_fd: &mut StdFd, ts: task_struct, fd: usize) -> ProbeResult<()> {
+    let file = match ts.get_fd(fd) {
+        Some(f) if !f.is_null() => f,
+        // fd is not open
+        _ => return Ok(()),
+    };
+
+    let ty = FdType::from_mode(core_read_kernel!(file, f_inode, file_type)?);
+    std_fd.ty = ty as u8;
+
+    match ty {
+        FdType::Socket => {
+            let socket =
+                co_re::socket::from_ptr(core_read_kernel!(file, private_data)? as *const _);
+            let sk = core_read_kernel!(socket, sk)?;
+            std_fd.socket = SocketInfo::try_from(sk)?;
+
+            if std_fd.socket.is_family(SaFamily::AF_INET)
+                || std_fd.socket.is_family(SaFamily::AF_INET6)
+            {
+                std_fd.peer =
+                    IpPort::from_sock_common_foreign_ip(core_read_kernel!(sk, sk_common)?)
+                        .unwrap_or_default();
+            } else if std_fd.socket.is_family(SaFamily::AF_UNIX) {
+                // peer pid is set on connected stream sockets and socket pairs
+                let peer = core_read_kernel!(sk, sk_peer_pid)?;
+                if !peer.is_null() {
+                    std_fd.peer_pid = core_read_kernel!(peer, nr)? as u32;
+                }
+            }
+        }
+        // pipe paths do not bring any information
+        FdType::Pipe => {}
+        _ => std_fd.path.core_resolve_file(&file, MAX_PATH_DEPTH)?,
+    }
+
+    Ok(())
+}
+
 #[map]
 static mut BPRM_EXECVE_ARGS: LruHashMap<u64, co_re::linux_binprm> =
     LruHashMap::with_max_entries(1024, 0);
@@ -93,6 +168,35 @@ unsafe fn execve_event<C: EbpfContext>(ctx: &C, rc: i32) -> ProbeResult<()> {
 
     let current = task_struct::current();
 
+    let mm = core_read_kernel!(ts, mm)?;
+    let arg_start = core_read_kernel!(mm, arg_start)?;
+    let arg_len = core_read_kernel!(mm, arg_len)?;
+
+    // parsing argv
+    if event
+        .data
+        .argv
+        .read_user_chunks_at(arg_start as *const u8, arg_len as u32)
+        .is_err()
+    {
+        warn_msg!(ctx, "failed to read argv")
+    }
+
+    // what does not fit in the event is streamed, this must be done before
+    // the event gets initialized so that chunks are ordered before it
+    let mut argv_len = event.data.argv.len() as u64;
+    let stream = get_cfg!()
+        .map(|c| c.is_event_enabled(Type::Execve) || c.is_event_enabled(Type::ExecveScript))
+        .unwrap_or_default();
+    if stream && argv_len == MAX_EXECVE_ARGV_SIZE as u64 && argv_len < arg_len {
+        match stream_argv(ctx, current, arg_start, arg_len) {
+            Ok(n) => argv_len += n,
+            Err(_) => warn_msg!(ctx, "failed to stream argv"),
+        }
+    }
+    // argv might not fit in buffers or be partially paged out
+    event.data.argv_truncated = argv_len < arg_len;
+
     // getting nodename first as we need the current task struct
     event.data.nodename.read_kernel_at(
         core_read_kernel!(current, nsproxy, uts_ns, name, nodename)?,
@@ -113,23 +217,25 @@ unsafe fn execve_event<C: EbpfContext>(ctx: &C, rc: i32) -> ProbeResult<()> {
 
     event.data.rc = rc;
 
-    let arg_start = core_read_kernel!(ts, mm, arg_start)?;
-    let arg_len = core_read_kernel!(ts, mm, arg_len)?;
+    // environment is filtered in userland
+    if get_cfg!().map(|c| c.capture_env).unwrap_or_default() {
+        let env_start = core_read_kernel!(mm, env_start)?;
+        let env_len = core_read_kernel!(mm, env_len)?;
 
-    // parsing argv
-    if event
-        .data
-        .argv
-        .read_user_at(arg_start as *const u8, arg_len as u32)
-        .is_err()
-    {
-        warn_msg!(ctx, "failed to read argv")
+        if event
+            .data
+            .env
+            .read_user_chunks_at(env_start as *const u8, env_len as u32)
+            .is_err()
+        {
+            warn_msg!(ctx, "failed to read env")
+        }
+        event.data.env_truncated = (event.data.env.len() as u64) < env_len;
     }
 
     // cgroup parsing
-    let cgroup = core_read_kernel!(ts, sched_task_group, css, cgroup)?;
     // we do not raise any error on cgroup parsing, we let a chance to userland to solve it
-    ignore_result!(event.data.cgroup.resolve(cgroup));
+    ignore_result!(event.data.cgroup.resolve_task(ts));
 
     pipe_event(ctx, event);
 
@@ -139,10 +245,55 @@ unsafe fn execve_event<C: EbpfContext>(ctx: &C, rc: i32) -> ProbeResult<()> {
     Ok(())
 }
 
+/// Streams the part of the command line not fitting in execve events as
+/// [ExecveArgvEvent]. It returns the number of bytes streamed.
+#[inline(always)]
+unsafe fn stream_argv<C: EbpfContext>(
+    ctx: &C,
+    ts: task_struct,
+    arg_start: u64,
+    arg_len: u64,
+) -> ProbeResult<u64> {
+    alloc::init()?;
+    let event = alloc::alloc_zero::<ExecveArgvEvent>()?;
+
+    event.init_from_task(Type::ExecveArgv, ts)?;
+
+    let mut off = MAX_EXECVE_ARGV_SIZE as u64;
+    for _ in 0..MAX_ARGV_CHUNKS {
+        if off >= arg_len {
+            break;
+        }
+
+        let size = min(arg_len - off, ARGV_CHUNK_SIZE as u64);
+        if event
+            .data
+            .chunk
+            .read_user_chunks_at((arg_start + off) as *const u8, size as u32)
+            .is_err()
+        {
+            break;
+        }
+
+        event.data.offset = off as u32;
+        pipe_event(ctx, event);
+
+        let read = event.data.chunk.len() as u64;
+        off += read;
+
+        // the rest of the chunk is paged out
+        if read < size {
+            break;
+        }
+    }
+
+    Ok(off - MAX_EXECVE_ARGV_SIZE as u64)
+}
+
 unsafe fn try_bprm_execve(ctx: &ProbeContext) -> ProbeResult<()> {
     let rc = ctx.ret().unwrap_or(-1);
 
-    // execve failed
+    // execve failed, this is handled at syscall exit
     if rc < 0 {
         return Ok(());
     }
@@ -177,9 +328,148 @@ pub unsafe fn try_sys_exit_execve(ctx: &TracePointContext) -> Result<(), ProbeEr
     let args = SysExitArgs::from_context(ctx)?;
     let rc = args.ret as i32;
 
+    // execve failed, this is handled by failed execution probes
     if rc < 0 {
         return Ok(());
     }
 
     execve_event(ctx, rc)
 }
+
+// maximum number of arguments we read from argv array of a failed execution
+const MAX_FAILED_ARGV_ENTRIES: usize = 64;
+
+#[map]
+static mut EXECVE_ARGS: LruHashMap<u64, ExecveArgs> = LruHashMap::with_max_entries(1024, 0);
+
+#[tracepoint(name = "sys_enter_execve", category = "syscalls")]
+pub fn execve_failed_syscalls_sys_enter_execve(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_enter_execve(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_sys_enter_execve(ctx: &TracePointContext) -> ProbeResult<()> {
+    let args = SysEnterArgs::<Execve>::from_context(ctx)?.args;
+    track_execve_args(args.into())
+}
+
+#[tracepoint(name = "sys_enter_execveat", category = "syscalls")]
+pub fn execve_failed_syscalls_sys_enter_execveat(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_enter_execveat(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_sys_enter_execveat(ctx: &TracePointContext) -> ProbeResult<()> {
+    let args = SysEnterArgs::<ExecveAt>::from_context(ctx)?.args;
+    track_execve_args(args.into())
+}
+
+#[inline(always)]
+unsafe fn track_execve_args(args: ExecveArgs) -> ProbeResult<()> {
+    if_disabled_return!(Type::ExecveFailed, ());
+
+    EXECVE_ARGS
+        .insert(&bpf_task_tracking_id(), &args, 0)
+        .map_err(|_| MapError::InsertFailure)?;
+
+    Ok(())
+}
+
+#[tracepoint(name = "sys_exit_execve", category = "syscalls")]
+pub fn execve_failed_syscalls_sys_exit_execve(ctx: TracePointContext) -> u32 {
+    match unsafe { try_execve_failed(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_execveat", category = "syscalls")]
+pub fn execve_failed_syscalls_sys_exit_execveat(ctx: TracePointContext) -> u32 {
+    match unsafe { try_execve_failed(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_execve_failed(ctx: &TracePointContext) -> ProbeResult<()> {
+    let key = bpf_task_tracking_id();
+    let rc = SysExitArgs::from_context(ctx)?.ret as i32;
+
+    let args = EXECVE_ARGS.get(&key).copied();
+    ignore_result!(EXECVE_ARGS.remove(&key));
+
+    // on successful execution tracked args point to a memory
+    // which does not exist anymore
+    if rc >= 0 {
+        return Ok(());
+    }
+
+    let ts = task_struct::current();
+    let task_uuid = ts.uuid();
+
+    // event is disabled, tracking must be cleaned up anyway
+    // otherwise the next execution of the task is missed
+    let Some(args) = args else {
+        ignore_result!(EXECVE_TRACKING.remove(&task_uuid));
+        return Ok(());
+    };
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<ExecveEvent>()?;
+
+    event.init_from_current_task(Type::ExecveFailed)?;
+    event.data.rc = rc;
+
+    // if file could be opened we have a resolved path
+    // we take it so that hashes can be computed
+    if let Some(tracked) = EXECVE_TRACKING.get_ptr(&task_uuid) {
+        event.data.executable.copy_from(&(*tracked).data.executable);
+    } else {
+        ignore_result!(inspect_err!(
+            event.data.executable.bpf_probe_read_str(args.filename),
+            |_| warn_msg!(ctx, "failed to read filename")
+        ));
+    }
+    ignore_result!(EXECVE_TRACKING.remove(&task_uuid));
+
+    // parsing argv array of the failed execution
+    if args.argv != 0 {
+        let argv = args.argv as *const u64;
+        for i in 0..MAX_FAILED_ARGV_ENTRIES {
+            let Ok(arg) = bpf_probe_read_user(argv.add(i)) else {
+                break;
+            };
+
+            if arg == 0 {
+                break;
+            }
+
+            if event.data.argv.append_user_str(arg as *const u8).is_err() {
+                event.data.argv_truncated = true;
+                break;
+            }
+        }
+    }
+
+    ignore_result!(event.data.cgroup.resolve_task(ts));
+
+    pipe_event(ctx, event);
+
+    Ok(())
+}
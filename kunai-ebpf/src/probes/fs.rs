@@ -1,10 +1,15 @@
+use super::io_uring::in_io_uring;
 use super::*;
 
 use aya_ebpf::cty::c_int;
+use aya_ebpf::helpers::bpf_probe_read_kernel;
 use aya_ebpf::maps::LruHashMap;
-use aya_ebpf::programs::ProbeContext;
+use aya_ebpf::programs::{ProbeContext, TracePointContext};
 use kunai_common::inspect_err;
+use kunai_common::kernel;
 use kunai_common::kprobe::ProbeFn;
+use kunai_common::syscalls::SysExitArgs;
+use kunai_common::version::kernel_version;
 
 #[repr(C)]
 struct RW(bool, bool);
@@ -89,8 +94,13 @@ pub fn fs_vfs_readv(ctx: ProbeContext) -> u32 {
 }
 
 unsafe fn try_vfs_read(ctx: &ProbeContext) -> ProbeResult<()> {
-    let config = get_cfg!()?;
     let file = co_re::file::from_ptr(ctx.arg(0).ok_or(ProbeError::KProbeArgFailure)?);
+    handle_read(ctx, &file, false)
+}
+
+#[inline(always)]
+unsafe fn handle_read(ctx: &ProbeContext, file: &co_re::file, io_uring: bool) -> ProbeResult<()> {
+    let config = get_cfg!()?;
 
     if !file.is_file().unwrap_or(false) {
         // if not file we do nothing
@@ -98,30 +108,32 @@ unsafe fn try_vfs_read(ctx: &ProbeContext) -> ProbeResult<()> {
     }
 
     // if file has already been tracked
-    if already_read(&file)? {
+    if already_read(file)? {
         return Ok(());
     }
 
     alloc::init()?;
     let event = alloc::alloc_zero::<ConfigEvent>()?;
 
+    event.data.io_uring = io_uring;
     ignore_result!(inspect_err!(
-        event.data.path.core_resolve_file(&file, MAX_PATH_DEPTH),
+        event.data.path.core_resolve_file(file, MAX_PATH_DEPTH),
         |e: &path::Error| warn!(ctx, "failed to resolve filename", (*e).into())
     ));
 
-    if event.data.path.starts_with("/etc/") {
+    if config.config_files.is_included(&event.data.path) {
         event.init_from_current_task(Type::ReadConfig)?;
         pipe_event(ctx, event);
-    } else if config.is_event_enabled(Type::Read) && !event.data.path.starts_with("/proc/") {
-        // we filter out procfs because it generates too much events
-        // maybe let the choice through a configuration option
+    } else if config.is_event_enabled(Type::Read)
+        && !config.config_files.is_read_excluded(&event.data.path)
+    {
+        // excluded paths (procfs by default) generate too much events
         event.init_from_current_task(Type::Read)?;
         pipe_event(ctx, event);
     }
 
     // we mark file as being tracked
-    ignore_result!(inspect_err!(track_read(&file), |_| warn_msg!(
+    ignore_result!(inspect_err!(track_read(file), |_| warn_msg!(
         ctx,
         "failed to track file read"
     )));
@@ -142,7 +154,7 @@ pub fn fs_vfs_write(ctx: ProbeContext) -> u32 {
 
 #[kprobe(function = "vfs_writev")]
 pub fn fs_vfs_writev(ctx: ProbeContext) -> u32 {
-    match unsafe { try_vfs_write(&ctx) } {
+    match unsafe { try_vfs_writev(&ctx) } {
         Ok(_) => errors::BPF_PROG_SUCCESS,
         Err(s) => {
             error!(&ctx, s);
@@ -152,8 +164,55 @@ pub fn fs_vfs_writev(ctx: ProbeContext) -> u32 {
 }
 
 unsafe fn try_vfs_write(ctx: &ProbeContext) -> ProbeResult<()> {
-    let config = get_cfg!()?;
     let file = co_re::file::from_ptr(ctx.arg(0).ok_or(ProbeError::KProbeArgFailure)?);
+    let buf: u64 = kprobe_arg!(ctx, 1)?;
+    let count: u64 = kprobe_arg!(ctx, 2)?;
+    handle_write(ctx, &file, Some((buf, count)), false)
+}
+
+unsafe fn try_vfs_writev(ctx: &ProbeContext) -> ProbeResult<()> {
+    let file = co_re::file::from_ptr(ctx.arg(0).ok_or(ProbeError::KProbeArgFailure)?);
+    handle_write(ctx, &file, None, false)
+}
+
+/// Reports writes to files allowing to tamper with the kernel
+/// (tracefs, sensitive sysctls). `written` is the user buffer
+/// and its length when it is available. Like other write events
+/// only the first write of a task to a given file is reported.
+#[inline(always)]
+unsafe fn handle_tamper_write(
+    ctx: &ProbeContext,
+    path: &Path,
+    written: Option<(u64, u64)>,
+) -> ProbeResult<()> {
+    if_disabled_return!(Type::KernelTamper, ());
+
+    let event = alloc::alloc_zero::<KernelTamperEvent>()?;
+
+    event.init_from_current_task(Type::KernelTamper)?;
+    event.data.kind = TamperKind::FileWrite;
+    event.data.path.copy_from(path);
+
+    if let Some((buf, count)) = written {
+        ignore_result!(inspect_err!(
+            event.data.data.read_user_at(buf as *const u8, count as u32),
+            |_| warn_msg!(ctx, "failed to read data written")
+        ));
+    }
+
+    pipe_event(ctx, event);
+
+    Ok(())
+}
+
+#[inline(always)]
+unsafe fn handle_write(
+    ctx: &ProbeContext,
+    file: &co_re::file,
+    written: Option<(u64, u64)>,
+    io_uring: bool,
+) -> ProbeResult<()> {
+    let config = get_cfg!()?;
 
     if !core_read_kernel!(file, is_file)? {
         // if not a regular file we do nothing
@@ -161,28 +220,38 @@ unsafe fn try_vfs_write(ctx: &ProbeContext) -> ProbeResult<()> {
     }
 
     // if file has already been tracked
-    if already_written(&file)? {
+    if already_written(file)? {
         return Ok(());
     }
 
     alloc::init()?;
     let event = alloc::alloc_zero::<ConfigEvent>()?;
 
+    event.data.io_uring = io_uring;
     ignore_result!(inspect_err!(
-        event.data.path.core_resolve_file(&file, MAX_PATH_DEPTH),
+        event.data.path.core_resolve_file(file, MAX_PATH_DEPTH),
         |e: &path::Error| warn!(ctx, "failed to resolve filename", (*e).into())
     ));
 
-    if event.data.path.starts_with("/etc/") {
+    if config.tamper_files.is_included(&event.data.path) {
+        ignore_result!(inspect_err!(
+            handle_tamper_write(ctx, &event.data.path, written),
+            |_| warn_msg!(ctx, "failed to handle tamper write")
+        ));
+    }
+
+    if config.config_files.is_included(&event.data.path) {
         event.init_from_current_task(Type::WriteConfig)?;
         pipe_event(ctx, event);
-    } else if config.is_event_enabled(Type::Write) {
+    } else if config.is_event_enabled(Type::Write)
+        && !config.config_files.is_write_excluded(&event.data.path)
+    {
         event.init_from_current_task(Type::Write)?;
         pipe_event(ctx, event);
     }
 
     // we mark file as being tracked
-    ignore_result!(inspect_err!(track_write(&file), |_| warn_msg!(
+    ignore_result!(inspect_err!(track_write(file), |_| warn_msg!(
         ctx,
         "failed to track file write"
     )));
@@ -190,6 +259,39 @@ unsafe fn try_vfs_write(ctx: &ProbeContext) -> ProbeResult<()> {
     Ok(())
 }
 
+// values of the read_write argument of rw_verify_area
+const READ: c_int = 0;
+const WRITE: c_int = 1;
+
+// io_uring read and write operations do not go through vfs_read
+// and vfs_write but they check file access with rw_verify_area
+#[kprobe(function = "rw_verify_area")]
+pub fn fs_rw_verify_area(ctx: ProbeContext) -> u32 {
+    match unsafe { try_rw_verify_area(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_rw_verify_area(ctx: &ProbeContext) -> ProbeResult<()> {
+    // syscalls are already handled by vfs_* probes
+    if !in_io_uring() {
+        return Ok(());
+    }
+
+    let read_write: c_int = kprobe_arg!(ctx, 0)?;
+    let file = co_re::file::from_ptr(kprobe_arg!(ctx, 1)?);
+
+    match read_write {
+        READ => handle_read(ctx, &file, true),
+        WRITE => handle_write(ctx, &file, None, true),
+        _ => Ok(()),
+    }
+}
+
 #[kprobe(function = "security_path_rename")]
 pub fn fs_security_path_rename(ctx: ProbeContext) -> u32 {
     match unsafe { try_security_path_rename(&ctx) } {
@@ -247,6 +349,101 @@ unsafe fn try_security_path_rename(ctx: &ProbeContext) -> ProbeResult<()> {
     Ok(())
 }
 
+#[kprobe(function = "security_path_link")]
+pub fn fs_security_path_link(ctx: ProbeContext) -> u32 {
+    match unsafe { try_security_path_link(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_security_path_link(ctx: &ProbeContext) -> ProbeResult<()> {
+    // if event is disabled we return
+    if_disabled_return!(Type::FileLink, ());
+
+    let old_dentry = co_re::dentry::from_ptr(kprobe_arg!(ctx, 0)?);
+    let new_dir = co_re::path::from_ptr(kprobe_arg!(ctx, 1)?);
+    let new_dentry = co_re::dentry::from_ptr(kprobe_arg!(ctx, 2)?);
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<FileLinkEvent>()?;
+
+    event.init_from_current_task(Type::FileLink)?;
+
+    // hard links cannot cross mounts so target is
+    // found in the same mount as the new link
+    let mnt = core_read_kernel!(new_dir, mnt)?;
+    ignore_result!(inspect_err!(
+        event
+            .data
+            .target
+            .core_resolve_dentry(&mnt, old_dentry, MAX_PATH_DEPTH),
+        |e: &path::Error| warn!(ctx, "failed to resolve link target", (*e).into())
+    ));
+
+    ignore_result!(inspect_err!(
+        event.data.link.prepend_dentry(&new_dentry),
+        |e: &path::Error| warn!(ctx, "failed to parse link dentry", (*e).into())
+    ));
+
+    ignore_result!(inspect_err!(
+        event.data.link.core_resolve(&new_dir, MAX_PATH_DEPTH),
+        |e: &path::Error| warn!(ctx, "failed to resolve link dir", (*e).into())
+    ));
+
+    pipe_event(ctx, event);
+
+    Ok(())
+}
+
+#[kprobe(function = "security_path_symlink")]
+pub fn fs_security_path_symlink(ctx: ProbeContext) -> u32 {
+    match unsafe { try_security_path_symlink(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_security_path_symlink(ctx: &ProbeContext) -> ProbeResult<()> {
+    // if event is disabled we return
+    if_disabled_return!(Type::FileSymlink, ());
+
+    let dir = co_re::path::from_ptr(kprobe_arg!(ctx, 0)?);
+    let dentry = co_re::dentry::from_ptr(kprobe_arg!(ctx, 1)?);
+    let old_name: *const u8 = kprobe_arg!(ctx, 2)?;
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<FileSymlinkEvent>()?;
+
+    event.init_from_current_task(Type::FileSymlink)?;
+
+    // symlink target has already been copied in kernel memory
+    ignore_result!(inspect_err!(
+        event.data.target.read_kernel_str_bytes(old_name),
+        |_| warn_msg!(ctx, "failed to read symlink target")
+    ));
+
+    ignore_result!(inspect_err!(
+        event.data.link.prepend_dentry(&dentry),
+        |e: &path::Error| warn!(ctx, "failed to parse link dentry", (*e).into())
+    ));
+
+    ignore_result!(inspect_err!(
+        event.data.link.core_resolve(&dir, MAX_PATH_DEPTH),
+        |e: &path::Error| warn!(ctx, "failed to resolve link dir", (*e).into())
+    ));
+
+    pipe_event(ctx, event);
+
+    Ok(())
+}
+
 #[map]
 static mut PATHS: LruHashMap<u128, Path> = LruHashMap::with_max_entries(4096, 0);
 
@@ -328,3 +525,635 @@ unsafe fn try_vfs_unlink(ctx: &ProbeContext) -> ProbeResult<()> {
 
     Ok(())
 }
+
+// chmod, chown and times events are sent at syscall exit
+// so that we know if the change has been applied
+#[map]
+static mut CHMOD_EVENTS: LruHashMap<u64, FileChmodEvent> = LruHashMap::with_max_entries(1024, 0);
+
+#[map]
+static mut CHOWN_EVENTS: LruHashMap<u64, FileChownEvent> = LruHashMap::with_max_entries(1024, 0);
+
+#[map]
+static mut TIMES_EVENTS: LruHashMap<u64, FileTimesEvent> = LruHashMap::with_max_entries(1024, 0);
+
+#[kprobe(function = "security_path_chmod")]
+pub fn fs_security_path_chmod(ctx: ProbeContext) -> u32 {
+    match unsafe { try_security_path_chmod(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_security_path_chmod(ctx: &ProbeContext) -> ProbeResult<()> {
+    // if event is disabled we return
+    if_disabled_return!(Type::FileChmod, ());
+
+    let path = co_re::path::from_ptr(kprobe_arg!(ctx, 0)?);
+    let mode: u16 = kprobe_arg!(ctx, 1)?;
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<FileChmodEvent>()?;
+
+    event.init_from_current_task(Type::FileChmod)?;
+
+    event.data.old_mode = core_read_kernel!(path, dentry, d_inode, i_mode)?;
+    event.data.new_mode = mode;
+
+    ignore_result!(inspect_err!(
+        event.data.path.core_resolve(&path, MAX_PATH_DEPTH),
+        |e: &path::Error| warn!(ctx, "failed to resolve path", (*e).into())
+    ));
+
+    CHMOD_EVENTS
+        .insert(&bpf_task_tracking_id(), event, 0)
+        .map_err(|_| MapError::InsertFailure)?;
+
+    Ok(())
+}
+
+#[inline(always)]
+unsafe fn try_sys_exit_chmod(ctx: &TracePointContext) -> ProbeResult<()> {
+    let key = bpf_task_tracking_id();
+    let rc = SysExitArgs::from_context(ctx)?.ret;
+
+    if let Some(event) = CHMOD_EVENTS.get_ptr_mut(&key) {
+        let event = &mut (*event);
+        event.data.rc = rc;
+        pipe_event(ctx, event);
+    }
+
+    // we cleanup map
+    ignore_result!(CHMOD_EVENTS.remove(&key));
+
+    Ok(())
+}
+
+#[tracepoint(name = "sys_exit_chmod", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_chmod(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_chmod(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_fchmod", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_fchmod(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_chmod(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_fchmodat", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_fchmodat(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_chmod(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_fchmodat2", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_fchmodat2(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_chmod(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[kprobe(function = "security_path_chown")]
+pub fn fs_security_path_chown(ctx: ProbeContext) -> u32 {
+    match unsafe { try_security_path_chown(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_security_path_chown(ctx: &ProbeContext) -> ProbeResult<()> {
+    // if event is disabled we return
+    if_disabled_return!(Type::FileChown, ());
+
+    let path = co_re::path::from_ptr(kprobe_arg!(ctx, 0)?);
+    // kuid_t and kgid_t are passed by value
+    let uid: u32 = kprobe_arg!(ctx, 1)?;
+    let gid: u32 = kprobe_arg!(ctx, 2)?;
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<FileChownEvent>()?;
+
+    event.init_from_current_task(Type::FileChown)?;
+
+    let inode = core_read_kernel!(path, dentry, d_inode)?;
+    event.data.old_uid = inode.i_uid();
+    event.data.old_gid = inode.i_gid();
+    event.data.new_uid = uid;
+    event.data.new_gid = gid;
+
+    ignore_result!(inspect_err!(
+        event.data.path.core_resolve(&path, MAX_PATH_DEPTH),
+        |e: &path::Error| warn!(ctx, "failed to resolve path", (*e).into())
+    ));
+
+    CHOWN_EVENTS
+        .insert(&bpf_task_tracking_id(), event, 0)
+        .map_err(|_| MapError::InsertFailure)?;
+
+    Ok(())
+}
+
+#[inline(always)]
+unsafe fn try_sys_exit_chown(ctx: &TracePointContext) -> ProbeResult<()> {
+    let key = bpf_task_tracking_id();
+    let rc = SysExitArgs::from_context(ctx)?.ret;
+
+    if let Some(event) = CHOWN_EVENTS.get_ptr_mut(&key) {
+        let event = &mut (*event);
+        event.data.rc = rc;
+        pipe_event(ctx, event);
+    }
+
+    // we cleanup map
+    ignore_result!(CHOWN_EVENTS.remove(&key));
+
+    Ok(())
+}
+
+#[tracepoint(name = "sys_exit_chown", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_chown(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_chown(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_lchown", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_lchown(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_chown(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_fchown", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_fchown(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_chown(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_fchownat", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_fchownat(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_chown(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+// vfs_utimes exists since 5.9, before that utimes
+// were set from a function not exported
+#[kprobe(function = "vfs_utimes")]
+pub fn fs_vfs_utimes(ctx: ProbeContext) -> u32 {
+    match unsafe { try_vfs_utimes(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_vfs_utimes(ctx: &ProbeContext) -> ProbeResult<()> {
+    // if event is disabled we return
+    if_disabled_return!(Type::FileTimes, ());
+
+    let path = co_re::path::from_ptr(kprobe_arg!(ctx, 0)?);
+    let times: *const co_re::timespec64 = kprobe_arg!(ctx, 1)?;
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<FileTimesEvent>()?;
+
+    event.init_from_current_task(Type::FileTimes)?;
+
+    // a NULL times means both times are set to current time
+    if times.is_null() {
+        event.data.atime.nsec = UTIME_NOW;
+        event.data.mtime.nsec = UTIME_NOW;
+    } else {
+        event.data.atime = bpf_probe_read_kernel(times)
+            .map_err(|_| ProbeError::CoReFieldRead)?
+            .into();
+        event.data.mtime = bpf_probe_read_kernel(times.add(1))
+            .map_err(|_| ProbeError::CoReFieldRead)?
+            .into();
+    }
+
+    // path metadata holds the times before the change
+    ignore_result!(inspect_err!(
+        event.data.path.core_resolve(&path, MAX_PATH_DEPTH),
+        |e: &path::Error| warn!(ctx, "failed to resolve path", (*e).into())
+    ));
+
+    TIMES_EVENTS
+        .insert(&bpf_task_tracking_id(), event, 0)
+        .map_err(|_| MapError::InsertFailure)?;
+
+    Ok(())
+}
+
+#[inline(always)]
+unsafe fn try_sys_exit_utimes(ctx: &TracePointContext) -> ProbeResult<()> {
+    let key = bpf_task_tracking_id();
+    let rc = SysExitArgs::from_context(ctx)?.ret;
+
+    if let Some(event) = TIMES_EVENTS.get_ptr_mut(&key) {
+        let event = &mut (*event);
+        event.data.rc = rc;
+        pipe_event(ctx, event);
+    }
+
+    // we cleanup map
+    ignore_result!(TIMES_EVENTS.remove(&key));
+
+    Ok(())
+}
+
+#[tracepoint(name = "sys_exit_utime", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_utime(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_utimes(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_utimes", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_utimes(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_utimes(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_futimesat", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_futimesat(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_utimes(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_utimensat", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_utimensat(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_utimes(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+// security_inode_{set,remove}xattr hooks only get a dentry so we need to
+// find the mount the syscall operates on to be able to resolve the path.
+// The mount is found when the syscall gets write access to it.
+#[repr(C)]
+struct XattrArgs {
+    op: u8,
+    mnt: u64,
+}
+
+#[map]
+static mut XATTR_ARGS: LruHashMap<u64, XattrArgs> = LruHashMap::with_max_entries(1024, 0);
+
+#[map]
+static mut XATTR_EVENTS: LruHashMap<u64, FileXattrEvent> = LruHashMap::with_max_entries(1024, 0);
+
+#[inline(always)]
+unsafe fn track_xattr(op: u8) -> ProbeResult<()> {
+    // if event is disabled we return
+    if_disabled_return!(Type::FileXattr, ());
+
+    XATTR_ARGS
+        .insert(&bpf_task_tracking_id(), &XattrArgs { op, mnt: 0 }, 0)
+        .map_err(|_| MapError::InsertFailure)?;
+
+    Ok(())
+}
+
+#[tracepoint(name = "sys_enter_setxattr", category = "syscalls")]
+pub fn fs_syscalls_sys_enter_setxattr(ctx: TracePointContext) -> u32 {
+    match unsafe { track_xattr(XATTR_OP_SET) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_enter_lsetxattr", category = "syscalls")]
+pub fn fs_syscalls_sys_enter_lsetxattr(ctx: TracePointContext) -> u32 {
+    match unsafe { track_xattr(XATTR_OP_SET) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_enter_fsetxattr", category = "syscalls")]
+pub fn fs_syscalls_sys_enter_fsetxattr(ctx: TracePointContext) -> u32 {
+    match unsafe { track_xattr(XATTR_OP_SET) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_enter_removexattr", category = "syscalls")]
+pub fn fs_syscalls_sys_enter_removexattr(ctx: TracePointContext) -> u32 {
+    match unsafe { track_xattr(XATTR_OP_REMOVE) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_enter_lremovexattr", category = "syscalls")]
+pub fn fs_syscalls_sys_enter_lremovexattr(ctx: TracePointContext) -> u32 {
+    match unsafe { track_xattr(XATTR_OP_REMOVE) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_enter_fremovexattr", category = "syscalls")]
+pub fn fs_syscalls_sys_enter_fremovexattr(ctx: TracePointContext) -> u32 {
+    match unsafe { track_xattr(XATTR_OP_REMOVE) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn set_xattr_mnt(mnt: u64) {
+    if let Some(args) = XATTR_ARGS.get_ptr_mut(&bpf_task_tracking_id()) {
+        // the first mount we get write access to is the one we want
+        if (*args).mnt == 0 {
+            (*args).mnt = mnt;
+        }
+    }
+}
+
+#[kprobe(function = "mnt_want_write")]
+pub fn fs_mnt_want_write(ctx: ProbeContext) -> u32 {
+    match unsafe { try_mnt_want_write(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_mnt_want_write(ctx: &ProbeContext) -> ProbeResult<()> {
+    set_xattr_mnt(kprobe_arg!(ctx, 0)?);
+    Ok(())
+}
+
+#[kprobe(function = "mnt_want_write_file")]
+pub fn fs_mnt_want_write_file(ctx: ProbeContext) -> u32 {
+    match unsafe { try_mnt_want_write_file(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_mnt_want_write_file(ctx: &ProbeContext) -> ProbeResult<()> {
+    // we don't need to go further if no xattr syscall is tracked
+    if XATTR_ARGS.get(&bpf_task_tracking_id()).is_none() {
+        return Ok(());
+    }
+
+    let file = co_re::file::from_ptr(kprobe_arg!(ctx, 0)?);
+    let mnt = core_read_kernel!(file, f_path, mnt)?;
+
+    set_xattr_mnt(mnt.as_ptr() as u64);
+
+    Ok(())
+}
+
+#[kprobe(function = "security_inode_setxattr")]
+pub fn fs_security_inode_setxattr(ctx: ProbeContext) -> u32 {
+    match unsafe { try_security_inode_xattr(&ctx, XATTR_OP_SET) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[kprobe(function = "security_inode_removexattr")]
+pub fn fs_security_inode_removexattr(ctx: ProbeContext) -> u32 {
+    match unsafe { try_security_inode_xattr(&ctx, XATTR_OP_REMOVE) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_security_inode_xattr(ctx: &ProbeContext, op: u8) -> ProbeResult<()> {
+    let key = bpf_task_tracking_id();
+
+    // we only handle hooks reached from the syscalls we track
+    let args = match XATTR_ARGS.get(&key) {
+        Some(a) if a.op == op => a,
+        _ => return Ok(()),
+    };
+
+    // since 5.12 hooks take a user namespace (later a mnt_idmap)
+    // as first argument
+    let (entry, name, size): (co_re::dentry, u64, u64) = if kernel_version() < kernel!(5, 12) {
+        (
+            co_re::dentry::from_ptr(kprobe_arg!(ctx, 0)?),
+            kprobe_arg!(ctx, 1)?,
+            kprobe_arg!(ctx, 3).unwrap_or(0),
+        )
+    } else {
+        (
+            co_re::dentry::from_ptr(kprobe_arg!(ctx, 1)?),
+            kprobe_arg!(ctx, 2)?,
+            kprobe_arg!(ctx, 4).unwrap_or(0),
+        )
+    };
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<FileXattrEvent>()?;
+
+    event.init_from_current_task(Type::FileXattr)?;
+
+    event.data.op = op;
+    if op == XATTR_OP_SET {
+        event.data.size = size;
+    }
+
+    ignore_result!(inspect_err!(
+        event.data.name.read_kernel_str_bytes(name as *const u8),
+        |_| warn_msg!(ctx, "failed to read xattr name")
+    ));
+
+    let mnt = co_re::vfsmount::from_ptr(args.mnt as *const _);
+    ignore_result!(inspect_err!(
+        event
+            .data
+            .path
+            .core_resolve_dentry(&mnt, entry, MAX_PATH_DEPTH),
+        |e: &path::Error| warn!(ctx, "failed to resolve path", (*e).into())
+    ));
+
+    XATTR_EVENTS
+        .insert(&key, event, 0)
+        .map_err(|_| MapError::InsertFailure)?;
+
+    Ok(())
+}
+
+#[inline(always)]
+unsafe fn try_sys_exit_xattr(ctx: &TracePointContext) -> ProbeResult<()> {
+    let key = bpf_task_tracking_id();
+    let rc = SysExitArgs::from_context(ctx)?.ret;
+
+    if let Some(event) = XATTR_EVENTS.get_ptr_mut(&key) {
+        let event = &mut (*event);
+        event.data.rc = rc;
+        pipe_event(ctx, event);
+    }
+
+    // we cleanup maps
+    ignore_result!(XATTR_EVENTS.remove(&key));
+    ignore_result!(XATTR_ARGS.remove(&key));
+
+    Ok(())
+}
+
+#[tracepoint(name = "sys_exit_setxattr", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_setxattr(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_xattr(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_lsetxattr", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_lsetxattr(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_xattr(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_fsetxattr", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_fsetxattr(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_xattr(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_removexattr", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_removexattr(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_xattr(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_lremovexattr", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_lremovexattr(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_xattr(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_exit_fremovexattr", category = "syscalls")]
+pub fn fs_syscalls_sys_exit_fremovexattr(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_xattr(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
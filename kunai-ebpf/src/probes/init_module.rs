@@ -2,6 +2,7 @@ use super::*;
 
 use aya_ebpf::maps::LruHashMap;
 use aya_ebpf::programs::{ProbeContext, TracePointContext};
+use kunai_common::co_re::task_struct;
 use kunai_common::syscalls::{SysEnterArgs, SysExitArgs};
 
 #[map]
@@ -131,6 +132,20 @@ unsafe fn try_sys_exit_init_module(ctx: &TracePointContext) -> ProbeResult<()> {
             event.data.name.push_bytes_unchecked("?");
         }
         event.data.loaded = args.ret == 0;
+
+        // file is still opened at syscall exit so we can resolve its path
+        if let InitModuleArgs::FInit(a) = &event.data.args {
+            if let Some(file) = task_struct::current()
+                .get_fd(a.fd as usize)
+                .filter(|f| !f.is_null())
+            {
+                ignore_result!(inspect_err!(
+                    event.data.path.core_resolve_file(&file, MAX_PATH_DEPTH),
+                    |_| warn_msg!(ctx, "failed to resolve module path")
+                ));
+            }
+        }
+
         pipe_event(ctx, event);
     }
 
@@ -139,3 +154,82 @@ unsafe fn try_sys_exit_init_module(ctx: &TracePointContext) -> ProbeResult<()> {
 
     Ok(())
 }
+
+#[map]
+static mut DELETE_MODULE_ARGS: LruHashMap<u64, SysEnterArgs<DeleteModuleArgs>> =
+    LruHashMap::with_max_entries(1024, 0);
+
+#[repr(C)]
+struct DeleteModuleArgs {
+    name_user: u64,
+    flags: u64,
+}
+
+#[tracepoint(name = "sys_enter_delete_module", category = "syscalls")]
+pub fn lkm_syscalls_sys_enter_delete_module(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_enter_delete_module(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_sys_enter_delete_module(ctx: &TracePointContext) -> ProbeResult<()> {
+    // early return if event is disabled
+    if_disabled_return!(Type::DeleteModule, ());
+
+    let args = SysEnterArgs::<DeleteModuleArgs>::from_context(ctx)?;
+
+    // we ignore result as we can check something went wrong when we try to insert argument
+    ignore_result!(DELETE_MODULE_ARGS.insert(&bpf_task_tracking_id(), &args, 0));
+
+    Ok(())
+}
+
+#[tracepoint(name = "sys_exit_delete_module", category = "syscalls")]
+pub fn lkm_syscalls_sys_exit_delete_module(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_delete_module(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_sys_exit_delete_module(ctx: &TracePointContext) -> ProbeResult<()> {
+    // early return if event is disabled
+    if_disabled_return!(Type::DeleteModule, ());
+
+    let exit_args = SysExitArgs::from_context(ctx)?;
+    let key = bpf_task_tracking_id();
+
+    let entry_args = DELETE_MODULE_ARGS
+        .get(&key)
+        .ok_or(errors::MapError::GetFailure)?;
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<DeleteModuleEvent>()?;
+
+    event.init_from_current_task(Type::DeleteModule)?;
+
+    event.data.flags = entry_args.args.flags as u32;
+    event.data.rc = exit_args.ret;
+
+    ignore_result!(inspect_err!(
+        event
+            .data
+            .name
+            .read_user_str_bytes(entry_args.args.name_user as *const u8),
+        |_| warn_msg!(ctx, "failed to read module name")
+    ));
+
+    pipe_event(ctx, event);
+
+    // cleanup arguments no need to handle failure
+    ignore_result!(DELETE_MODULE_ARGS.remove(&key));
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use super::*;
+
+use aya_ebpf::helpers::bpf_probe_read_user;
+use aya_ebpf::maps::LruHashMap;
+use aya_ebpf::programs::{ProbeContext, TracePointContext};
+use kunai_common::co_re::task_struct;
+use kunai_common::syscalls::{SysEnterArgs, SysExitArgs};
+
+#[map]
+static mut IO_URING_SETUP_ARGS: LruHashMap<u64, SysEnterArgs<IoUringSetupArgs>> =
+    LruHashMap::with_max_entries(1024, 0);
+
+// start time of the tasks being in io_uring_enter syscall, it
+// is compared to current task start time so that an entry left
+// by a task killed within the syscall cannot tag a new task
+// reusing its pid
+#[map]
+static mut IO_URING_ENTER: LruHashMap<u64, u64> = LruHashMap::with_max_entries(4096, 0);
+
+// flag of io-wq workers and, since 5.12, of sqpoll threads
+const PF_IO_WORKER: u32 = 0x00000010;
+
+// key is padding free as it is hashed as raw bytes. The file is identified
+// by its inode rather than by its struct file, which gets reused once freed.
+#[repr(C)]
+struct OpKey {
+    task_id: u64,
+    ino: u64,
+    // device of the inode in the upper bits, opcode in the lower byte
+    dev_op: u64,
+}
+
+// operations already reported for a task and a file
+#[map]
+static mut IO_URING_OPS: LruHashMap<OpKey, bool> = LruHashMap::with_max_entries(0x1ffff, 0);
+
+#[repr(C)]
+struct IoUringSetupArgs {
+    entries: u64,
+    params: u64,
+}
+
+/// Returns true if the current task is running io_uring
+/// operations. Probes shared with syscalls use it to tag
+/// events generated through io_uring.
+#[inline(always)]
+pub(crate) unsafe fn in_io_uring() -> bool {
+    let task = task_struct::current();
+
+    // io_uring workers only run io_uring operations
+    if task.flags().is_some_and(|f| f & PF_IO_WORKER != 0) {
+        return true;
+    }
+
+    // a task which never used io_uring has no io_uring context,
+    // this saves a map lookup to most of the tasks
+    if task.io_uring().is_some_and(|ctx| ctx.is_null()) {
+        return false;
+    }
+
+    // operations not offloaded to a worker run inline in io_uring_enter
+    match IO_URING_ENTER.get(&bpf_task_tracking_id()) {
+        Some(start_time) => task.start_time() == Some(*start_time),
+        None => false,
+    }
+}
+
+#[tracepoint(name = "sys_enter_io_uring_enter", category = "syscalls")]
+pub fn syscalls_sys_enter_io_uring_enter(_ctx: TracePointContext) -> u32 {
+    unsafe {
+        if let Some(start_time) = task_struct::current().start_time() {
+            // we ignore result as failing only misses io_uring tagging
+            ignore_result!(IO_URING_ENTER.insert(&bpf_task_tracking_id(), &start_time, 0));
+        }
+    }
+    0
+}
+
+#[tracepoint(name = "sys_exit_io_uring_enter", category = "syscalls")]
+pub fn syscalls_sys_exit_io_uring_enter(_ctx: TracePointContext) -> u32 {
+    // cleanup no need to handle failure
+    ignore_result!(unsafe { IO_URING_ENTER.remove(&bpf_task_tracking_id()) });
+    0
+}
+
+#[tracepoint(name = "sys_enter_io_uring_setup", category = "syscalls")]
+pub fn syscalls_sys_enter_io_uring_setup(ctx: TracePointContext) -> u32 {
+    match unsafe { try_enter_io_uring_setup(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_enter_io_uring_setup(ctx: &TracePointContext) -> ProbeResult<()> {
+    // early return if event is disabled
+    if_disabled_return!(Type::IoUringSetup, ());
+
+    let args = SysEnterArgs::<IoUringSetupArgs>::from_context(ctx)?;
+
+    // we ignore result as we can check something went wrong when we try to insert argument
+    ignore_result!(IO_URING_SETUP_ARGS.insert(&bpf_task_tracking_id(), &args, 0));
+
+    Ok(())
+}
+
+#[tracepoint(name = "sys_exit_io_uring_setup", category = "syscalls")]
+pub fn syscalls_sys_exit_io_uring_setup(ctx: TracePointContext) -> u32 {
+    match unsafe { try_exit_io_uring_setup(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_exit_io_uring_setup(ctx: &TracePointContext) -> ProbeResult<()> {
+    // early return if event is disabled
+    if_disabled_return!(Type::IoUringSetup, ());
+
+    let exit_args = SysExitArgs::from_context(ctx)?;
+    let key = bpf_task_tracking_id();
+
+    let entry_args = IO_URING_SETUP_ARGS
+        .get(&key)
+        .ok_or(errors::MapError::GetFailure)?;
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<IoUringSetupEvent>()?;
+
+    event.init_from_current_task(Type::IoUringSetup)?;
+
+    event.data.entries = entry_args.args.entries as u32;
+    event.data.rc = exit_args.ret;
+
+    // params are updated by the kernel so we read them at exit
+    match bpf_probe_read_user(entry_args.args.params as *const IoUringParams) {
+        Ok(p) => {
+            event.data.sq_entries = p.sq_entries;
+            event.data.cq_entries = p.cq_entries;
+            event.data.flags = p.flags;
+            event.data.sq_thread_cpu = p.sq_thread_cpu;
+            event.data.sq_thread_idle = p.sq_thread_idle;
+        }
+        Err(_) => warn_msg!(ctx, "failed to read io_uring params"),
+    }
+
+    pipe_event(ctx, event);
+
+    // cleanup arguments no need to handle failure
+    ignore_result!(IO_URING_SETUP_ARGS.remove(&key));
+
+    Ok(())
+}
+
+#[kprobe(function = "io_issue_sqe")]
+pub fn io_uring_enter_io_issue_sqe(ctx: ProbeContext) -> u32 {
+    match unsafe { try_enter_io_issue_sqe(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_enter_io_issue_sqe(ctx: &ProbeContext) -> ProbeResult<()> {
+    if_disabled_return!(Type::IoUringOp, ());
+
+    // we cannot use io_uring_submit_req tracepoint as the file
+    // of a request is assigned only when it gets issued
+    let req = co_re::io_kiocb::from_ptr(kprobe_arg!(ctx, 0)?);
+    let opcode = core_read_kernel!(req, opcode)?;
+
+    let Ok(op) = IoUringOpcode::try_from_uint(opcode) else {
+        return Ok(());
+    };
+
+    if !op.is_reported() {
+        return Ok(());
+    }
+
+    let file = core_read_kernel!(req, file)?;
+
+    // we report an operation on a given file only once per task
+    // but all the open operations as they have no file yet
+    let key = if file.is_null() {
+        None
+    } else {
+        let ino = core_read_kernel!(file, f_inode, i_ino)?;
+        let dev = core_read_kernel!(file, f_inode, i_sb, s_dev)?;
+        Some(OpKey {
+            task_id: bpf_task_tracking_id(),
+            ino,
+            dev_op: (dev as u64) << 8 | opcode as u64,
+        })
+    };
+
+    if key.as_ref().is_some_and(|k| IO_URING_OPS.get(k).is_some()) {
+        return Ok(());
+    }
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<IoUringOpEvent>()?;
+
+    event.init_from_current_task(Type::IoUringOp)?;
+    event.data.opcode = opcode;
+
+    if op.is_open() {
+        let name = core_read_kernel!(req.open_cmd(), filename, name)?;
+        ignore_result!(inspect_err!(
+            event.data.name.read_kernel_str_bytes(name),
+            |_| warn_msg!(ctx, "failed to read io_uring open filename")
+        ));
+    } else if let Some(key) = key {
+        ignore_result!(inspect_err!(
+            event.data.path.core_resolve_file(&file, MAX_PATH_DEPTH),
+            |e: &path::Error| warn!(ctx, "failed to resolve filename", (*e).into())
+        ));
+        ignore_result!(IO_URING_OPS.insert(&key, &true, 0));
+    }
+
+    pipe_event(ctx, event);
+
+    Ok(())
+}
@@ -0,0 +1,329 @@
+use super::*;
+
+use aya_ebpf::helpers::bpf_probe_read_user;
+use aya_ebpf::maps::LruHashMap;
+use aya_ebpf::programs::{ProbeContext, TracePointContext};
+use kunai_common::co_re::task_struct;
+use kunai_common::syscalls::{SysEnterArgs, SysExitArgs};
+
+// kprobes and uprobes created with perf_event_open
+// do not appear in tracefs so we need to catch them
+// at creation time
+#[kprobe(function = "perf_kprobe_init")]
+pub fn kernel_tamper_perf_kprobe_init(ctx: ProbeContext) -> u32 {
+    match unsafe { try_perf_probe_init(&ctx, TamperKind::PerfKprobe) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[kprobe(function = "perf_uprobe_init")]
+pub fn kernel_tamper_perf_uprobe_init(ctx: ProbeContext) -> u32 {
+    match unsafe { try_perf_probe_init(&ctx, TamperKind::PerfUprobe) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_perf_probe_init(ctx: &ProbeContext, kind: TamperKind) -> ProbeResult<()> {
+    if_disabled_return!(Type::KernelTamper, ());
+
+    let p_event = co_re::perf_event::from_ptr(kprobe_arg!(ctx, 0)?);
+    // is_retprobe is the second argument of perf_kprobe_init
+    // and the third one of perf_uprobe_init
+    let is_retprobe: u64 = match kind {
+        TamperKind::PerfUprobe => kprobe_arg!(ctx, 2)?,
+        _ => kprobe_arg!(ctx, 1)?,
+    };
+
+    // config1 holds a user pointer to the kprobed function
+    // or to the uprobed binary path
+    let name = core_read_kernel!(p_event, attr, config1)?;
+    // config2 holds the kprobe address or the uprobe offset
+    let addr = core_read_kernel!(p_event, attr, config2)?;
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<KernelTamperEvent>()?;
+
+    event.init_from_current_task(Type::KernelTamper)?;
+
+    event.data.kind = kind;
+    event.data.addr = addr;
+    event.data.retprobe = is_retprobe & 0xff != 0;
+
+    // name is null for kprobes set on an address
+    if name != 0 {
+        ignore_result!(inspect_err!(
+            event.data.data.append_user_str(name as *const u8),
+            |_| warn_msg!(ctx, "failed to read perf probe target")
+        ));
+    }
+
+    pipe_event(ctx, event);
+
+    Ok(())
+}
+
+#[repr(C)]
+struct KexecLoadArgs {
+    entry: u64,
+    nr_segments: u64,
+    segments: u64,
+    flags: u64,
+}
+
+#[tracepoint(name = "sys_enter_kexec_load", category = "syscalls")]
+pub fn kernel_tamper_sys_enter_kexec_load(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_enter_kexec_load(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_sys_enter_kexec_load(ctx: &TracePointContext) -> ProbeResult<()> {
+    if_disabled_return!(Type::KernelTamper, ());
+
+    let args = SysEnterArgs::<KexecLoadArgs>::from_context(ctx)?;
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<KernelTamperEvent>()?;
+
+    event.init_from_current_task(Type::KernelTamper)?;
+
+    event.data.kind = TamperKind::KexecLoad;
+    event.data.addr = args.args.entry;
+    event.data.flags = args.args.flags;
+
+    pipe_event(ctx, event);
+
+    Ok(())
+}
+
+#[repr(C)]
+struct KexecFileLoadArgs {
+    kernel_fd: u64,
+    initrd_fd: u64,
+    cmdline_len: u64,
+    cmdline_ptr: u64,
+    flags: u64,
+}
+
+#[tracepoint(name = "sys_enter_kexec_file_load", category = "syscalls")]
+pub fn kernel_tamper_sys_enter_kexec_file_load(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_enter_kexec_file_load(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_sys_enter_kexec_file_load(ctx: &TracePointContext) -> ProbeResult<()> {
+    if_disabled_return!(Type::KernelTamper, ());
+
+    let args = SysEnterArgs::<KexecFileLoadArgs>::from_context(ctx)?;
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<KernelTamperEvent>()?;
+
+    event.init_from_current_task(Type::KernelTamper)?;
+
+    event.data.kind = TamperKind::KexecFileLoad;
+    event.data.flags = args.args.flags;
+
+    if let Some(file) = task_struct::current()
+        .get_fd(args.args.kernel_fd as usize)
+        .filter(|f| !f.is_null())
+    {
+        ignore_result!(inspect_err!(
+            event.data.path.core_resolve_file(&file, MAX_PATH_DEPTH),
+            |_| warn_msg!(ctx, "failed to resolve kexec kernel path")
+        ));
+    }
+
+    if args.args.cmdline_ptr != 0 {
+        ignore_result!(inspect_err!(
+            event
+                .data
+                .data
+                .append_user_str(args.args.cmdline_ptr as *const u8),
+            |_| warn_msg!(ctx, "failed to read kexec command line")
+        ));
+    }
+
+    pipe_event(ctx, event);
+
+    Ok(())
+}
+
+#[repr(C)]
+struct BpfArgs {
+    cmd: u64,
+    uattr: u64,
+    size: u64,
+}
+
+// attributes common to BPF_PROG_ATTACH and BPF_LINK_CREATE
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct BpfAttachArgs {
+    cmd: u32,
+    prog_fd: u32,
+    target_fd: u32,
+    attach_type: u32,
+    flags: u32,
+}
+
+#[map]
+static mut BPF_ATTACH_ARGS: LruHashMap<u64, BpfAttachArgs> = LruHashMap::with_max_entries(1024, 0);
+
+#[tracepoint(name = "sys_enter_bpf", category = "syscalls")]
+pub fn kernel_tamper_sys_enter_bpf(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_enter_bpf(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_sys_enter_bpf(ctx: &TracePointContext) -> ProbeResult<()> {
+    let args = SysEnterArgs::<BpfArgs>::from_context(ctx)?;
+    let cmd = args.args.cmd as u32;
+
+    let attach = match cmd {
+        BPF_PROG_ATTACH => {
+            if_disabled_return!(Type::BpfProgAttach, ());
+            let Ok(a) = bpf_probe_read_user(args.args.uattr as *const BpfProgAttachAttr) else {
+                warn_msg!(ctx, "failed to read bpf attributes");
+                return Ok(());
+            };
+            BpfAttachArgs {
+                cmd,
+                prog_fd: a.attach_bpf_fd,
+                target_fd: a.target_fd,
+                attach_type: a.attach_type,
+                flags: a.attach_flags,
+            }
+        }
+        BPF_LINK_CREATE => {
+            if_disabled_return!(Type::BpfLinkCreate, ());
+            let Ok(a) = bpf_probe_read_user(args.args.uattr as *const BpfLinkCreateAttr) else {
+                warn_msg!(ctx, "failed to read bpf attributes");
+                return Ok(());
+            };
+            BpfAttachArgs {
+                cmd,
+                prog_fd: a.prog_fd,
+                target_fd: a.target_fd,
+                attach_type: a.attach_type,
+                flags: a.flags,
+            }
+        }
+        _ => return Ok(()),
+    };
+
+    ignore_result!(BPF_ATTACH_ARGS.insert(&bpf_task_tracking_id(), &attach, 0));
+
+    Ok(())
+}
+
+#[tracepoint(name = "sys_exit_bpf", category = "syscalls")]
+pub fn kernel_tamper_sys_exit_bpf(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_bpf(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_sys_exit_bpf(ctx: &TracePointContext) -> ProbeResult<()> {
+    let key = bpf_task_tracking_id();
+
+    // most of the bpf commands are not tracked
+    let Some(args) = BPF_ATTACH_ARGS.get(&key).copied() else {
+        return Ok(());
+    };
+
+    // cleanup no need to handle failure
+    ignore_result!(BPF_ATTACH_ARGS.remove(&key));
+
+    let exit_args = SysExitArgs::from_context(ctx)?;
+
+    let ty = match args.cmd {
+        BPF_LINK_CREATE => Type::BpfLinkCreate,
+        _ => Type::BpfProgAttach,
+    };
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<BpfProgAttachEvent>()?;
+
+    event.init_from_current_task(ty)?;
+
+    event.data.attach_type = args.attach_type;
+    event.data.flags = args.flags;
+    event.data.target_fd = args.target_fd;
+    event.data.rc = exit_args.ret;
+
+    let current = task_struct::current();
+
+    // bpf_prog_fops is needed to make sure the file is a bpf program
+    let prog_fops = get_cfg!()?.bpf_prog_fops;
+
+    // file descriptors are still opened at syscall exit
+    if let Some(file) = current
+        .get_fd(args.prog_fd as usize)
+        .filter(|f| !f.is_null())
+        .filter(|f| prog_fops != 0 && f.f_op().is_some_and(|op| op as u64 == prog_fops))
+    {
+        // private data of a bpf program file is the program itself
+        let prog = co_re::bpf_prog::from_ptr(core_read_kernel!(file, private_data)? as *const _);
+        if !prog.is_null() {
+            let aux = core_read_kernel!(prog, aux)?;
+
+            event.data.prog_id = core_read_kernel!(aux, id)?;
+            event.data.prog_type = prog.ty().unwrap_or_default();
+
+            if let Some(p_name) = aux.name() {
+                ignore_result!(inspect_err!(
+                    event.data.prog_name.read_kernel_str_bytes(p_name),
+                    |_| warn_msg!(ctx, "failed to read program name")
+                ));
+            }
+
+            if let Some(afn) = aux.attach_func_name() {
+                ignore_result!(inspect_err!(
+                    event.data.attached_func_name.read_kernel_str_bytes(afn),
+                    |_| warn_msg!(ctx, "failed to read attach_func_name")
+                ));
+            }
+        }
+    }
+
+    // target_fd is an interface index or unused for some attach types
+    if let Some(file) = attach_target_is_fd(args.attach_type)
+        .then(|| current.get_fd(args.target_fd as usize))
+        .flatten()
+        .filter(|f| !f.is_null())
+    {
+        ignore_result!(event.data.target.core_resolve_file(&file, MAX_PATH_DEPTH));
+    }
+
+    pipe_event(ctx, event);
+
+    Ok(())
+}
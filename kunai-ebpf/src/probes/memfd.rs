@@ -0,0 +1,108 @@
+use super::*;
+
+use aya_ebpf::{maps::LruHashMap, programs::TracePointContext};
+use kunai_common::syscalls::{SysEnterArgs, SysExitArgs};
+
+#[map]
+static mut MEMFD_CREATE_ARGS: LruHashMap<u64, SysEnterArgs<MemfdCreateArgs>> =
+    LruHashMap::with_max_entries(1024, 0);
+
+#[repr(C)]
+struct MemfdCreateArgs {
+    uname: u64,
+    flags: u64,
+}
+
+/// Computes FILELESS_* flags of a file. Path must be
+/// the one resolved from the file.
+#[inline(always)]
+pub(crate) unsafe fn fileless_flags(file: &co_re::file, path: &Path) -> ProbeResult<u8> {
+    let inode = core_read_kernel!(file, f_inode)?;
+
+    // file still linked somewhere in the filesystem
+    if core_read_kernel!(inode, i_nlink)? != 0 {
+        return Ok(0);
+    }
+
+    // memfd files are unlinked shmem files named memfd:<name>
+    if path.starts_with(MEMFD_PREFIX) {
+        return Ok(FILELESS_MEMFD);
+    }
+
+    if inode.is_tmpfile().unwrap_or_default() {
+        return Ok(FILELESS_TMPFILE);
+    }
+
+    Ok(FILELESS_DELETED)
+}
+
+#[tracepoint(name = "sys_enter_memfd_create", category = "syscalls")]
+pub fn syscalls_sys_enter_memfd_create(ctx: TracePointContext) -> u32 {
+    match unsafe { try_enter_memfd_create(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_enter_memfd_create(ctx: &TracePointContext) -> ProbeResult<()> {
+    // early return if event is disabled
+    if_disabled_return!(Type::MemfdCreate, ());
+
+    let args = SysEnterArgs::<MemfdCreateArgs>::from_context(ctx)?;
+
+    // we ignore result as we can check something went wrong when we try to insert argument
+    ignore_result!(MEMFD_CREATE_ARGS.insert(&bpf_task_tracking_id(), &args, 0));
+
+    Ok(())
+}
+
+#[tracepoint(name = "sys_exit_memfd_create", category = "syscalls")]
+pub fn syscalls_sys_exit_memfd_create(ctx: TracePointContext) -> u32 {
+    match unsafe { try_exit_memfd_create(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_exit_memfd_create(ctx: &TracePointContext) -> ProbeResult<()> {
+    // early return if event is disabled
+    if_disabled_return!(Type::MemfdCreate, ());
+
+    let exit_args = SysExitArgs::from_context(ctx)?;
+    let key = bpf_task_tracking_id();
+
+    let entry_args = MEMFD_CREATE_ARGS
+        .get(&key)
+        .ok_or(errors::MapError::GetFailure)?;
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<MemfdCreateEvent>()?;
+
+    event.init_from_current_task(Type::MemfdCreate)?;
+
+    event.data.flags = entry_args.args.flags as u32;
+    event.data.rc = exit_args.ret;
+
+    ignore_result!(inspect_err!(
+        event
+            .data
+            .name
+            .read_user_str_bytes(entry_args.args.uname as *const u8),
+        |_| warn_msg!(ctx, "failed to read memfd name")
+    ));
+
+    pipe_event(ctx, event);
+
+    // cleanup arguments no need to handle failure
+    ignore_result!(MEMFD_CREATE_ARGS.remove(&key));
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use super::memfd::fileless_flags;
 use super::*;
 use aya_ebpf::programs::TracePointContext;
 use kunai_common::{co_re::task_struct, syscalls::SysEnterArgs};
@@ -47,7 +48,23 @@ unsafe fn try_sys_enter_mmap(ctx: &TracePointContext) -> ProbeResult<()> {
     let mmap_args = SysEnterArgs::<MmapArgs>::from_context(ctx)?.args;
     let fd = mmap_args.fd as i32;
 
-    if fd >= 0 && mmap_args.prot & PROT_EXEC as u64 == PROT_EXEC as u64 {
+    if mmap_args.prot & PROT_EXEC as u64 != PROT_EXEC as u64 {
+        return Ok(());
+    }
+
+    // fd is ignored for anonymous mappings
+    let anonymous = mmap_args.flag & MAP_ANONYMOUS as u64 == MAP_ANONYMOUS as u64;
+
+    if !anonymous && fd < 0 {
+        return Ok(());
+    }
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<MmapExecEvent>()?;
+
+    event.init_from_current_task(Type::MmapExec)?;
+
+    if !anonymous {
         let current = task_struct::current();
 
         let file = current
@@ -58,18 +75,20 @@ unsafe fn try_sys_enter_mmap(ctx: &TracePointContext) -> ProbeResult<()> {
             return Err(ProbeError::FileNotFound);
         }
 
-        alloc::init()?;
-        let event = alloc::alloc_zero::<MmapExecEvent>()?;
-
-        event.init_from_current_task(Type::MmapExec)?;
-
         event
             .data
             .filename
             .core_resolve_file(&file, MAX_PATH_DEPTH)?;
 
-        pipe_event(ctx, event);
+        event.data.fileless = fileless_flags(&file, &event.data.filename).unwrap_or(0);
     }
 
+    event.data.fd = fd;
+    event.data.len = mmap_args.len;
+    event.data.prot = mmap_args.prot;
+    event.data.flags = mmap_args.flag;
+
+    pipe_event(ctx, event);
+
     Ok(())
 }
@@ -1,5 +1,6 @@
 use super::*;
-use aya_ebpf::programs::TracePointContext;
+use aya_ebpf::maps::LruHashMap;
+use aya_ebpf::programs::{ProbeContext, TracePointContext};
 use kunai_common::syscalls::SysEnterArgs;
 
 // print fmt: "0x%lx", REC->ret
@@ -17,12 +18,17 @@ use kunai_common::syscalls::SysEnterArgs;
 // field:unsigned long prot;	offset:32;	size:8;	signed:0;
 
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct MprotectArgs {
     pub start: u64,
     pub len: u64,
     pub prot: u64,
 }
 
+// arguments of mprotect calls making memory executable
+#[map]
+static mut MPROTECT_ARGS: LruHashMap<u64, MprotectArgs> = LruHashMap::with_max_entries(1024, 0);
+
 #[tracepoint(name = "sys_enter_mprotect", category = "syscalls")]
 pub fn syscalls_sys_enter_mprotect(ctx: TracePointContext) -> u32 {
     match unsafe { try_sys_enter_mprotect(&ctx) } {
@@ -40,21 +46,123 @@ unsafe fn try_sys_enter_mprotect(ctx: &TracePointContext) -> ProbeResult<()> {
 
     let args = SysEnterArgs::<MprotectArgs>::from_context(ctx)?.args;
     if args.prot & PROT_EXEC as u64 == PROT_EXEC as u64 {
-        alloc::init()?;
-        let event = alloc::alloc_zero::<MprotectEvent>()?;
+        // event is sent once we know the memory area being changed
+        MPROTECT_ARGS
+            .insert(&bpf_task_tracking_id(), &args, 0)
+            .map_err(|_| MapError::InsertFailure)?;
+    }
 
-        event.init_from_current_task(Type::MprotectExec)?;
+    Ok(())
+}
 
-        // setting event data
-        event.data.start = args.start;
-        event.data.prot = args.prot;
-        event.data.len = args.len;
+// called by do_mprotect_pkey for every memory area in the range
+#[kprobe(function = "security_file_mprotect")]
+pub fn mprotect_security_file_mprotect(ctx: ProbeContext) -> u32 {
+    match unsafe { try_security_file_mprotect(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
 
-        // todo: work on section identification
-        //copy_ascii_str(event.data.section, "?");
+unsafe fn try_security_file_mprotect(ctx: &ProbeContext) -> ProbeResult<()> {
+    // early return if event is disabled
+    if_disabled_return!(Type::MprotectExec, ());
+
+    let key = bpf_task_tracking_id();
+
+    let Some(args) = MPROTECT_ARGS.get(&key) else {
+        return Ok(());
+    };
+
+    let vma = co_re::vm_area_struct::from_ptr(kprobe_arg!(ctx, 0)?);
+    let reqprot: u64 = kprobe_arg!(ctx, 1)?;
+    let vma_start = core_read_kernel!(vma, vm_start)?;
+    let vma_end = core_read_kernel!(vma, vm_end)?;
 
-        pipe_event(ctx, event);
+    // we make sure we are not processing arguments left by a
+    // previous mprotect which failed before reaching this hook
+    if reqprot & PROT_EXEC as u64 != PROT_EXEC as u64
+        || args.start < vma_start
+        || args.start >= vma_end
+    {
+        return Ok(());
     }
 
+    alloc::init()?;
+    let event = alloc::alloc_zero::<MprotectEvent>()?;
+
+    event.init_from_current_task(Type::MprotectExec)?;
+
+    // setting event data
+    event.data.start = args.start;
+    event.data.prot = args.prot;
+    event.data.len = args.len;
+
+    event.data.has_vma = true;
+    event.data.vma_start = vma_start;
+    event.data.vma_end = vma_end;
+    event.data.vma_flags = core_read_kernel!(vma, vm_flags)?;
+
+    let file = core_read_kernel!(vma, vm_file)?;
+    event.data.vma_kind = if !file.is_null() {
+        ignore_result!(inspect_err!(
+            event.data.vma_file.core_resolve_file(&file, MAX_PATH_DEPTH),
+            |e: &path::Error| warn!(ctx, "failed to resolve filename", (*e).into())
+        ));
+        VmaKind::File
+    } else if vma.is_heap().unwrap_or_default() {
+        VmaKind::Heap
+    } else if vma.is_stack().unwrap_or_default() {
+        VmaKind::Stack
+    } else {
+        VmaKind::Anon
+    };
+
+    pipe_event(ctx, event);
+
+    // we report only the first memory area of the range
+    ignore_result!(MPROTECT_ARGS.remove(&key));
+
+    Ok(())
+}
+
+#[tracepoint(name = "sys_exit_mprotect", category = "syscalls")]
+pub fn syscalls_sys_exit_mprotect(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_mprotect(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+unsafe fn try_sys_exit_mprotect(ctx: &TracePointContext) -> ProbeResult<()> {
+    let key = bpf_task_tracking_id();
+
+    // arguments are still there if mprotect failed
+    // before reaching security_file_mprotect
+    let Some(args) = MPROTECT_ARGS.get(&key).copied() else {
+        return Ok(());
+    };
+
+    // cleanup no need to handle failure
+    ignore_result!(MPROTECT_ARGS.remove(&key));
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<MprotectEvent>()?;
+
+    event.init_from_current_task(Type::MprotectExec)?;
+
+    // we report the call without memory area information
+    event.data.start = args.start;
+    event.data.prot = args.prot;
+    event.data.len = args.len;
+
+    pipe_event(ctx, event);
+
     Ok(())
 }
@@ -0,0 +1,237 @@
+use super::*;
+
+use aya_ebpf::{
+    maps::LruHashMap,
+    programs::{ProbeContext, TracePointContext},
+    EbpfContext,
+};
+use kunai_common::{
+    co_re::task_struct,
+    syscalls::{SysEnterArgs, SysExitArgs},
+};
+
+// access mode checked when a task wants to control another one
+// https://elixir.bootlin.com/linux/v6.6/source/include/linux/ptrace.h#L62
+const PTRACE_MODE_ATTACH: u32 = 0x02;
+
+// events are built at syscall entry, target is set by the kernel
+// functions looking up the target and events are sent at syscall exit
+#[map]
+static mut PROCESS_ACCESS_TRACKING: LruHashMap<u64, ProcessAccessEvent> =
+    LruHashMap::with_max_entries(1024, 0);
+
+#[repr(C)]
+struct PtraceArgs {
+    request: i64,
+    pid: i64,
+    addr: u64,
+    data: u64,
+}
+
+#[inline(always)]
+unsafe fn track_access(kind: AccessKind, ptrace_request: i64) -> ProbeResult<()> {
+    alloc::init()?;
+    let event = alloc::alloc_zero::<ProcessAccessEvent>()?;
+
+    event.init_from_current_task(Type::ProcessAccess)?;
+
+    event.data.kind = kind as u8;
+    event.data.ptrace_request = ptrace_request;
+
+    PROCESS_ACCESS_TRACKING
+        .insert(&bpf_task_tracking_id(), event, 0)
+        .map_err(|_| MapError::InsertFailure)?;
+
+    Ok(())
+}
+
+#[inline(always)]
+unsafe fn set_target(target: task_struct) -> ProbeResult<()> {
+    if let Some(event) = PROCESS_ACCESS_TRACKING.get_ptr_mut(&bpf_task_tracking_id()) {
+        let event = &mut (*event);
+        // the first task looked up is the target
+        if !event.data.has_target {
+            event.data.target.from_task(target)?;
+            event.data.has_target = true;
+        }
+    }
+    Ok(())
+}
+
+#[inline(always)]
+unsafe fn send_access<C: EbpfContext>(ctx: &C, rc: i64) {
+    let key = bpf_task_tracking_id();
+
+    if let Some(event) = PROCESS_ACCESS_TRACKING.get_ptr_mut(&key) {
+        let event = &mut (*event);
+        event.data.rc = rc;
+        // target might not be found (i.e. wrong pid)
+        if event.data.has_target {
+            pipe_event(ctx, event);
+        }
+    }
+
+    // we remove item from map
+    ignore_result!(PROCESS_ACCESS_TRACKING.remove(&key));
+}
+
+#[tracepoint(name = "sys_enter_ptrace", category = "syscalls")]
+pub fn syscalls_sys_enter_ptrace(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_enter_ptrace(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_sys_enter_ptrace(ctx: &TracePointContext) -> ProbeResult<()> {
+    // early return if event is disabled
+    if_disabled_return!(Type::ProcessAccess, ());
+
+    let args = SysEnterArgs::<PtraceArgs>::from_context(ctx)?.args;
+
+    // we only track requests giving control over the target
+    if !PtraceRequest::is_tracked(args.request) {
+        return Ok(());
+    }
+
+    track_access(AccessKind::Ptrace, args.request)
+}
+
+#[tracepoint(name = "sys_exit_ptrace", category = "syscalls")]
+pub fn syscalls_sys_exit_ptrace(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_process_access(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[tracepoint(name = "sys_enter_process_vm_writev", category = "syscalls")]
+pub fn syscalls_sys_enter_process_vm_writev(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_enter_process_vm_writev() } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_sys_enter_process_vm_writev() -> ProbeResult<()> {
+    // early return if event is disabled
+    if_disabled_return!(Type::ProcessAccess, ());
+
+    track_access(AccessKind::ProcessVmWritev, 0)
+}
+
+#[tracepoint(name = "sys_exit_process_vm_writev", category = "syscalls")]
+pub fn syscalls_sys_exit_process_vm_writev(ctx: TracePointContext) -> u32 {
+    match unsafe { try_sys_exit_process_access(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_sys_exit_process_access(ctx: &TracePointContext) -> ProbeResult<()> {
+    let args = SysExitArgs::from_context(ctx)?;
+    send_access(ctx, args.ret);
+    Ok(())
+}
+
+// called when ptrace attaches or when another task's memory
+// is accessed (process_vm_writev, /proc/<pid>/mem)
+#[kprobe(function = "security_ptrace_access_check")]
+pub fn process_access_security_ptrace_access_check(ctx: ProbeContext) -> u32 {
+    match unsafe { try_security_ptrace_access_check(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_security_ptrace_access_check(ctx: &ProbeContext) -> ProbeResult<()> {
+    let mode: u32 = kprobe_arg!(ctx, 1)?;
+
+    if mode & PTRACE_MODE_ATTACH != PTRACE_MODE_ATTACH {
+        return Ok(());
+    }
+
+    set_target(task_struct::from_ptr(kprobe_arg!(ctx, 0)?))
+}
+
+// called for ptrace requests made on an already attached task
+#[kprobe(function = "arch_ptrace")]
+pub fn process_access_arch_ptrace(ctx: ProbeContext) -> u32 {
+    match unsafe { try_arch_ptrace(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_arch_ptrace(ctx: &ProbeContext) -> ProbeResult<()> {
+    set_target(task_struct::from_ptr(kprobe_arg!(ctx, 0)?))
+}
+
+#[kprobe(function = "mem_write")]
+pub fn process_access_enter_mem_write(ctx: ProbeContext) -> u32 {
+    match unsafe { try_enter_mem_write(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn try_enter_mem_write(ctx: &ProbeContext) -> ProbeResult<()> {
+    // early return if event is disabled
+    if_disabled_return!(Type::ProcessAccess, ());
+
+    let file = co_re::file::from_ptr(kprobe_arg!(ctx, 0)?);
+    // /proc/<pid>/mem files keep the mm of the target
+    let mm = co_re::mm_struct::from_ptr(core_read_kernel!(file, private_data)? as *const _);
+    if mm.is_null() {
+        return Ok(());
+    }
+
+    track_access(AccessKind::ProcMemWrite, 0)?;
+
+    // mm->owner only exists if kernel is compiled with CONFIG_MEMCG, without
+    // it we can only find the target when a task writes its own memory
+    match mm.owner() {
+        Some(owner) if !owner.is_null() => set_target(owner),
+        Some(_) => Ok(()),
+        None => {
+            let current = task_struct::current();
+            if core_read_kernel!(current, mm)?.as_ptr() == mm.as_ptr() {
+                set_target(current)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[kretprobe(function = "mem_write")]
+pub fn process_access_exit_mem_write(ctx: ProbeContext) -> u32 {
+    unsafe { send_access(&ctx, ctx.ret::<i64>().unwrap_or(-1)) };
+    errors::BPF_PROG_SUCCESS
+}
@@ -63,7 +63,7 @@ unsafe fn try_schedule(ctx: &ProbeContext) -> ProbeResult<()> {
             event
                 .data
                 .argv
-                .read_user_at(arg_start as *const u8, arg_len as u32),
+                .read_user_chunks_at(arg_start as *const u8, arg_len as u32),
             |_| warn_msg!(ctx, "failed to read argv")
         ));
     }
@@ -78,10 +78,8 @@ unsafe fn try_schedule(ctx: &ProbeContext) -> ProbeResult<()> {
         return Ok(());
     }
 
-    let cgroup = core_read_kernel!(ts, sched_task_group, css, cgroup)?;
-
     // we do not raise any error on cgroup parsing, we let a chance to userland to solve it
-    ignore_result!(event.data.cgroup.resolve(cgroup));
+    ignore_result!(event.data.cgroup.resolve_task(ts));
 
     let current = task_struct::current();
     event.data.nodename.read_kernel_at(
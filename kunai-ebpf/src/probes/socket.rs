@@ -0,0 +1,75 @@
+use aya_ebpf::programs::ProbeContext;
+use kunai_common::net::{NetlinkProtocol, SaFamily, SockType, SocketInfo};
+
+use super::*;
+
+// security_socket_post_create is called only once the socket
+// got successfully created by the protocol family
+#[kprobe(function = "security_socket_post_create")]
+pub fn net_security_socket_post_create(ctx: ProbeContext) -> u32 {
+    match unsafe { try_security_socket_post_create(&ctx) } {
+        Ok(_) => errors::BPF_PROG_SUCCESS,
+        Err(s) => {
+            error!(&ctx, s);
+            errors::BPF_PROG_FAILURE
+        }
+    }
+}
+
+/// Returns true for the sockets we want to report, those
+/// allowing to sniff traffic, craft packets, tamper with
+/// sensitive kernel subsystems or talk to the hypervisor
+#[inline(always)]
+fn is_reported(si: &SocketInfo, protocol: u16) -> bool {
+    if si.is_family(SaFamily::AF_PACKET)
+        || si.is_family(SaFamily::AF_VSOCK)
+        || si.is_type(SockType::SOCK_RAW)
+    {
+        return true;
+    }
+
+    if si.is_family(SaFamily::AF_NETLINK) {
+        return NetlinkProtocol::try_from_uint(protocol)
+            .map(|p| p.is_sensitive())
+            .unwrap_or_default();
+    }
+
+    false
+}
+
+#[inline(always)]
+unsafe fn try_security_socket_post_create(ctx: &ProbeContext) -> ProbeResult<()> {
+    // early return if event is disabled
+    if_disabled_return!(Type::SocketCreate, ());
+
+    let family: i32 = kprobe_arg!(ctx, 1)?;
+    let ty: i32 = kprobe_arg!(ctx, 2)?;
+    let protocol: i32 = kprobe_arg!(ctx, 3)?;
+    let kern: i32 = kprobe_arg!(ctx, 4)?;
+
+    // we don't want sockets created by the kernel for itself
+    if kern != 0 {
+        return Ok(());
+    }
+
+    let socket_info = SocketInfo {
+        domain: family as u16,
+        ty: ty as u16,
+    };
+
+    if !is_reported(&socket_info, protocol as u16) {
+        return Ok(());
+    }
+
+    alloc::init()?;
+    let event = alloc::alloc_zero::<SocketCreateEvent>()?;
+
+    event.init_from_current_task(Type::SocketCreate)?;
+
+    event.data.socket_info = socket_info;
+    event.data.protocol = protocol as u16;
+
+    pipe_event(ctx, event);
+
+    Ok(())
+}
@@ -0,0 +1,753 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    io,
+    path::{Component, Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use base64::{
+    engine::general_purpose::{STANDARD, STANDARD_NO_PAD},
+    Engine,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    cache::{self, Cache},
+    util::{accounts::db_records, namespaces::Namespace, sha256_data},
+};
+
+pub use crate::util::accounts::PASSWD_PATH;
+pub const SHADOW_PATH: &str = "/etc/shadow";
+pub const SUDOERS_PATH: &str = "/etc/sudoers";
+pub const SUDOERS_DIR: &str = "/etc/sudoers.d";
+/// Authentication files bigger than this are not parsed
+pub const MAX_FILE_SIZE: u64 = 4 << 20;
+
+/// Files holding authentication or authorization material
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthFile {
+    Passwd,
+    Shadow,
+    Sudoers,
+    AuthorizedKeys,
+}
+
+impl AuthFile {
+    pub fn from_path<P: AsRef<Path>>(p: P) -> Option<Self> {
+        let p = p.as_ref();
+
+        if p == Path::new(PASSWD_PATH) {
+            return Some(Self::Passwd);
+        }
+
+        if p == Path::new(SHADOW_PATH) {
+            return Some(Self::Shadow);
+        }
+
+        if p == Path::new(SUDOERS_PATH) || p.parent() == Some(Path::new(SUDOERS_DIR)) {
+            return Some(Self::Sudoers);
+        }
+
+        let is_keys = p
+            .file_name()
+            .is_some_and(|n| n == "authorized_keys" || n == "authorized_keys2");
+        let in_ssh_dir = p
+            .parent()
+            .and_then(|d| d.file_name())
+            .is_some_and(|n| n == ".ssh");
+
+        if is_keys && in_ssh_dir {
+            return Some(Self::AuthorizedKeys);
+        }
+
+        None
+    }
+}
+
+/// Entry of /etc/passwd and /etc/shadow merged. Passwords are never
+/// kept, only a digest of them is used to detect changes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub name: String,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub home: Option<String>,
+    pub shell: Option<String>,
+    password_digest: Option<String>,
+    pub locked: bool,
+}
+
+impl Account {
+    // fields compared to report modifications
+    fn changes(&self, other: &Self) -> Vec<String> {
+        let mut v = vec![];
+        if self.uid != other.uid {
+            v.push("uid".into());
+        }
+        if self.gid != other.gid {
+            v.push("gid".into());
+        }
+        if self.home != other.home {
+            v.push("home".into());
+        }
+        if self.shell != other.shell {
+            v.push("shell".into());
+        }
+        if self.password_digest != other.password_digest {
+            v.push("password".into());
+        }
+        if self.locked != other.locked {
+            v.push("locked".into());
+        }
+        v
+    }
+}
+
+// a password field not containing an actual hash
+#[inline]
+fn is_password_placeholder(pw: &str) -> bool {
+    pw.is_empty() || pw == "x" || pw == "*" || pw == "!" || pw == "!!" || pw == "!*"
+}
+
+#[inline]
+fn password_digest(pw: &str) -> Option<String> {
+    // locking prefixes are not part of the password
+    let pw = pw.trim_start_matches('!');
+    (!is_password_placeholder(pw)).then(|| sha256_data(pw))
+}
+
+fn parse_passwd(data: &str) -> HashMap<String, Account> {
+    let mut out = HashMap::new();
+    for f in db_records(data).filter(|f| f.len() >= 7) {
+        out.entry(f[0].to_string()).or_insert_with(|| Account {
+            name: f[0].into(),
+            uid: f[2].parse().ok(),
+            gid: f[3].parse().ok(),
+            home: Some(f[5].into()),
+            shell: Some(f[6].into()),
+            // passwords can still be found in passwd
+            password_digest: password_digest(f[1]),
+            locked: false,
+        });
+    }
+    out
+}
+
+fn parse_shadow(data: &str) -> HashMap<String, Account> {
+    let mut out = HashMap::new();
+    for f in db_records(data) {
+        let (Some(name), Some(pw)) = (f.first(), f.get(1)) else {
+            continue;
+        };
+
+        out.entry(name.to_string()).or_insert_with(|| Account {
+            name: name.to_string(),
+            password_digest: password_digest(pw),
+            locked: pw.starts_with('!'),
+            ..Default::default()
+        });
+    }
+    out
+}
+
+// sudoers lines without comments, continuations joined
+// and whitespaces normalized
+fn parse_sudoers(data: &str) -> BTreeSet<String> {
+    let mut out = BTreeSet::new();
+    let mut cur = String::new();
+
+    for line in data.lines() {
+        let line = line.trim();
+        // include directives start with a #
+        if cur.is_empty() && line.starts_with('#') && !line.starts_with("#include") {
+            continue;
+        }
+
+        match line.strip_suffix('\\') {
+            Some(l) => {
+                cur.push_str(l);
+                cur.push(' ');
+            }
+            None => {
+                cur.push_str(line);
+                let norm = cur.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !norm.is_empty() {
+                    out.insert(norm);
+                }
+                cur.clear();
+            }
+        }
+    }
+    out
+}
+
+/// Public key found in an authorized_keys file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshKey {
+    pub key_type: String,
+    /// OpenSSH like SHA256 fingerprint
+    pub fingerprint: String,
+    pub comment: String,
+    pub options: String,
+}
+
+impl SshKey {
+    const TYPE_PREFIXES: [&'static str; 4] = ["ssh-", "ecdsa-", "sk-", "rsa-"];
+
+    fn is_key_type(s: &str) -> bool {
+        Self::TYPE_PREFIXES.iter().any(|p| s.starts_with(p))
+    }
+
+    // splits on whitespaces not found between double quotes
+    fn tokens(line: &str) -> Vec<&str> {
+        let mut out = vec![];
+        let mut start = None;
+        let mut quoted = false;
+        for (i, c) in line.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                c if c.is_whitespace() && !quoted => {
+                    if let Some(s) = start.take() {
+                        out.push(&line[s..i]);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            if start.is_none() {
+                start = Some(i);
+            }
+        }
+        if let Some(s) = start {
+            out.push(&line[s..]);
+        }
+        out
+    }
+
+    pub fn from_line(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let tokens = Self::tokens(line);
+        let pos = tokens.iter().position(|t| Self::is_key_type(t))?;
+        let blob = tokens.get(pos + 1)?;
+
+        let raw = STANDARD.decode(blob).ok()?;
+        let fingerprint = format!("SHA256:{}", STANDARD_NO_PAD.encode(Sha256::digest(raw)));
+
+        Some(Self {
+            key_type: tokens[pos].into(),
+            fingerprint,
+            comment: tokens[pos + 2..].join(" "),
+            options: tokens[..pos].join(" "),
+        })
+    }
+}
+
+fn parse_authorized_keys(data: &str) -> HashMap<String, SshKey> {
+    data.lines()
+        .filter_map(SshKey::from_line)
+        .map(|k| (k.fingerprint.clone(), k))
+        .collect()
+}
+
+/// Returns the user owning an authorized_keys file
+/// (/root/.ssh/... or /home/<user>/.ssh/...)
+pub fn authorized_keys_owner<P: AsRef<Path>>(p: P) -> Option<String> {
+    let mut c = p
+        .as_ref()
+        .components()
+        .skip_while(|c| c == &Component::RootDir);
+    match (c.next(), c.next()) {
+        (Some(Component::Normal(r)), Some(Component::Normal(_))) if r == "root" => {
+            Some("root".into())
+        }
+        (Some(Component::Normal(h)), Some(Component::Normal(u))) if h == "home" => {
+            Some(u.to_string_lossy().into())
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Snapshot {
+    Accounts(HashMap<String, Account>),
+    Sudoers(BTreeSet<String>),
+    Keys(HashMap<String, SshKey>),
+}
+
+impl Snapshot {
+    fn parse(kind: AuthFile, data: &[u8]) -> Self {
+        let data = String::from_utf8_lossy(data);
+        match kind {
+            AuthFile::Passwd => Self::Accounts(parse_passwd(&data)),
+            AuthFile::Shadow => Self::Accounts(parse_shadow(&data)),
+            AuthFile::Sudoers => Self::Sudoers(parse_sudoers(&data)),
+            AuthFile::AuthorizedKeys => Self::Keys(parse_authorized_keys(&data)),
+        }
+    }
+}
+
+/// Semantic change found in an authentication file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    AccountAdded(Account),
+    AccountModified {
+        old: Account,
+        new: Account,
+        changes: Vec<String>,
+    },
+    AccountDeleted(Account),
+    SudoersChanged {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    SshKeyAdded {
+        user: Option<String>,
+        key: SshKey,
+    },
+}
+
+fn diff(path: &Path, old: &Snapshot, new: &Snapshot) -> Vec<Change> {
+    let mut out = vec![];
+    match (old, new) {
+        (Snapshot::Accounts(old), Snapshot::Accounts(new)) => {
+            for (name, n) in new.iter() {
+                match old.get(name) {
+                    None => out.push(Change::AccountAdded(n.clone())),
+                    Some(o) => {
+                        let changes = o.changes(n);
+                        if !changes.is_empty() {
+                            out.push(Change::AccountModified {
+                                old: o.clone(),
+                                new: n.clone(),
+                                changes,
+                            })
+                        }
+                    }
+                }
+            }
+
+            out.extend(
+                old.iter()
+                    .filter(|(name, _)| !new.contains_key(*name))
+                    .map(|(_, o)| Change::AccountDeleted(o.clone())),
+            );
+        }
+        (Snapshot::Sudoers(old), Snapshot::Sudoers(new)) => {
+            let added: Vec<String> = new.difference(old).cloned().collect();
+            let removed: Vec<String> = old.difference(new).cloned().collect();
+            if !added.is_empty() || !removed.is_empty() {
+                out.push(Change::SudoersChanged { added, removed });
+            }
+        }
+        (Snapshot::Keys(old), Snapshot::Keys(new)) => {
+            let user = authorized_keys_owner(path);
+            out.extend(
+                new.iter()
+                    .filter(|(fp, _)| !old.contains_key(*fp))
+                    .map(|(_, k)| Change::SshKeyAdded {
+                        user: user.clone(),
+                        key: k.clone(),
+                    }),
+            );
+        }
+        _ => {}
+    }
+    out
+}
+
+/// Access to the files of mount namespaces
+pub trait FileSource {
+    /// Lists the entries of a directory
+    fn read_dir(&mut self, ns: Namespace, dir: &Path) -> Vec<PathBuf>;
+
+    /// Reads a file, an empty content is returned if the file does not
+    /// exist and None if it cannot be read or is bigger than [MAX_FILE_SIZE]
+    fn read(&mut self, ns: Namespace, path: &Path) -> Option<Vec<u8>>;
+}
+
+impl FileSource for Cache {
+    fn read_dir(&mut self, ns: Namespace, dir: &Path) -> Vec<PathBuf> {
+        self.read_dir_in_ns(ns, dir)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    fn read(&mut self, ns: Namespace, path: &Path) -> Option<Vec<u8>> {
+        match self.read_in_ns(ns, path, MAX_FILE_SIZE) {
+            // a truncated file would be seen as modified
+            Ok(data) if data.len() as u64 > MAX_FILE_SIZE => None,
+            Ok(data) => Some(data),
+            Err(cache::Error::IoError(e)) if e.kind() == io::ErrorKind::NotFound => Some(vec![]),
+            Err(_) => None,
+        }
+    }
+}
+
+/// Returns the authentication files which may be found in a mount namespace
+pub fn candidate_files<S: FileSource>(src: &mut S, ns: Namespace) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = [PASSWD_PATH, SHADOW_PATH, SUDOERS_PATH]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+    files.extend(src.read_dir(ns, Path::new(SUDOERS_DIR)));
+
+    let mut ssh_dirs = vec![PathBuf::from("/root/.ssh")];
+    ssh_dirs.extend(
+        src.read_dir(ns, Path::new("/home"))
+            .into_iter()
+            .map(|h| h.join(".ssh")),
+    );
+    for d in ssh_dirs {
+        files.extend(src.read_dir(ns, &d));
+    }
+
+    files
+        .into_iter()
+        .filter(|p| AuthFile::from_path(p).is_some())
+        .collect()
+}
+
+type Key = (Namespace, PathBuf);
+
+/// Keeps a parsed copy of authentication files (accounts, sudoers and
+/// authorized_keys) per mount namespace and reports semantic changes
+/// when files are parsed again after a write. T is the context given
+/// when a file is modified (i.e. the writer).
+pub struct AuthFiles<T> {
+    settle: Duration,
+    // namespaces whose files have been snapshot
+    baselines: HashSet<Namespace>,
+    snapshots: HashMap<Key, Snapshot>,
+    pending: HashMap<Key, (Instant, T)>,
+}
+
+impl<T: Clone> AuthFiles<T> {
+    pub fn new(settle: Duration) -> Self {
+        Self {
+            settle,
+            baselines: HashSet::new(),
+            snapshots: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Returns true if the files of a namespace have been snapshot
+    #[inline]
+    pub fn has_baseline(&self, ns: Namespace) -> bool {
+        self.baselines.contains(&ns)
+    }
+
+    /// Snapshots the authentication files of a mount namespace
+    /// and returns the number of files found.
+    pub fn build_baseline<S: FileSource>(&mut self, src: &mut S, ns: Namespace) -> usize {
+        let mut n = 0;
+        for p in candidate_files(src, ns) {
+            match src.read(ns, &p) {
+                Some(data) if !data.is_empty() => {
+                    self.snapshot(ns, &p, &data);
+                    n += 1;
+                }
+                _ => {}
+            }
+        }
+        self.baselines.insert(ns);
+        n
+    }
+
+    /// Drops everything known about a mount namespace
+    pub fn forget_ns(&mut self, ns: Namespace) {
+        self.baselines.remove(&ns);
+        self.snapshots.retain(|(n, _), _| *n != ns);
+        self.pending.retain(|(n, _), _| *n != ns);
+    }
+
+    /// Returns true if a snapshot of the file exists
+    #[inline]
+    pub fn has_snapshot(&self, ns: Namespace, path: &Path) -> bool {
+        self.snapshots.contains_key(&(ns, path.to_path_buf()))
+    }
+
+    /// Stores the content of a file as the reference
+    /// future changes are compared with
+    pub fn snapshot(&mut self, ns: Namespace, path: &Path, data: &[u8]) {
+        if let Some(kind) = AuthFile::from_path(path) {
+            self.snapshots
+                .insert((ns, path.to_path_buf()), Snapshot::parse(kind, data));
+        }
+    }
+
+    /// Schedules the parsing of a modified file. It returns
+    /// false if the file is not an authentication file.
+    pub fn track(&mut self, ns: Namespace, path: &Path, context: T) -> bool {
+        if AuthFile::from_path(path).is_none() {
+            return false;
+        }
+        self.pending.insert(
+            (ns, path.to_path_buf()),
+            (Instant::now() + self.settle, context),
+        );
+        true
+    }
+
+    /// Parses files whose settle time elapsed and returns the changes
+    /// found. Shadow changes are completed with account information
+    /// found in passwd snapshot.
+    pub fn poll<S: FileSource>(&mut self, src: &mut S) -> Vec<(T, PathBuf, Change)> {
+        let now = Instant::now();
+        let due: Vec<Key> = self
+            .pending
+            .iter()
+            .filter(|(_, (deadline, _))| *deadline <= now)
+            .map(|(k, _)| k.clone())
+            .collect();
+
+        let mut out = vec![];
+        for key in due {
+            let Some((_, ctx)) = self.pending.remove(&key) else {
+                continue;
+            };
+
+            let (ns, path) = &key;
+            let Some(kind) = AuthFile::from_path(path) else {
+                continue;
+            };
+
+            // namespace may have disappeared, we keep old snapshot
+            let Some(data) = src.read(*ns, path) else {
+                continue;
+            };
+            let new = Snapshot::parse(kind, &data);
+
+            let old = match self.snapshots.insert(key.clone(), new.clone()) {
+                Some(old) => old,
+                // file created after the baseline
+                None if self.baselines.contains(ns) => Snapshot::parse(kind, &[]),
+                // we have no reference to compare with
+                None => continue,
+            };
+
+            for mut c in diff(path, &old, &new) {
+                if kind == AuthFile::Shadow {
+                    self.complete_account(*ns, &mut c);
+                }
+                out.push((ctx.clone(), path.clone(), c));
+            }
+        }
+        out
+    }
+
+    // fills account information of shadow changes from passwd
+    fn complete_account(&self, ns: Namespace, c: &mut Change) {
+        let Some(Snapshot::Accounts(passwd)) =
+            self.snapshots.get(&(ns, PathBuf::from(PASSWD_PATH)))
+        else {
+            return;
+        };
+
+        let accounts = match c {
+            Change::AccountAdded(a) | Change::AccountDeleted(a) => vec![a],
+            Change::AccountModified { old, new, .. } => vec![old, new],
+            _ => vec![],
+        };
+
+        for a in accounts {
+            if let Some(p) = passwd.get(&a.name) {
+                a.uid = p.uid;
+                a.gid = p.gid;
+                a.home.clone_from(&p.home);
+                a.shell.clone_from(&p.shell);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ns() -> Namespace {
+        Namespace::mnt(4026531841)
+    }
+
+    // in memory files, missing files are read as empty and
+    // None content stands for an unreadable file
+    #[derive(Default)]
+    struct Files(HashMap<PathBuf, Option<Vec<u8>>>);
+
+    impl Files {
+        fn set<P: AsRef<Path>>(&mut self, p: P, data: Option<&[u8]>) {
+            self.0
+                .insert(p.as_ref().to_path_buf(), data.map(|d| d.to_vec()));
+        }
+    }
+
+    impl FileSource for Files {
+        fn read_dir(&mut self, _ns: Namespace, dir: &Path) -> Vec<PathBuf> {
+            let mut entries: Vec<PathBuf> = self
+                .0
+                .keys()
+                .filter_map(|p| {
+                    p.strip_prefix(dir)
+                        .ok()
+                        .and_then(|r| r.components().next())
+                        .map(|c| dir.join(c))
+                })
+                .collect();
+            entries.sort();
+            entries.dedup();
+            entries
+        }
+
+        fn read(&mut self, _ns: Namespace, path: &Path) -> Option<Vec<u8>> {
+            self.0.get(path).cloned().unwrap_or(Some(vec![]))
+        }
+    }
+
+    #[test]
+    fn test_auth_file() {
+        assert_eq!(AuthFile::from_path("/etc/passwd"), Some(AuthFile::Passwd));
+        assert_eq!(
+            AuthFile::from_path("/etc/sudoers.d/admins"),
+            Some(AuthFile::Sudoers)
+        );
+        assert_eq!(
+            AuthFile::from_path("/home/bob/.ssh/authorized_keys"),
+            Some(AuthFile::AuthorizedKeys)
+        );
+        assert_eq!(AuthFile::from_path("/home/bob/authorized_keys"), None);
+        assert_eq!(AuthFile::from_path("/etc/group"), None);
+
+        assert_eq!(
+            authorized_keys_owner("/home/bob/.ssh/authorized_keys"),
+            Some("bob".into())
+        );
+        assert_eq!(
+            authorized_keys_owner("/root/.ssh/authorized_keys"),
+            Some("root".into())
+        );
+        assert_eq!(authorized_keys_owner("/srv/.ssh/authorized_keys"), None);
+    }
+
+    #[test]
+    fn test_ssh_key() {
+        // fingerprint checked against ssh-keygen -lf
+        let k = SshKey::from_line(
+            r#"from="10.0.0.1,10.0.0.2",no-pty ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl bob@host"#,
+        )
+        .unwrap();
+        assert_eq!(k.key_type, "ssh-ed25519");
+        assert_eq!(k.options, r#"from="10.0.0.1,10.0.0.2",no-pty"#);
+        assert_eq!(k.comment, "bob@host");
+        assert_eq!(
+            k.fingerprint,
+            "SHA256:+DiY3wvvV6TuJJhbpZisF/zLDA0zPMSvHdkr4UvCOqU"
+        );
+
+        assert!(SshKey::from_line("# comment").is_none());
+        assert!(SshKey::from_line("ssh-rsa not_base64!").is_none());
+    }
+
+    #[test]
+    fn test_sudoers() {
+        let s = parse_sudoers(
+            "# comment\nroot ALL=(ALL:ALL) ALL\n#includedir /etc/sudoers.d\nbob  ALL=(ALL) \\\n NOPASSWD: ALL\n",
+        );
+        assert_eq!(s.len(), 3);
+        assert!(s.contains("bob ALL=(ALL) NOPASSWD: ALL"));
+        assert!(s.contains("#includedir /etc/sudoers.d"));
+    }
+
+    #[test]
+    fn test_changes() {
+        let mut af: AuthFiles<u32> = AuthFiles::new(Duration::from_millis(0));
+        let mut files = Files::default();
+        let passwd = Path::new(PASSWD_PATH);
+        let shadow = Path::new(SHADOW_PATH);
+
+        files.set(passwd, Some(b"root:x:0:0:root:/root:/bin/bash\n"));
+        files.set(shadow, Some(b"root:$6$salt$hash:19000:0:99999:7:::\n"));
+        assert_eq!(af.build_baseline(&mut files, ns()), 2);
+        assert!(af.has_baseline(ns()));
+
+        // not an auth file
+        assert!(!af.track(ns(), Path::new("/etc/hosts"), 0));
+
+        assert!(af.track(ns(), passwd, 1));
+        files.set(
+            passwd,
+            Some(b"root:x:0:0:root:/root:/bin/sh\nevil:x:0:0::/tmp:/bin/bash\n"),
+        );
+        let changes = af.poll(&mut files);
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|(ctx, _, _)| *ctx == 1));
+        assert!(changes.iter().any(|(_, _, c)| matches!(c,
+            Change::AccountModified { changes, .. } if changes == &vec!["shell".to_string()])));
+        assert!(changes.iter().any(|(_, _, c)| matches!(c,
+            Change::AccountAdded(a) if a.name == "evil" && a.uid == Some(0))));
+
+        // password change is reported but not the password
+        assert!(af.track(ns(), shadow, 2));
+        files.set(shadow, Some(b"root:$6$salt$other:19000:0:99999:7:::\n"));
+        let changes = af.poll(&mut files);
+        assert_eq!(changes.len(), 1);
+        let Change::AccountModified { changes, new, .. } = &changes[0].2 else {
+            panic!("unexpected change");
+        };
+        assert_eq!(changes, &vec!["password".to_string()]);
+        assert_eq!(new.shell.as_deref(), Some("/bin/sh"));
+        assert!(!format!("{:?}", new).contains("other"));
+
+        // unreadable file does not produce changes
+        assert!(af.track(ns(), passwd, 3));
+        files.set(passwd, None);
+        assert!(af.poll(&mut files).is_empty());
+        assert!(af.has_snapshot(ns(), passwd));
+
+        // file created after the baseline is compared with an empty content
+        let keys = Path::new("/root/.ssh/authorized_keys");
+        assert!(!af.has_snapshot(ns(), keys));
+        assert!(af.track(ns(), keys, 4));
+        files.set(
+            keys,
+            Some(b"ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIOMqqnkVzrm0SdG6UOoqKLsabgH5C9okWi0dh2l9GKJl\n"),
+        );
+        let changes = af.poll(&mut files);
+        assert!(matches!(&changes[0].2, Change::SshKeyAdded { user: Some(u), .. } if u == "root"));
+    }
+
+    #[test]
+    fn test_baseline() {
+        let mut af: AuthFiles<u32> = AuthFiles::new(Duration::from_millis(0));
+        let mut files = Files::default();
+        let keys = Path::new("/home/bob/.ssh/authorized_keys");
+
+        files.set(PASSWD_PATH, Some(b"root:x:0:0:root:/root:/bin/bash\n"));
+        files.set(keys, Some(b""));
+        files.set("/home/bob/.ssh/known_hosts", Some(b""));
+
+        let candidates = candidate_files(&mut files, ns());
+        assert!(candidates.contains(&keys.to_path_buf()));
+        assert!(!candidates.contains(&PathBuf::from("/home/bob/.ssh/known_hosts")));
+
+        // no baseline, changes cannot be known
+        let passwd = Path::new(PASSWD_PATH);
+        assert!(af.track(ns(), passwd, 1));
+        assert!(af.poll(&mut files).is_empty());
+        assert!(af.has_snapshot(ns(), passwd));
+
+        // empty files are not snapshot
+        assert_eq!(af.build_baseline(&mut files, ns()), 1);
+        assert!(!af.has_snapshot(ns(), keys));
+
+        af.forget_ns(ns());
+        assert!(!af.has_baseline(ns()));
+        assert!(!af.has_snapshot(ns(), passwd));
+    }
+}
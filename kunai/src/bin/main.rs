@@ -4,27 +4,39 @@ use anyhow::anyhow;
 use aya::maps::MapData;
 use bytes::BytesMut;
 
+use chrono::DateTime;
 use clap::builder::styling;
 use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use env_logger::Builder;
 use gene::rules::MAX_SEVERITY;
 use gene::Engine;
-use kunai::containers::Container;
+use kunai::auth_files::{self, Account, AuthFiles};
+use kunai::containers::{self, k8s, Container};
 use kunai::events::{
-    BpfProgLoadData, BpfProgTypeInfo, BpfSocketFilterData, CloneData, ConnectData, DnsQueryData,
-    ExecveData, ExitData, FileRenameData, FilterInfo, InitModuleData, KillData, KunaiEvent,
-    MmapExecData, MprotectData, NetworkInfo, PrctlData, RWData, ScanResult, SendDataData,
-    SocketInfo, TargetTask, UnlinkData, UserEvent,
+    AccountData, BpfAttachTypeInfo, BpfMapInfo, BpfProgAttachData, BpfProgLoadData,
+    BpfProgTypeInfo, BpfSocketFilterData, CloneData, ConnectData, DeleteModuleData, DnsQueryData,
+    Environment, ExecveData, ExecveFailedData, ExitData, FdSection, FileChmodData, FileChownData,
+    FileIntegrityData, FileLinkData, FileRenameData, FileSymlinkData, FileTimesData, FileXattrData,
+    FilelessSection, FilterInfo, InitModuleData, IoUringOpData, IoUringSetupData, KernelTamperData,
+    KillData, KunaiEvent, MemfdCreateData, MmapExecData, MprotectData, NetworkInfo, PrctlData,
+    ProcessAccessData, RWData, ScanResult, SendDataData, SocketCreateData, SocketInfo, SocketPeer,
+    SshKeyData, StdioSection, SudoersData, TargetTask, UnixSocketInfo, UnlinkData, UserEvent,
+    UtcDateTime, VmaSection, VIA_IO_URING,
 };
+use kunai::fim::Fim;
 use kunai::info::{AdditionalInfo, StdEventInfo, TaskKey};
 use kunai::ioc::IoC;
+use kunai::sessions::{Peer, Sessions};
 use kunai::util::uname::Utsname;
 use kunai::{cache, util};
 use kunai_common::bpf_events::{
-    self, error, event, mut_event, EncodedEvent, Event, PrctlOption, Signal, Type,
-    MAX_BPF_EVENT_SIZE,
+    self, error, event, mut_event, EncodedEvent, Event, PrctlOption, Signal, TamperKind, Type,
+    VmaKind, MAX_BPF_EVENT_SIZE,
 };
-use kunai_common::config::{BpfConfig, Filter};
+use kunai_common::config::{BpfConfig, Filter, PathFilter};
+use kunai_common::consts::{AF_UNIX, MAP_ANONYMOUS, PROT_EXEC, PROT_READ, PROT_WRITE};
+use kunai_common::net::{NetlinkProtocol, SaFamily};
+use kunai_common::time::Time;
 use kunai_common::{inspect_err, kernel};
 
 use kunai_common::version::KernelVersion;
@@ -48,7 +60,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use std::process;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use aya::{
     include_bytes_aligned,
@@ -69,11 +81,31 @@ use kunai::cache::*;
 
 use kunai::compat::Programs;
 use kunai::config::Config;
+use kunai::util::accounts;
+use kunai::util::bpf::insn;
+use kunai::util::kmod::{self, ModuleInfo};
 use kunai::util::namespaces::{unshare, Namespace};
 use kunai::util::*;
 
 const PAGE_SIZE: usize = 4096;
+const HASH_CACHE_SAVE_INTERVAL: Duration = Duration::from_secs(300);
+// interval at which background work (hashes, baselines, file changes) is polled
+const CONSUMER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// maximum number of events processed at once by the consumer
+const CONSUMER_BATCH_SIZE: usize = 256;
 const KERNEL_IMAGE: &str = "kernel";
+// delay after a write before authentication files are parsed
+const AUTH_FILES_SETTLE: Duration = Duration::from_millis(500);
+// maximum number of executions we keep command line chunks for
+const MAX_PENDING_ARGV: usize = 1024;
+// events generated from authentication files changes
+const AUTH_FILES_EVENTS: [Type; 5] = [
+    Type::AccountAdded,
+    Type::AccountModified,
+    Type::AccountDeleted,
+    Type::SudoersChanged,
+    Type::SshKeyAdded,
+];
 
 #[derive(Debug, Clone)]
 struct Task {
@@ -84,10 +116,13 @@ struct Task {
     flags: u32,
     resolved: HashMap<IpAddr, String>,
     container: Option<Container>,
+    container_id: Option<String>,
     // needs to be vec because of procfs
     cgroups: Vec<String>,
     nodename: Option<String>,
     parent_key: Option<TaskKey>,
+    // network peer the task is bound to, inherited by children
+    peer: Option<Peer>,
 }
 
 impl Task {
@@ -174,12 +209,42 @@ impl io::Write for Output {
     }
 }
 
+/// Task modifying an authentication file, reported with
+/// the changes found in the file
+#[derive(Clone)]
+struct AuthFileWriter {
+    info: StdEventInfo,
+    ancestors: String,
+    command_line: String,
+    exe: PathBuf,
+}
+
 struct EventConsumer {
     system_info: SystemInfo,
     engine: gene::Engine,
     iocs: HashSet<String>,
     random: u32,
     cache: cache::Cache,
+    cache_saved: Instant,
+    // file integrity monitoring, changes are reported with the writer
+    fim: Option<Fim<UserEvent<FileIntegrityData>>>,
+    // accounts, sudoers and ssh keys changes
+    auth_files: Option<AuthFiles<AuthFileWriter>>,
+    // mount namespaces whose baselines are waiting to be built
+    pending_baselines: VecDeque<Namespace>,
+    // configuration files patterns, also used in eBPF
+    config_files: PathFilter,
+    k8s: Option<k8s::Resolver>,
+    containers: containers::Detector,
+    sessions: Sessions,
+    // names of environment variables to capture
+    env_vars: Option<HashSet<String>>,
+    // command line chunks waiting for the execve event they belong to
+    argv_chunks: HashMap<TaskKey, Vec<(u32, Vec<u8>)>>,
+    // adds disassembly to bpf_prog_load events
+    bpf_disassembly: bool,
+    // built at first bpf program analysis
+    bpf_helpers: Option<insn::HelperResolver>,
     tasks: HashMap<TaskKey, Task>,
     resolved: HashMap<IpAddr, String>,
     output: Output,
@@ -240,13 +305,96 @@ impl EventConsumer {
             engine: Engine::new(),
             iocs: HashSet::new(),
             random: util::getrandom::<u32>().unwrap(),
-            cache: Cache::with_max_entries(10000),
+            cache: Cache::with_max_entries(10000).with_options(
+                config
+                    .hashing
+                    .as_ref()
+                    .map(|h| h.options())
+                    .unwrap_or_default(),
+            ),
+            cache_saved: Instant::now(),
+            fim: None,
+            auth_files: None,
+            pending_baselines: VecDeque::new(),
+            config_files: config.config_files_filter()?,
+            k8s: config
+                .kubernetes
+                .as_ref()
+                .filter(|k| k.enable)
+                .map(|k| k8s::Resolver::with_state_dirs(k.state_dirs.clone())),
+            containers: config.container_detector()?,
+            sessions: Sessions::default(),
+            env_vars: config
+                .env_capture
+                .as_ref()
+                .filter(|e| e.enable)
+                .map(|e| e.variables()),
+            argv_chunks: HashMap::new(),
+            bpf_disassembly: config.bpf_prog.as_ref().is_some_and(|b| b.disassemble),
+            bpf_helpers: None,
             tasks: HashMap::new(),
             resolved: HashMap::new(),
             output: Self::prepare_output(&config)?,
             task: None,
         };
 
+        // hashing files in background so that events are not blocked by I/O
+        if let Some(h) = config.hashing.as_ref() {
+            ep.cache
+                .enable_async_hashing(
+                    h.workers,
+                    h.queue_size,
+                    Duration::from_millis(h.max_wait_ms),
+                )
+                .map_err(|e| anyhow!("failed to start hashing workers: {e}"))?;
+        }
+
+        // building file integrity baseline of the host, baselines
+        // of other mount namespaces are built when they are met
+        if let Some(fs) = config.fim.as_ref().filter(|f| f.enable) {
+            if Filter::try_from(&config)?.is_enabled(Type::FileIntegrity) {
+                for p in config.fim_paths_without_events()? {
+                    warn!("file integrity monitoring of {p} requires write events to be enabled");
+                }
+
+                let mut fim = Fim::new(&fs.paths, Duration::from_millis(fs.settle_ms))?;
+                let pid = process::id() as i32;
+                let ns = ep.system_info.mount_ns;
+                ep.cache.cache_ns(pid, pid, ns)?;
+                info!(
+                    "file integrity baseline: {} files",
+                    fim.build_baseline(&mut ep.cache, ns)
+                );
+                ep.fim = Some(fim);
+            }
+        }
+
+        // parsing authentication files of the host, files of
+        // other mount namespaces are parsed when they are met
+        let filter = Filter::try_from(&config)?;
+        if AUTH_FILES_EVENTS.iter().any(|t| filter.is_enabled(*t)) {
+            let mut af = AuthFiles::new(AUTH_FILES_SETTLE);
+            let pid = process::id() as i32;
+            let ns = ep.system_info.mount_ns;
+            ep.cache.cache_ns(pid, pid, ns)?;
+            info!(
+                "authentication files baseline: {} files",
+                af.build_baseline(&mut ep.cache, ns)
+            );
+            ep.auth_files = Some(af);
+        }
+
+        // loading persisted hashes
+        if let Some(hc) = config.hash_cache.as_ref() {
+            match ep
+                .cache
+                .enable_persistence(&hc.path, &hc.secret_path, hc.max_entries)
+            {
+                Ok(n) => info!("loaded {n} entries from hash cache: {}", hc.path.display()),
+                Err(e) => warn!("failed to load hash cache {}: {e}", hc.path.display()),
+            }
+        }
+
         // loading rules in the engine
         if !config.rules.is_empty() {
             for rule in config.rules.iter() {
@@ -291,10 +439,34 @@ impl EventConsumer {
 
         // we spawn thread only if there is a receiver
         ep.write().await.task = Some(tokio::spawn(async move {
-            while let Some(mut enc) = receiver.recv().await {
-                // lock error is a symptom of implementation mistake so we panic
-                let mut ep = shared.write().await;
-                ep.handle_event(&mut enc);
+            let mut ticker = time::interval(CONSUMER_POLL_INTERVAL);
+            ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+
+            loop {
+                let mut batch = Vec::new();
+                tokio::select! {
+                    n = receiver.recv_many(&mut batch, CONSUMER_BATCH_SIZE) => {
+                        // channel is closed
+                        if n == 0 {
+                            break;
+                        }
+                    }
+                    _ = ticker.tick() => {}
+                }
+
+                let shared = Arc::clone(&shared);
+                // event processing may wait for hashes computed in background
+                // or walk directories, so it must not block the async runtime
+                task::spawn_blocking(move || {
+                    let mut ep = shared.blocking_write();
+                    for enc in batch.iter_mut() {
+                        // collecting hashes computed in background
+                        ep.cache.poll();
+                        ep.handle_event(enc);
+                    }
+                    ep.poll();
+                })
+                .await?;
             }
 
             Ok::<(), anyhow::Error>(())
@@ -303,6 +475,38 @@ impl EventConsumer {
         Ok(ep)
     }
 
+    // saving is done in background not to block event processing,
+    // the outcome of a save is known at the next one
+    /// Processes the work done in background or delayed outside of
+    /// event processing, it is called periodically even without events.
+    fn poll(&mut self) {
+        self.cache.poll();
+        self.poll_baselines();
+        self.poll_fim();
+        self.poll_auth_files();
+
+        if self.cache_saved.elapsed() > HASH_CACHE_SAVE_INTERVAL {
+            self.save_hash_cache_in_background();
+        }
+    }
+
+    fn save_hash_cache_in_background(&mut self) {
+        match self.cache.save_in_background() {
+            Some(Ok(n)) => debug!("saved {n} entries to hash cache"),
+            Some(Err(e)) => error!("failed to save hash cache: {e}"),
+            None => {}
+        }
+        self.cache_saved = Instant::now();
+    }
+
+    fn save_hash_cache(&mut self) {
+        match self.cache.save() {
+            Ok(n) => debug!("saved {n} entries to hash cache"),
+            Err(e) => error!("failed to save hash cache: {e}"),
+        }
+        self.cache_saved = Instant::now();
+    }
+
     fn load_iocs<P: AsRef<Path>>(&mut self, p: P) -> io::Result<()> {
         let p = p.as_ref();
         let f = io::BufReader::new(File::open(p)?);
@@ -337,8 +541,9 @@ impl EventConsumer {
             if let Some(parent) = pk {
                 if let Some(t) = self.tasks.get_mut(&tk) {
                     // trying to find container type in cgroups
-                    t.container = Container::from_cgroups(&t.cgroups);
-                    if t.container.is_some() {
+                    if let Some((c, id)) = self.containers.from_cgroups(&t.cgroups) {
+                        t.container = Some(c);
+                        t.container_id = id;
                         // we don't need to do the ancestor's lookup
                         continue;
                     }
@@ -346,7 +551,7 @@ impl EventConsumer {
 
                 // lookup in ancestors
                 let ancestors = self.get_ancestors(parent, 0);
-                if let Some(c) = Container::from_ancestors(&ancestors) {
+                if let Some(c) = self.containers.from_ancestors(&ancestors) {
                     self.tasks
                         .entry(tk)
                         .and_modify(|task| task.container = Some(c));
@@ -399,8 +604,12 @@ impl EventConsumer {
             flags: stat.flags,
             resolved: HashMap::new(),
             container: None,
+            container_id: None,
             cgroups,
             nodename: None,
+            peer: parent_key
+                .and_then(|pk| self.tasks.get(&pk))
+                .and_then(|t| t.peer),
             parent_key,
         };
 
@@ -558,6 +767,59 @@ impl EventConsumer {
         }
     }
 
+    /// gets hashes of a file which may not be reachable through its path
+    /// (memfd, O_TMPFILE or deleted file). In such a case hashes are
+    /// computed through a proxy path in /proc.
+    #[inline]
+    fn get_hashes_fileless<P: AsRef<std::path::Path>>(
+        &mut self,
+        ns: Option<Namespace>,
+        p: &kunai_common::path::Path,
+        fileless: u8,
+        proxy: P,
+    ) -> Hashes {
+        if fileless == 0 {
+            return self.get_hashes_with_ns(ns, p);
+        }
+
+        match self.cache.get_or_cache_with_proxy(p, proxy) {
+            Ok(h) => h,
+            Err(e) => Hashes {
+                file: p.to_path_buf(),
+                error: Some(format!("{e}")),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Rebuilds the command line of an execution out of the execve
+    /// event and the chunks streamed before it
+    fn command_line(&mut self, key: TaskKey, event: &bpf_events::ExecveEvent) -> (String, bool) {
+        let mut argv = event.data.argv.as_slice().to_vec();
+        let mut truncated = event.data.argv_truncated;
+
+        if let Some(mut chunks) = self.argv_chunks.remove(&key) {
+            chunks.sort_unstable_by_key(|(off, _)| *off);
+            for (off, chunk) in chunks {
+                // a chunk got lost, we cannot go further
+                if off as usize != argv.len() {
+                    truncated = true;
+                    break;
+                }
+                argv.extend_from_slice(&chunk);
+            }
+        }
+
+        let command_line = argv
+            .split(|&b| b == b'\0')
+            .map(String::from_utf8_lossy)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        (command_line, truncated)
+    }
+
     #[inline]
     fn execve_event(
         &mut self,
@@ -568,11 +830,39 @@ impl EventConsumer {
 
         let opt_mnt_ns = Self::task_mnt_ns(&event.info);
 
+        // /proc/<pid>/exe points to the interpreter so we can only
+        // use it when the executable is not a script
+        let exe = if event.data.executable == event.data.interpreter {
+            self.get_hashes_fileless(
+                opt_mnt_ns,
+                &event.data.executable,
+                event.data.fileless,
+                format!("/proc/{}/exe", event.info.process.tgid),
+            )
+        } else {
+            self.get_hashes_with_ns(opt_mnt_ns, &event.data.executable)
+        };
+
+        let (command_line, command_line_truncated) = self.command_line(info.task_key(), event);
+
         let mut data = ExecveData {
             ancestors: ancestors.join("|"),
             parent_exe: self.get_parent_image(&info),
-            command_line: event.data.argv.to_command_line(),
-            exe: self.get_hashes_with_ns(opt_mnt_ns, &event.data.executable),
+            command_line,
+            command_line_truncated,
+            env: self.env_vars.as_ref().map(|names| {
+                Environment::from_block(event.data.env.as_slice(), event.data.env_truncated, names)
+            }),
+            env_truncated: self.env_vars.is_some() && event.data.env_truncated,
+            cwd: event.data.cwd.to_path_buf(),
+            tty: Some(event.data.tty.to_string_lossy().to_string()).filter(|s| !s.is_empty()),
+            stdio: StdioSection {
+                stdin: Self::fd_section(&event.data.stdio[0]),
+                stdout: Self::fd_section(&event.data.stdio[1]),
+                stderr: Self::fd_section(&event.data.stdio[2]),
+            },
+            exe,
+            fileless: FilelessSection::from_flags(event.data.fileless),
             interpreter: None,
         };
 
@@ -583,6 +873,97 @@ impl EventConsumer {
         UserEvent::new(data, info)
     }
 
+    /// learns the network peer of tasks and the tty of sessions from execve
+    /// events so that we can later know where sessions come from
+    #[inline]
+    fn update_exec_context(&mut self, info: &StdEventInfo, event: &bpf_events::ExecveEvent) {
+        // a task with stdin bound to an INET socket is bound to a network peer,
+        // this is the case of sshd re-executing itself for every new connection
+        let stdin = &event.data.stdio[0];
+        if stdin.ty == bpf_events::FdType::Socket as u8 && !stdin.peer.is_zero() {
+            let exe = event.data.executable.to_path_buf();
+            let ssh = exe
+                .file_name()
+                .is_some_and(|n| n == "sshd" || n == "sshd-session");
+
+            if let Some(t) = self.tasks.get_mut(&info.task_key()) {
+                t.peer = Some(Peer {
+                    ip: stdin.peer.into(),
+                    port: stdin.peer.port(),
+                    ssh,
+                });
+            }
+        }
+
+        if let Some(sid) = info.info.process.sessionid() {
+            let tty = event.data.tty.to_string_lossy();
+            self.sessions.update(
+                sid,
+                info.info.process.loginuid(),
+                None,
+                Some(tty.as_ref()).filter(|t| !t.is_empty()),
+            );
+        }
+    }
+
+    #[inline]
+    fn fd_section(fd: &bpf_events::StdFd) -> FdSection {
+        let ty = bpf_events::FdType::try_from_uint(fd.ty).unwrap_or(bpf_events::FdType::Unknown);
+
+        let mut s = FdSection {
+            ty: ty.as_str().into(),
+            path: None,
+            socket: None,
+        };
+
+        match ty {
+            bpf_events::FdType::None | bpf_events::FdType::Pipe => {}
+            bpf_events::FdType::Socket => {
+                let ip = (!fd.peer.is_zero()).then(|| IpAddr::from(fd.peer));
+                let peer_pid = (fd.peer_pid != 0).then_some(fd.peer_pid);
+                s.socket = Some(SocketPeer {
+                    domain: fd.socket.domain_to_string(),
+                    ty: fd.socket.type_to_string(),
+                    ip,
+                    port: ip.map(|_| fd.peer.port()),
+                    peer_pid,
+                    // peer_pid is a pid in the initial pid namespace
+                    peer_exe: peer_pid
+                        .and_then(|pid| fs::read_link(format!("/proc/{pid}/exe")).ok()),
+                })
+            }
+            _ => s.path = Some(fd.path.to_path_buf()),
+        }
+
+        s
+    }
+
+    #[inline]
+    fn execve_failed_event(
+        &mut self,
+        info: StdEventInfo,
+        event: &bpf_events::ExecveEvent,
+    ) -> UserEvent<ExecveFailedData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+        let opt_mnt_ns = Self::task_mnt_ns(&event.info);
+        let rc = event.data.rc;
+
+        let data = ExecveFailedData {
+            ancestors: self.get_ancestors_string(&info),
+            exe: exe.into(),
+            command_line,
+            attempted_exe: self.get_hashes_with_ns(opt_mnt_ns, &event.data.executable),
+            attempted_command_line: event.data.argv.to_command_line(),
+            attempted_command_line_truncated: event.data.argv_truncated,
+            errno: util::errno_name(rc)
+                .map(String::from)
+                .unwrap_or(format!("unknown({rc})")),
+            rc,
+        };
+
+        UserEvent::new(data, info)
+    }
+
     #[inline]
     fn clone_event(
         &mut self,
@@ -598,6 +979,83 @@ impl EventConsumer {
         UserEvent::new(data, info)
     }
 
+    #[inline]
+    fn memfd_create_event(
+        &mut self,
+        info: StdEventInfo,
+        event: &bpf_events::MemfdCreateEvent,
+    ) -> UserEvent<MemfdCreateData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let data = MemfdCreateData {
+            ancestors: self.get_ancestors_string(&info),
+            exe: exe.into(),
+            command_line,
+            name: event.data.name.to_string_lossy().into(),
+            flags: event.data.flags,
+            fd: event.data.rc,
+            success: event.data.rc >= 0,
+        };
+
+        UserEvent::new(data, info)
+    }
+
+    #[inline]
+    fn io_uring_setup_event(
+        &self,
+        info: StdEventInfo,
+        event: &bpf_events::IoUringSetupEvent,
+    ) -> UserEvent<IoUringSetupData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let data = IoUringSetupData {
+            ancestors: self.get_ancestors_string(&info),
+            exe: exe.into(),
+            command_line,
+            entries: event.data.entries,
+            sq_entries: event.data.sq_entries,
+            cq_entries: event.data.cq_entries,
+            flags: event.data.flags,
+            sqpoll: event.data.flags & bpf_events::IORING_SETUP_SQPOLL != 0,
+            fd: event.data.rc,
+            success: event.data.rc >= 0,
+        };
+
+        UserEvent::new(data, info)
+    }
+
+    #[inline]
+    fn io_uring_op_event(
+        &self,
+        info: StdEventInfo,
+        event: &bpf_events::IoUringOpEvent,
+    ) -> UserEvent<IoUringOpData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let operation = bpf_events::IoUringOpcode::try_from_uint(event.data.opcode)
+            .map(|o| o.as_str().into())
+            .unwrap_or(format!("unknown({})", event.data.opcode));
+
+        let path = if !event.data.name.is_empty() {
+            Some(event.data.name.to_string_lossy().into_owned().into())
+        } else if !event.data.path.is_empty() {
+            Some(event.data.path.to_path_buf())
+        } else {
+            None
+        };
+
+        let data = IoUringOpData {
+            ancestors: self.get_ancestors_string(&info),
+            exe: exe.into(),
+            command_line,
+            operation,
+            opcode: event.data.opcode,
+            path,
+        };
+
+        UserEvent::new(data, info)
+    }
+
     #[inline]
     fn prctl_event(
         &mut self,
@@ -658,6 +1116,45 @@ impl EventConsumer {
         UserEvent::new(data, info)
     }
 
+    #[inline]
+    fn process_access_event(
+        &mut self,
+        info: StdEventInfo,
+        event: &bpf_events::ProcessAccessEvent,
+    ) -> UserEvent<ProcessAccessData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let kind = bpf_events::AccessKind::try_from_uint(event.data.kind);
+        let operation = kind
+            .map(|k| k.as_str().into())
+            .unwrap_or(format!("unknown({})", event.data.kind));
+        let ptrace_request = matches!(kind, Ok(bpf_events::AccessKind::Ptrace)).then(|| {
+            bpf_events::PtraceRequest::from_uint_to_string(event.data.ptrace_request as u64)
+        });
+
+        // we need to set uuid part of target task
+        let mut target = event.data.target;
+        target.set_uuid_random(self.random);
+
+        let tk = TaskKey::from(target.tg_uuid);
+
+        let data = ProcessAccessData {
+            ancestors: self.get_ancestors_string(&info),
+            exe: exe.into(),
+            command_line,
+            operation,
+            ptrace_request,
+            target: TargetTask {
+                command_line: self.get_command_line(tk),
+                exe: self.get_exe(tk).into(),
+                task: target.into(),
+            },
+            success: event.data.rc >= 0,
+        };
+
+        UserEvent::new(data, info)
+    }
+
     #[inline]
     fn mmap_exec_event(
         &mut self,
@@ -665,8 +1162,17 @@ impl EventConsumer {
         event: &bpf_events::MmapExecEvent,
     ) -> UserEvent<kunai::events::MmapExecData> {
         let filename = event.data.filename;
-        let opt_mnt_ns = Self::task_mnt_ns(&event.info);
-        let mmapped_hashes = self.get_hashes_with_ns(opt_mnt_ns, &filename);
+        let anonymous = event.data.flags & MAP_ANONYMOUS as u64 == MAP_ANONYMOUS as u64;
+
+        let mmapped_hashes = (!anonymous).then(|| {
+            let opt_mnt_ns = Self::task_mnt_ns(&event.info);
+            self.get_hashes_fileless(
+                opt_mnt_ns,
+                &filename,
+                event.data.fileless,
+                format!("/proc/{}/fd/{}", event.info.process.tgid, event.data.fd),
+            )
+        });
 
         let ck = info.task_key();
 
@@ -677,6 +1183,11 @@ impl EventConsumer {
             command_line: self.get_command_line(ck),
             exe: exe.into(),
             mapped: mmapped_hashes,
+            fileless: FilelessSection::from_flags(event.data.fileless),
+            anonymous,
+            len: event.data.len,
+            prot: event.data.prot,
+            flags: event.data.flags,
         };
 
         UserEvent::new(data, info)
@@ -747,6 +1258,7 @@ impl EventConsumer {
             command_line,
             exe: exe.into(),
             path: event.data.path.to_path_buf(),
+            via: event.data.io_uring.then(|| VIA_IO_URING.into()),
         };
 
         UserEvent::new(data, info)
@@ -798,6 +1310,11 @@ impl EventConsumer {
                 sha256: "?".into(),
                 sha512: "?".into(),
                 size: 0,
+                insn_count: 0,
+                helpers: vec![],
+                dangerous_helpers: vec![],
+                maps: vec![],
+                disassembly: None,
             },
             verified_insns: event.data.verified_insns,
             loaded: event.data.loaded,
@@ -811,6 +1328,107 @@ impl EventConsumer {
             data.bpf_prog.size = h.size;
         }
 
+        if let Some(insns) = &event.data.insns {
+            self.set_bpf_prog_insns(&mut data.bpf_prog, event, insns);
+        }
+
+        UserEvent::new(data, info)
+    }
+
+    /// Fills program information with the analysis of its instructions
+    /// done at load time. Disassembly needs the program instructions so
+    /// it is missing if the program has already been unloaded.
+    fn set_bpf_prog_insns(
+        &mut self,
+        prog: &mut kunai::events::BpfProgInfo,
+        event: &bpf_events::BpfProgLoadEvent,
+        insns: &bpf_events::ProgInsns,
+    ) {
+        let mut helpers =
+            insns.helpers[..insns.helpers_len.min(bpf_events::MAX_PROG_HELPERS)].to_vec();
+
+        let dump = if self.bpf_disassembly {
+            match util::bpf::bpf_dump_xlated_by_id_and_tag(event.data.id, event.data.tag) {
+                Ok(d) => {
+                    let decoded = insn::decode(&d);
+                    // helpers not reported in event because of its limited size
+                    for h in insn::Analysis::from_insns(&decoded).helpers {
+                        if !helpers.contains(&h) {
+                            helpers.push(h);
+                        }
+                    }
+                    Some(decoded)
+                }
+                Err(e) => {
+                    warn!("failed to dump bpf program for disassembly: {e}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // kallsyms is parsed once, helpers do not move at runtime
+        let resolver = self.bpf_helpers.get_or_insert_with(|| {
+            insn::HelperResolver::from_proc()
+                .inspect_err(|e| warn!("failed to resolve bpf helpers: {e}"))
+                .unwrap_or_default()
+        });
+
+        prog.insn_count = insns.count;
+
+        for imm in helpers.iter() {
+            let name = resolver
+                .resolve(*imm)
+                .map(String::from)
+                .unwrap_or(format!("unknown#{imm}"));
+            if insn::DANGEROUS_HELPERS.contains(&name.as_str()) {
+                prog.dangerous_helpers.push(name.clone());
+            }
+            prog.helpers.push(name);
+        }
+
+        prog.maps = insns.maps[..insns.maps_len.min(bpf_events::MAX_PROG_MAPS)]
+            .iter()
+            .map(|m| BpfMapInfo {
+                id: m.id,
+                ty: util::bpf::bpf_map_type_to_string(m.map_type),
+                name: m.name.into(),
+            })
+            .collect();
+
+        prog.disassembly = dump.map(|d| insn::disassemble(&d, resolver));
+    }
+
+    #[inline]
+    fn bpf_prog_attach_event(
+        &mut self,
+        info: StdEventInfo,
+        event: &bpf_events::BpfProgAttachEvent,
+    ) -> UserEvent<BpfProgAttachData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let data = BpfProgAttachData {
+            ancestors: self.get_ancestors_string(&info),
+            command_line,
+            exe: exe.into(),
+            id: event.data.prog_id,
+            prog_type: BpfProgTypeInfo {
+                id: event.data.prog_type,
+                name: util::bpf::bpf_type_to_string(event.data.prog_type),
+            },
+            name: event.data.prog_name.into(),
+            attached_func: event.data.attached_func_name.into(),
+            attach_type: BpfAttachTypeInfo {
+                id: event.data.attach_type,
+                name: util::bpf::bpf_attach_type_to_string(event.data.attach_type),
+            },
+            flags: event.data.flags,
+            target_fd: event.data.target_fd,
+            target: (!event.data.target.is_empty()).then(|| event.data.target.to_path_buf()),
+            success: event.data.rc >= 0,
+        };
+
         UserEvent::new(data, info)
     }
 
@@ -853,12 +1471,25 @@ impl EventConsumer {
     ) -> UserEvent<MprotectData> {
         let (exe, cmd_line) = self.get_exe_and_command_line(&info);
 
+        let backing = match event.data.vma_kind {
+            VmaKind::File => event.data.vma_file.to_path_buf().to_string_lossy().into(),
+            k => k.as_str().into(),
+        };
+
         let data = MprotectData {
             ancestors: self.get_ancestors_string(&info),
             command_line: cmd_line,
             exe: exe.into(),
             addr: event.data.start,
+            len: event.data.len,
             prot: event.data.prot,
+            vma: event.data.has_vma.then(|| VmaSection {
+                backing,
+                start: event.data.vma_start,
+                size: event.data.vma_end.saturating_sub(event.data.vma_start),
+                // VM_READ, VM_WRITE and VM_EXEC flags match PROT_* values
+                prot: event.data.vma_flags & (PROT_READ | PROT_WRITE | PROT_EXEC) as u64,
+            }),
         };
 
         UserEvent::new(data, info)
@@ -871,20 +1502,42 @@ impl EventConsumer {
         event: &bpf_events::ConnectEvent,
     ) -> UserEvent<ConnectData> {
         let (exe, command_line) = self.get_exe_and_command_line(&info);
-        let dst_ip: IpAddr = event.data.ip_port.into();
+
+        let (dst, unix) = if event.data.family == AF_UNIX {
+            let peer_pid = (event.data.peer_pid != 0).then_some(event.data.peer_pid);
+            (
+                None,
+                Some(UnixSocketInfo {
+                    path: event.data.unix_path(),
+                    is_abstract: event.data.is_abstract_unix(),
+                    peer_pid,
+                    // peer_pid is a pid in the initial pid namespace
+                    peer_exe: peer_pid
+                        .and_then(|pid| fs::read_link(format!("/proc/{pid}/exe")).ok()),
+                }),
+            )
+        } else {
+            let dst_ip: IpAddr = event.data.ip_port.into();
+            (
+                Some(NetworkInfo {
+                    hostname: Some(self.get_resolved(dst_ip, &info).into()),
+                    ip: dst_ip,
+                    port: event.data.ip_port.port(),
+                    public: is_public_ip(dst_ip),
+                    is_v6: event.data.ip_port.is_v6(),
+                }),
+                None,
+            )
+        };
 
         let data = ConnectData {
             ancestors: self.get_ancestors_string(&info),
             command_line,
             exe: exe.into(),
-            dst: NetworkInfo {
-                hostname: Some(self.get_resolved(dst_ip, &info).into()),
-                ip: dst_ip,
-                port: event.data.ip_port.port(),
-                public: is_public_ip(dst_ip),
-                is_v6: event.data.ip_port.is_v6(),
-            },
+            dst,
+            unix,
             connected: event.data.connected,
+            via: event.data.io_uring.then(|| VIA_IO_URING.into()),
         };
 
         UserEvent::new(data, info)
@@ -918,12 +1571,119 @@ impl EventConsumer {
     }
 
     #[inline]
-    fn init_module_event(
+    fn socket_create_event(
         &self,
         info: StdEventInfo,
+        event: &bpf_events::SocketCreateEvent,
+    ) -> UserEvent<SocketCreateData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+        let si = &event.data.socket_info;
+
+        let (protocol, protocol_name) = if si.is_family(SaFamily::AF_NETLINK) {
+            (
+                event.data.protocol,
+                Some(NetlinkProtocol::protocol_to_string(event.data.protocol)),
+            )
+        } else if si.is_family(SaFamily::AF_PACKET) {
+            // packet sockets protocol is an ethertype in network byte order
+            (u16::from_be(event.data.protocol), None)
+        } else {
+            (event.data.protocol, None)
+        };
+
+        let data = SocketCreateData {
+            ancestors: self.get_ancestors_string(&info),
+            command_line,
+            exe: exe.into(),
+            socket: SocketInfo {
+                domain: si.domain_to_string(),
+                ty: si.type_to_string(),
+            },
+            protocol,
+            protocol_name,
+        };
+
+        UserEvent::new(data, info)
+    }
+
+    /// Hashes and parses the image of a module being loaded. For finit_module the
+    /// module file is hashed like any other file and only its headers are read to get
+    /// module information. For init_module we read the buffer given to the syscall
+    /// in the memory of the task, only small images are hashed to limit the amount
+    /// of data read while processing the event. The latter is done on a best effort
+    /// basis as the task may have released its memory by the time we process the event.
+    fn module_image(
+        &mut self,
+        info: &StdEventInfo,
+        event: &bpf_events::InitModuleEvent,
+    ) -> (Hashes, Option<ModuleInfo>) {
+        let error = |file: PathBuf, e: String| Hashes {
+            file,
+            error: Some(e),
+            ..Default::default()
+        };
+
+        match &event.data.args {
+            bpf_events::InitModuleArgs::Init(a) => {
+                let pid = info.info.process.tgid;
+                let path = PathBuf::new();
+                // compressed modules cannot be parsed
+                let modinfo = ModuleInfo::from_process_memory(pid, a.umod, a.len);
+
+                if a.len
+                    > kmod::MAX_MEMORY_IMAGE_HASH_SIZE.min(self.cache.hash_options().max_size())
+                {
+                    let h = Hashes {
+                        file: path,
+                        size: a.len as usize,
+                        status: HashStatus::TooBig,
+                        ..Default::default()
+                    };
+                    return (h, modinfo);
+                }
+
+                match kmod::read_process_memory(pid, a.umod, a.len as usize) {
+                    Ok(data) => (
+                        Hashes::from_bytes(&path, &data, self.cache.hash_options()),
+                        modinfo,
+                    ),
+                    Err(e) => (
+                        error(path, format!("failed to read module image: {e}")),
+                        None,
+                    ),
+                }
+            }
+            bpf_events::InitModuleArgs::FInit(_) => {
+                let path = event.data.path.to_path_buf();
+                let Some(ns) = Self::task_mnt_ns(&info.info) else {
+                    return (error(path, "unknown namespace".into()), None);
+                };
+
+                let h = self
+                    .cache
+                    .get_or_cache_in_ns(ns, &event.data.path)
+                    .unwrap_or_else(|e| error(path.clone(), format!("{e}")));
+
+                // compressed modules cannot be parsed
+                let modinfo = self
+                    .cache
+                    .open_in_ns(ns, &path)
+                    .ok()
+                    .and_then(|f| ModuleInfo::from_file(&f));
+
+                (h, modinfo)
+            }
+        }
+    }
+
+    #[inline]
+    fn init_module_event(
+        &mut self,
+        info: StdEventInfo,
         event: &bpf_events::InitModuleEvent,
     ) -> UserEvent<InitModuleData> {
         let (exe, command_line) = self.get_exe_and_command_line(&info);
+        let (image, modinfo) = self.module_image(&info, event);
 
         let data = InitModuleData {
             ancestors: self.get_ancestors_string(&info),
@@ -932,12 +1692,82 @@ impl EventConsumer {
             syscall: event.data.args.syscall_name().into(),
             module_name: event.data.name.to_string(),
             args: event.data.uargs.to_string(),
+            compressed: event.data.args.is_compressed(),
+            image,
+            modinfo,
             loaded: event.data.loaded,
         };
 
         UserEvent::new(data, info)
     }
 
+    #[inline]
+    fn delete_module_event(
+        &self,
+        info: StdEventInfo,
+        event: &bpf_events::DeleteModuleEvent,
+    ) -> UserEvent<DeleteModuleData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let data = DeleteModuleData {
+            ancestors: self.get_ancestors_string(&info),
+            exe: exe.into(),
+            command_line,
+            module_name: event.data.name.to_string(),
+            flags: event.data.flags,
+            success: event.data.rc == 0,
+        };
+
+        UserEvent::new(data, info)
+    }
+
+    #[inline]
+    fn kernel_tamper_event(
+        &self,
+        info: StdEventInfo,
+        event: &bpf_events::KernelTamperEvent,
+    ) -> UserEvent<KernelTamperData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let path = (!event.data.path.is_empty()).then(|| event.data.path.to_path_buf());
+
+        let kind = match event.data.kind {
+            // writes to procfs are sysctl changes, others go to tracefs
+            TamperKind::FileWrite => {
+                if path.as_ref().is_some_and(|p| p.starts_with("/proc/sys/")) {
+                    "sysctl_write".into()
+                } else {
+                    "tracefs_write".into()
+                }
+            }
+            k => k.as_str().into(),
+        };
+
+        // strings read from userland are NULL terminated
+        let data = event
+            .data
+            .data
+            .as_slice()
+            .split(|b| *b == 0)
+            .next()
+            .filter(|d| !d.is_empty())
+            .map(|d| String::from_utf8_lossy(d).trim_end().to_string());
+
+        let data = KernelTamperData {
+            ancestors: self.get_ancestors_string(&info),
+            exe: exe.into(),
+            command_line,
+            kind,
+            path,
+            data,
+            addr: event.data.addr,
+            flags: event.data.flags,
+            retprobe: event.data.retprobe,
+        };
+
+        UserEvent::new(data, info)
+    }
+
     #[inline]
     fn file_rename_event(
         &self,
@@ -957,6 +1787,182 @@ impl EventConsumer {
         UserEvent::new(data, info)
     }
 
+    /// Returns true if path matches the configuration files patterns
+    #[inline]
+    fn is_config_file<P: AsRef<Path>>(&self, path: P) -> bool {
+        let mut p = kunai_common::path::Path::default();
+        p.copy_from_str(
+            path.as_ref().to_string_lossy(),
+            kunai_common::path::Mode::Append,
+        )
+        .is_ok()
+            && self.config_files.is_included(&p)
+    }
+
+    #[inline]
+    fn file_link_event(
+        &self,
+        info: StdEventInfo,
+        event: &bpf_events::FileLinkEvent,
+    ) -> UserEvent<FileLinkData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let target = event.data.target.to_path_buf();
+        let link = event.data.link.to_path_buf();
+
+        let data = FileLinkData {
+            ancestors: self.get_ancestors_string(&info),
+            command_line,
+            exe: exe.into(),
+            outside_dir: link.parent().is_some_and(|d| !target.starts_with(d)),
+            sensitive_target: self.is_config_file(&target),
+            target,
+            link,
+        };
+
+        UserEvent::new(data, info)
+    }
+
+    #[inline]
+    fn file_symlink_event(
+        &self,
+        info: StdEventInfo,
+        event: &bpf_events::FileSymlinkEvent,
+    ) -> UserEvent<FileSymlinkData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let target = PathBuf::from(event.data.target.to_string_lossy().as_ref());
+        let link = event.data.link.to_path_buf();
+        let dir = link.parent().unwrap_or(Path::new("/"));
+        // a relative target is relative to the directory of the link
+        let resolved_target = util::normalize_path(dir.join(&target));
+
+        let data = FileSymlinkData {
+            ancestors: self.get_ancestors_string(&info),
+            command_line,
+            exe: exe.into(),
+            outside_dir: !resolved_target.starts_with(dir),
+            sensitive_target: self.is_config_file(&resolved_target),
+            target,
+            resolved_target,
+            link,
+        };
+
+        UserEvent::new(data, info)
+    }
+
+    #[inline]
+    fn file_chmod_event(
+        &self,
+        info: StdEventInfo,
+        event: &bpf_events::FileChmodEvent,
+    ) -> UserEvent<FileChmodData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let mut data = FileChmodData {
+            ancestors: self.get_ancestors_string(&info),
+            command_line,
+            exe: exe.into(),
+            path: event.data.path.to_path_buf(),
+            old_mode: String::new(),
+            new_mode: String::new(),
+            setid_added: false,
+            exec_added: false,
+            success: event.data.rc == 0,
+        };
+
+        data.set_modes(event.data.old_mode, event.data.new_mode);
+
+        UserEvent::new(data, info)
+    }
+
+    #[inline]
+    fn file_chown_event(
+        &self,
+        info: StdEventInfo,
+        event: &bpf_events::FileChownEvent,
+    ) -> UserEvent<FileChownData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+        let changed = |id: u32| (id != bpf_events::INVALID_ID).then_some(id);
+
+        let data = FileChownData {
+            ancestors: self.get_ancestors_string(&info),
+            command_line,
+            exe: exe.into(),
+            path: event.data.path.to_path_buf(),
+            old_uid: event.data.old_uid,
+            old_gid: event.data.old_gid,
+            new_uid: changed(event.data.new_uid),
+            new_gid: changed(event.data.new_gid),
+            success: event.data.rc == 0,
+        };
+
+        UserEvent::new(data, info)
+    }
+
+    #[inline]
+    fn file_xattr_event(
+        &self,
+        info: StdEventInfo,
+        event: &bpf_events::FileXattrEvent,
+    ) -> UserEvent<FileXattrData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let operation = match event.data.op {
+            bpf_events::XATTR_OP_SET => "set",
+            bpf_events::XATTR_OP_REMOVE => "remove",
+            _ => "?",
+        };
+
+        let data = FileXattrData {
+            ancestors: self.get_ancestors_string(&info),
+            command_line,
+            exe: exe.into(),
+            path: event.data.path.to_path_buf(),
+            operation: operation.into(),
+            name: event.data.name.to_string_lossy().into(),
+            size: event.data.size,
+            success: event.data.rc == 0,
+        };
+
+        UserEvent::new(data, info)
+    }
+
+    #[inline]
+    fn file_times_event(
+        &self,
+        info: StdEventInfo,
+        event: &bpf_events::FileTimesEvent,
+    ) -> UserEvent<FileTimesData> {
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let to_utc =
+            |t: &Time| DateTime::from_timestamp(t.sec, t.nsec as u32).map(UtcDateTime::from);
+
+        // times given to utimensat may be special values
+        let new_time = |t: &Time| match t.nsec {
+            bpf_events::UTIME_OMIT => None,
+            bpf_events::UTIME_NOW => Some(info.utc_timestamp.into()),
+            _ => to_utc(t),
+        };
+
+        let meta = event.data.path.metadata;
+
+        let data = FileTimesData {
+            ancestors: self.get_ancestors_string(&info),
+            command_line,
+            exe: exe.into(),
+            path: event.data.path.to_path_buf(),
+            old_atime: meta.and_then(|m| to_utc(&m.atime)),
+            old_mtime: meta.and_then(|m| to_utc(&m.mtime)),
+            new_atime: new_time(&event.data.atime),
+            new_mtime: new_time(&event.data.mtime),
+            success: event.data.rc == 0,
+        };
+
+        UserEvent::new(data, info)
+    }
+
     #[inline]
     fn exit_event(
         &mut self,
@@ -983,6 +1989,19 @@ impl EventConsumer {
             self.tasks
                 .entry(info.task_key())
                 .and_modify(|t| t.free_memory());
+
+            // releases the namespace if it was the last process we knew in it
+            if let Some(ns) = Self::task_mnt_ns(&info.info) {
+                if self.cache.forget_task(ns, info.info.process.tgid) {
+                    if let Some(fim) = self.fim.as_mut() {
+                        fim.forget_ns(ns);
+                    }
+                    if let Some(af) = self.auth_files.as_mut() {
+                        af.forget_ns(ns);
+                    }
+                    self.pending_baselines.retain(|&n| n != ns);
+                }
+            }
         }
 
         UserEvent::new(data, info)
@@ -1041,11 +2060,14 @@ impl EventConsumer {
             }
         };
 
-        let mut container_type = Container::from_cgroups(&cgroups);
+        let (mut container_type, container_id) = match self.containers.from_cgroups(&cgroups) {
+            Some((c, id)) => (Some(c), id),
+            None => (None, None),
+        };
 
         if container_type.is_none() {
             let ancestors = self.get_ancestors(info.parent_key(), 0);
-            container_type = Container::from_ancestors(&ancestors);
+            container_type = self.containers.from_ancestors(&ancestors);
         }
 
         let image = {
@@ -1056,6 +2078,9 @@ impl EventConsumer {
             }
         };
 
+        // peer is inherited from parent
+        let peer = self.tasks.get(&info.parent_key()).and_then(|t| t.peer);
+
         // we insert only if not existing
         self.tasks.entry(ck).or_insert(Task {
             image,
@@ -1064,24 +2089,288 @@ impl EventConsumer {
             flags: info.info.process.flags,
             resolved: HashMap::new(),
             container: container_type,
+            container_id,
             cgroups,
             nodename: event.data.nodename(),
             parent_key: Some(info.parent_key()),
+            peer,
         });
     }
 
+    /// Builds a file integrity event holding the information about
+    /// the task changing a file. Hashes are filled when the change
+    /// is detected.
+    fn file_integrity_event(&self, info: &StdEventInfo) -> UserEvent<FileIntegrityData> {
+        let mut info = info.clone();
+        info.info.etype = Type::FileIntegrity;
+
+        let (exe, command_line) = self.get_exe_and_command_line(&info);
+
+        let data = FileIntegrityData {
+            ancestors: self.get_ancestors_string(&info),
+            command_line,
+            exe: exe.into(),
+            path: PathBuf::new(),
+            operation: String::new(),
+            old: None,
+            new: None,
+        };
+
+        UserEvent::new(data, info)
+    }
+
+    #[inline]
+    fn fim_monitors(&self, path: &kunai_common::path::Path) -> bool {
+        self.fim.as_ref().is_some_and(|f| f.is_monitored(path))
+    }
+
+    #[inline]
+    fn needs_baselines(&self, ns: Namespace) -> bool {
+        self.fim.as_ref().is_some_and(|f| !f.has_baseline(ns))
+            || self
+                .auth_files
+                .as_ref()
+                .is_some_and(|a| !a.has_baseline(ns))
+    }
+
+    /// Queues a mount namespace met for the first time so that its
+    /// baselines are built outside of event processing
+    fn queue_baselines(&mut self, ns: Namespace) {
+        if self.needs_baselines(ns) && !self.pending_baselines.contains(&ns) {
+            self.pending_baselines.push_back(ns);
+        }
+    }
+
+    /// Builds the baselines of a single queued mount namespace
+    fn poll_baselines(&mut self) {
+        if let Some(ns) = self.pending_baselines.pop_front() {
+            self.ns_baselines(ns);
+        }
+    }
+
+    /// Builds the file integrity and authentication files
+    /// baselines of a mount namespace
+    fn ns_baselines(&mut self, ns: Namespace) {
+        if let Some(fim) = self.fim.as_mut().filter(|f| !f.has_baseline(ns)) {
+            let n = fim.build_baseline(&mut self.cache, ns);
+            debug!("file integrity baseline ns={ns}: {n} files");
+        }
+
+        if let Some(af) = self.auth_files.as_mut().filter(|a| !a.has_baseline(ns)) {
+            let n = af.build_baseline(&mut self.cache, ns);
+            debug!("authentication files baseline ns={ns}: {n} files");
+        }
+    }
+
+    /// Schedules the re-hashing of a monitored file being modified
+    fn fim_track_write(&mut self, info: &StdEventInfo, path: &kunai_common::path::Path) {
+        if !self.fim_monitors(path) {
+            return;
+        }
+
+        let Some(ns) = Self::task_mnt_ns(&info.info) else {
+            return;
+        };
+
+        let e = self.file_integrity_event(info);
+        if let Some(fim) = self.fim.as_mut() {
+            fim.schedule(ns, path.to_path_buf(), e);
+        }
+    }
+
+    /// Reports the deletion of a monitored file
+    fn fim_track_delete(&mut self, info: &StdEventInfo, path: &kunai_common::path::Path) {
+        if !self.fim_monitors(path) {
+            return;
+        }
+
+        let Some(ns) = Self::task_mnt_ns(&info.info) else {
+            return;
+        };
+
+        let e = self.file_integrity_event(info);
+        let change = self
+            .fim
+            .as_mut()
+            .and_then(|fim| fim.delete(ns, path.to_path_buf(), e));
+
+        if let Some(c) = change {
+            self.print_fim_change(c);
+        }
+    }
+
+    fn print_fim_change(&mut self, c: kunai::fim::Change<UserEvent<FileIntegrityData>>) {
+        let mut e = c.context;
+        e.data.path = c.path;
+        e.data.operation = c.operation.as_str().into();
+        e.data.old = c.old;
+        e.data.new = c.new;
+        self.scan_and_print(&mut e);
+    }
+
+    /// Reports the changes of monitored files whose settle time elapsed
+    fn poll_fim(&mut self) {
+        let Some(changes) = self.fim.as_mut().map(|f| f.poll(&mut self.cache)) else {
+            return;
+        };
+
+        for c in changes {
+            self.print_fim_change(c);
+        }
+    }
+
+    fn auth_file_writer(&self, info: &StdEventInfo) -> AuthFileWriter {
+        let (exe, command_line) = self.get_exe_and_command_line(info);
+        AuthFileWriter {
+            info: info.clone(),
+            ancestors: self.get_ancestors_string(info),
+            command_line,
+            exe,
+        }
+    }
+
+    /// Schedules the parsing of an authentication file being modified
+    fn auth_files_track(&mut self, info: &StdEventInfo, path: &kunai_common::path::Path) {
+        let path = path.to_path_buf();
+        if self.auth_files.is_none() || auth_files::AuthFile::from_path(&path).is_none() {
+            return;
+        }
+
+        let Some(ns) = Self::task_mnt_ns(&info.info) else {
+            return;
+        };
+
+        let w = self.auth_file_writer(info);
+        let Some(af) = self.auth_files.as_mut() else {
+            return;
+        };
+
+        af.track(ns, &path, w);
+    }
+
+    fn account_event(
+        w: AuthFileWriter,
+        etype: Type,
+        file: PathBuf,
+        old: Option<Account>,
+        new: Account,
+        changes: Vec<String>,
+    ) -> UserEvent<AccountData> {
+        let mut info = w.info;
+        info.info.etype = etype;
+
+        let data = AccountData {
+            ancestors: w.ancestors,
+            command_line: w.command_line,
+            exe: w.exe.into(),
+            file,
+            user: new.name,
+            uid: new.uid,
+            gid: new.gid,
+            home: new.home,
+            shell: new.shell,
+            locked: new.locked,
+            changes,
+            old_uid: old.as_ref().and_then(|o| o.uid),
+            old_gid: old.as_ref().and_then(|o| o.gid),
+            old_home: old.as_ref().and_then(|o| o.home.clone()),
+            old_shell: old.and_then(|o| o.shell),
+        };
+
+        UserEvent::new(data, info)
+    }
+
+    fn print_auth_change(&mut self, w: AuthFileWriter, file: PathBuf, c: auth_files::Change) {
+        match c {
+            auth_files::Change::AccountAdded(a) => {
+                let mut e = Self::account_event(w, Type::AccountAdded, file, None, a, vec![]);
+                self.scan_and_print(&mut e);
+            }
+            auth_files::Change::AccountModified { old, new, changes } => {
+                let mut e =
+                    Self::account_event(w, Type::AccountModified, file, Some(old), new, changes);
+                self.scan_and_print(&mut e);
+            }
+            auth_files::Change::AccountDeleted(a) => {
+                let mut e = Self::account_event(w, Type::AccountDeleted, file, None, a, vec![]);
+                self.scan_and_print(&mut e);
+            }
+            auth_files::Change::SudoersChanged { added, removed } => {
+                let mut info = w.info;
+                info.info.etype = Type::SudoersChanged;
+                let data = SudoersData {
+                    ancestors: w.ancestors,
+                    command_line: w.command_line,
+                    exe: w.exe.into(),
+                    file,
+                    added,
+                    removed,
+                };
+                self.scan_and_print(&mut UserEvent::new(data, info));
+            }
+            auth_files::Change::SshKeyAdded { user, key } => {
+                let mut info = w.info;
+                info.info.etype = Type::SshKeyAdded;
+                let data = SshKeyData {
+                    ancestors: w.ancestors,
+                    command_line: w.command_line,
+                    exe: w.exe.into(),
+                    file,
+                    user,
+                    key_type: key.key_type,
+                    fingerprint: key.fingerprint,
+                    comment: key.comment,
+                    options: key.options,
+                };
+                self.scan_and_print(&mut UserEvent::new(data, info));
+            }
+        }
+    }
+
+    /// Reports the changes of authentication files whose settle time elapsed
+    fn poll_auth_files(&mut self) {
+        let Some(changes) = self.auth_files.as_mut().map(|af| af.poll(&mut self.cache)) else {
+            return;
+        };
+
+        for (w, file, c) in changes {
+            self.print_auth_change(w, file, c);
+        }
+    }
+
     #[inline]
     fn handle_hash_event(&mut self, info: StdEventInfo, event: &bpf_events::HashEvent) {
         let opt_mnt_ns = Self::task_mnt_ns(&info.info);
-        self.get_hashes_with_ns(opt_mnt_ns, &event.data.path);
+        // we only schedule hashing so that we don't block on I/O
+        if let Some(ns) = opt_mnt_ns {
+            if let Err(e) = self.cache.prefetch_in_ns(ns, &event.data.path) {
+                debug!("failed to prefetch hashes of {}: {e}", event.data.path);
+            }
+        }
     }
 
     fn build_std_event_info(&mut self, i: bpf_events::EventInfo) -> StdEventInfo {
         let opt_mnt_ns = Self::task_mnt_ns(&i);
 
+        let task_names = self.account_names(opt_mnt_ns, &i.process);
+        // parent namespaces may be unknown so we fallback to task's ones
+        let parent_names = self.account_names(
+            i.parent
+                .namespaces
+                .map(|ns| Namespace::mnt(ns.mnt))
+                .or(opt_mnt_ns),
+            &i.parent,
+        );
+
         let std_info = StdEventInfo::from_bpf(i, self.random);
 
         let cd = self.tasks.get(&std_info.task_key());
+        let container_id = cd.and_then(|cd| cd.container_id.clone());
+
+        let session = i.process.sessionid().map(|sid| {
+            self.sessions
+                .update(sid, i.process.loginuid(), cd.and_then(|t| t.peer), None)
+        });
 
         let host = kunai::info::HostInfo {
             name: self.system_info.hostname.clone(),
@@ -1095,11 +2384,61 @@ impl EventConsumer {
                 container = Some(kunai::info::ContainerInfo {
                     name: cd.and_then(|t| t.nodename.clone()).unwrap_or("?".into()),
                     ty: cd.and_then(|cd| cd.container),
+                    pod: container_id
+                        .as_ref()
+                        .and_then(|id| self.k8s.as_mut().and_then(|r| r.get(id))),
+                    id: container_id,
                 });
             }
         }
 
-        std_info.with_additional_info(AdditionalInfo { host, container })
+        std_info.with_additional_info(AdditionalInfo {
+            host,
+            container,
+            session,
+            task_names,
+            parent_names,
+        })
+    }
+
+    /// resolves user and group names of a task inside its mount namespace
+    #[inline]
+    fn account_names(
+        &mut self,
+        ns: Option<Namespace>,
+        t: &bpf_events::TaskInfo,
+    ) -> kunai::info::AccountNames {
+        let Some(ns) = ns else {
+            return Default::default();
+        };
+
+        match self.cache.get_or_cache_accounts_in_ns(ns) {
+            Ok(a) => kunai::info::AccountNames {
+                user: a.user(t.uid).cloned(),
+                group: a.group(t.gid).cloned(),
+            },
+            Err(e) => {
+                debug!("failed to resolve accounts in ns={ns}: {e}");
+                Default::default()
+            }
+        }
+    }
+
+    /// invalidates accounts cached for the mount namespace of the task, and the
+    /// ones sharing its databases, if path is one of the account databases
+    #[inline]
+    fn invalidate_accounts_on_write<P: AsRef<std::path::Path>>(
+        &mut self,
+        info: &StdEventInfo,
+        path: P,
+    ) {
+        if !accounts::is_account_file(&path) {
+            return;
+        }
+
+        if let Some(ns) = Self::task_mnt_ns(&info.info) {
+            self.cache.invalidate_accounts(ns, path);
+        }
     }
 
     #[inline(always)]
@@ -1178,11 +2517,33 @@ impl EventConsumer {
             debug!("skipping our event");
         }
 
+        // command line chunks are kept until their execve event comes
+        if i.etype == Type::ExecveArgv {
+            match event!(enc_event, bpf_events::ExecveArgvEvent) {
+                Ok(e) => {
+                    let key = TaskKey::from(i.process.tg_uuid);
+                    // execve events might have been lost
+                    if self.argv_chunks.len() >= MAX_PENDING_ARGV
+                        && !self.argv_chunks.contains_key(&key)
+                    {
+                        self.argv_chunks.clear();
+                    }
+                    self.argv_chunks
+                        .entry(key)
+                        .or_default()
+                        .push((e.data.offset, e.data.chunk.as_slice().to_vec()));
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", i.etype, e),
+            }
+            return;
+        }
+
         if let Some(ns) = i.process.namespaces {
             let pid = i.process.pid;
             let mnt = Namespace::mnt(ns.mnt);
-            if let Err(e) = self.cache.cache_ns(pid, mnt) {
-                debug!("failed to cache namespace pid={pid} ns={mnt}: {e}");
+            match self.cache.cache_ns(pid, i.process.tgid, mnt) {
+                Ok(()) => self.queue_baselines(mnt),
+                Err(e) => debug!("failed to cache namespace pid={pid} ns={mnt}: {e}"),
             }
         } else {
             // the few cases where we expect namespaces to be unknown
@@ -1208,6 +2569,7 @@ impl EventConsumer {
                             std_info.clone(),
                             &bpf_events::CorrelationEvent::from(e),
                         );
+                        self.update_exec_context(&std_info, e);
                         // we have to rebuild std_info as it has it is uses correlation
                         // information
                         let std_info = self.build_std_event_info(std_info.info);
@@ -1244,6 +2606,14 @@ impl EventConsumer {
                 Err(e) => error!("failed to decode {} event: {:?}", etype, e),
             },
 
+            Type::ExecveFailed => match event!(enc_event, bpf_events::ExecveEvent) {
+                Ok(e) => {
+                    let mut e = self.execve_failed_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
             Type::Kill => match event!(enc_event, bpf_events::KillEvent) {
                 Ok(e) => {
                     let mut e = self.kill_event(std_info, e);
@@ -1252,6 +2622,14 @@ impl EventConsumer {
                 Err(e) => error!("failed to decode {} event: {:?}", etype, e),
             },
 
+            Type::ProcessAccess => match event!(enc_event, bpf_events::ProcessAccessEvent) {
+                Ok(e) => {
+                    let mut e = self.process_access_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
             Type::MmapExec => match event!(enc_event, bpf_events::MmapExecEvent) {
                 Ok(e) => {
                     let mut e = self.mmap_exec_event(std_info, e);
@@ -1260,6 +2638,14 @@ impl EventConsumer {
                 Err(e) => error!("failed to decode {} event: {:?}", etype, e),
             },
 
+            Type::MemfdCreate => match event!(enc_event, bpf_events::MemfdCreateEvent) {
+                Ok(e) => {
+                    let mut e = self.memfd_create_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
             Type::MprotectExec => match event!(enc_event, bpf_events::MprotectEvent) {
                 Ok(e) => {
                     let mut e = self.mprotect_event(std_info, e);
@@ -1268,6 +2654,22 @@ impl EventConsumer {
                 Err(e) => error!("failed to decode {} event: {:?}", etype, e),
             },
 
+            Type::IoUringSetup => match event!(enc_event, bpf_events::IoUringSetupEvent) {
+                Ok(e) => {
+                    let mut e = self.io_uring_setup_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
+            Type::IoUringOp => match event!(enc_event, bpf_events::IoUringOpEvent) {
+                Ok(e) => {
+                    let mut e = self.io_uring_op_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
             Type::Connect => match event!(enc_event, bpf_events::ConnectEvent) {
                 Ok(e) => {
                     let mut e = self.connect_event(std_info, e);
@@ -1293,6 +2695,14 @@ impl EventConsumer {
                 Err(e) => error!("failed to decode {} event: {:?}", etype, e),
             },
 
+            Type::SocketCreate => match event!(enc_event, bpf_events::SocketCreateEvent) {
+                Ok(e) => {
+                    let mut e = self.socket_create_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
             Type::InitModule => match event!(enc_event, bpf_events::InitModuleEvent) {
                 Ok(e) => {
                     let mut e = self.init_module_event(std_info, e);
@@ -1301,9 +2711,32 @@ impl EventConsumer {
                 Err(e) => error!("failed to decode {} event: {:?}", etype, e),
             },
 
+            Type::DeleteModule => match event!(enc_event, bpf_events::DeleteModuleEvent) {
+                Ok(e) => {
+                    let mut e = self.delete_module_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
+            Type::KernelTamper => match event!(enc_event, bpf_events::KernelTamperEvent) {
+                Ok(e) => {
+                    let mut e = self.kernel_tamper_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
             Type::WriteConfig | Type::Write | Type::ReadConfig | Type::Read => {
                 match event!(enc_event, bpf_events::ConfigEvent) {
                     Ok(e) => {
+                        if etype == Type::WriteConfig {
+                            self.invalidate_accounts_on_write(&std_info, e.data.path.to_path_buf());
+                        }
+                        if matches!(etype, Type::WriteConfig | Type::Write) {
+                            self.fim_track_write(&std_info, &e.data.path);
+                            self.auth_files_track(&std_info, &e.data.path);
+                        }
                         let mut e = self.rw_event(std_info, e);
                         self.scan_and_print(&mut e);
                     }
@@ -1313,6 +2746,9 @@ impl EventConsumer {
 
             Type::FileUnlink => match event!(enc_event, bpf_events::UnlinkEvent) {
                 Ok(e) => {
+                    if e.data.success {
+                        self.fim_track_delete(&std_info, &e.data.path);
+                    }
                     let mut e = self.unlink_event(std_info, e);
                     self.scan_and_print(&mut e);
                 }
@@ -1321,12 +2757,65 @@ impl EventConsumer {
 
             Type::FileRename => match event!(enc_event, bpf_events::FileRenameEvent) {
                 Ok(e) => {
+                    // account management tools replace databases with a rename
+                    self.invalidate_accounts_on_write(&std_info, e.data.new_name.to_path_buf());
+                    self.fim_track_delete(&std_info, &e.data.old_name);
+                    self.fim_track_write(&std_info, &e.data.new_name);
+                    self.auth_files_track(&std_info, &e.data.new_name);
                     let mut e = self.file_rename_event(std_info, e);
                     self.scan_and_print(&mut e);
                 }
                 Err(e) => error!("failed to decode {} event: {:?}", etype, e),
             },
 
+            Type::FileLink => match event!(enc_event, bpf_events::FileLinkEvent) {
+                Ok(e) => {
+                    let mut e = self.file_link_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
+            Type::FileSymlink => match event!(enc_event, bpf_events::FileSymlinkEvent) {
+                Ok(e) => {
+                    let mut e = self.file_symlink_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
+            Type::FileChmod => match event!(enc_event, bpf_events::FileChmodEvent) {
+                Ok(e) => {
+                    let mut e = self.file_chmod_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
+            Type::FileChown => match event!(enc_event, bpf_events::FileChownEvent) {
+                Ok(e) => {
+                    let mut e = self.file_chown_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
+            Type::FileXattr => match event!(enc_event, bpf_events::FileXattrEvent) {
+                Ok(e) => {
+                    let mut e = self.file_xattr_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
+            Type::FileTimes => match event!(enc_event, bpf_events::FileTimesEvent) {
+                Ok(e) => {
+                    let mut e = self.file_times_event(std_info, e);
+                    self.scan_and_print(&mut e);
+                }
+                Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+            },
+
             Type::BpfProgLoad => match event!(enc_event, bpf_events::BpfProgLoadEvent) {
                 Ok(e) => {
                     let mut e = self.bpf_prog_load_event(std_info, e);
@@ -1335,6 +2824,16 @@ impl EventConsumer {
                 Err(e) => error!("failed to decode {} event: {:?}", etype, e),
             },
 
+            Type::BpfProgAttach | Type::BpfLinkCreate => {
+                match event!(enc_event, bpf_events::BpfProgAttachEvent) {
+                    Ok(e) => {
+                        let mut e = self.bpf_prog_attach_event(std_info, e);
+                        self.scan_and_print(&mut e);
+                    }
+                    Err(e) => error!("failed to decode {} event: {:?}", etype, e),
+                }
+            }
+
             Type::BpfSocketFilter => match event!(enc_event, bpf_events::BpfSocketFilterEvent) {
                 Ok(e) => {
                     let mut e = self.bpf_socket_filter_event(std_info, e);
@@ -1366,7 +2865,15 @@ impl EventConsumer {
             },
 
             Type::Error => panic!("error events should be processed earlier"),
+            Type::ExecveArgv => panic!("execve_argv events should be processed earlier"),
             Type::SyscoreResume => { /*  just ignore it */ }
+            // generated in userland from file changes
+            Type::FileIntegrity
+            | Type::AccountAdded
+            | Type::AccountModified
+            | Type::AccountDeleted
+            | Type::SudoersChanged
+            | Type::SshKeyAdded => {}
         }
     }
 }
@@ -1495,6 +3002,51 @@ impl EventProducer {
         self.sender.send(EncodedEvent::from_event(event)).await
     }
 
+    /// Analyses instructions of a program just loaded. Information about the
+    /// maps it references is retrieved now as they may not outlive the program.
+    fn bpf_prog_insns(insns: &[u8]) -> bpf_events::ProgInsns {
+        let analysis = insn::Analysis::from_insns(&insn::decode(insns));
+        let mut out: bpf_events::ProgInsns = unsafe { core::mem::zeroed() };
+
+        out.count = analysis.insn_count as u32;
+
+        for (i, imm) in analysis
+            .helpers
+            .iter()
+            .take(bpf_events::MAX_PROG_HELPERS)
+            .enumerate()
+        {
+            out.helpers[i] = *imm;
+            out.helpers_len += 1;
+        }
+
+        for (i, id) in analysis
+            .maps
+            .iter()
+            .take(bpf_events::MAX_PROG_MAPS)
+            .enumerate()
+        {
+            let m = &mut out.maps[i];
+            m.id = *id;
+            match util::bpf::bpf_map_get_info_by_id(*id) {
+                Ok(info) => {
+                    m.map_type = info.type_;
+                    let name = info
+                        .name
+                        .iter()
+                        .take_while(|c| **c != 0)
+                        .map(|c| *c as u8 as char)
+                        .collect::<String>();
+                    m.name = name.try_into().unwrap_or_default();
+                }
+                Err(e) => warn!("failed to get information about bpf map id={id}: {e}"),
+            }
+            out.maps_len += 1;
+        }
+
+        out
+    }
+
     /// function used to pre-process some targetted events where time is critical and for which
     /// processing can be done in EventReader
     /// this function must return true if main processing loop has to pass to the next event
@@ -1526,6 +3078,7 @@ impl EventProducer {
                         };
 
                         event.data.hashes = Some(h);
+                        event.data.insns = Some(Self::bpf_prog_insns(&insns));
                     }
 
                     Err(e) => {
@@ -1574,6 +3127,16 @@ impl EventProducer {
                 }
             }
 
+            Type::ExecveFailed => {
+                let event = event!(e, bpf_events::ExecveEvent).unwrap();
+                self.send_event(bpf_events::HashEvent::from_execve_with_path(
+                    event,
+                    event.data.executable,
+                ))
+                .await
+                .unwrap();
+            }
+
             Type::MmapExec => {
                 let event = event!(e, bpf_events::MmapExecEvent).unwrap();
                 self.send_event(bpf_events::HashEvent::from(event))
@@ -1691,8 +3254,16 @@ impl EventProducer {
                             .expect("info should not fail here")
                             .etype;
 
-                        // filtering out unwanted events
-                        if !er.filter.is_enabled(etype) {
+                        // filtering out unwanted events, command line chunks
+                        // are only needed if execve events are reported
+                        let enabled = match etype {
+                            Type::ExecveArgv => {
+                                er.filter.is_enabled(Type::Execve)
+                                    || er.filter.is_enabled(Type::ExecveScript)
+                            }
+                            _ => er.filter.is_enabled(etype),
+                        };
+                        if !enabled {
                             continue;
                         }
 
@@ -1985,7 +3556,15 @@ fn prepare_bpf(kernel: KernelVersion, conf: &Config, vll: VerifierLogLevel) -> a
         .set_global("LINUX_KERNEL_VERSION", &kernel, true)
         .load(BPF_ELF)?;
 
-    BpfConfig::init_config_in_bpf(&mut bpf, conf.clone().try_into()?)
+    let mut bpf_config: BpfConfig = conf.clone().try_into()?;
+    // needed to check that file descriptors are bpf programs
+    bpf_config.bpf_prog_fops = util::kallsyms_lookup("bpf_prog_fops")
+        .inspect_err(|e| warn!("failed to read kallsyms: {e}"))
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+
+    BpfConfig::init_config_in_bpf(&mut bpf, bpf_config)
         .expect("failed to initialize bpf configuration");
 
     Ok(bpf)
@@ -2046,7 +3625,12 @@ fn load_and_attach_bpf<'a>(
             p.prio
         );
 
-        p.load_and_attach(&btf)?;
+        if let Err(e) = p.load_and_attach(&btf) {
+            if !p.optional {
+                return Err(e.into());
+            }
+            warn!("failed to load optional {} probe: {e}", p.name);
+        }
     }
 
     Ok(programs)
@@ -2090,19 +3674,42 @@ impl Command {
                         Type::Execve | Type::ExecveScript => scan_event!(p, ExecveData),
                         Type::Clone => scan_event!(p, CloneData),
                         Type::Prctl => scan_event!(p, PrctlData),
+                        Type::ExecveFailed => scan_event!(p, ExecveFailedData),
                         Type::Kill => unimplemented!(),
+                        Type::ProcessAccess => scan_event!(p, ProcessAccessData),
                         Type::MmapExec => scan_event!(p, MmapExecData),
+                        Type::MemfdCreate => scan_event!(p, MemfdCreateData),
+                        Type::IoUringSetup => scan_event!(p, IoUringSetupData),
+                        Type::IoUringOp => scan_event!(p, IoUringOpData),
                         Type::MprotectExec => scan_event!(p, MprotectData),
                         Type::Connect => scan_event!(p, ConnectData),
                         Type::DnsQuery => scan_event!(p, DnsQueryData),
                         Type::SendData => scan_event!(p, SendDataData),
+                        Type::SocketCreate => scan_event!(p, SocketCreateData),
                         Type::InitModule => scan_event!(p, InitModuleData),
+                        Type::DeleteModule => scan_event!(p, DeleteModuleData),
+                        Type::KernelTamper => scan_event!(p, KernelTamperData),
                         Type::WriteConfig | Type::Write | Type::ReadConfig | Type::Read => {
                             scan_event!(p, RWData)
                         }
                         Type::FileUnlink => scan_event!(p, UnlinkData),
                         Type::FileRename => scan_event!(p, FileRenameData),
+                        Type::FileChmod => scan_event!(p, FileChmodData),
+                        Type::FileChown => scan_event!(p, FileChownData),
+                        Type::FileXattr => scan_event!(p, FileXattrData),
+                        Type::FileTimes => scan_event!(p, FileTimesData),
+                        Type::FileLink => scan_event!(p, FileLinkData),
+                        Type::FileSymlink => scan_event!(p, FileSymlinkData),
+                        Type::FileIntegrity => scan_event!(p, FileIntegrityData),
+                        Type::AccountAdded | Type::AccountModified | Type::AccountDeleted => {
+                            scan_event!(p, AccountData)
+                        }
+                        Type::SudoersChanged => scan_event!(p, SudoersData),
+                        Type::SshKeyAdded => scan_event!(p, SshKeyData),
                         Type::BpfProgLoad => scan_event!(p, BpfProgLoadData),
+                        Type::BpfProgAttach | Type::BpfLinkCreate => {
+                            scan_event!(p, BpfProgAttachData)
+                        }
                         Type::BpfSocketFilter => scan_event!(p, BpfSocketFilterData),
                         Type::Exit | Type::ExitGroup => scan_event!(p, ExitData),
 
@@ -2113,6 +3720,7 @@ impl Command {
                         | Type::EndEvents
                         | Type::TaskSched
                         | Type::SyscoreResume
+                        | Type::ExecveArgv
                         | Type::Max => {}
                     }
                 }
@@ -2186,6 +3794,8 @@ impl Command {
                 .consume(receiver)
                 .await?;
 
+            let shared_cons = Arc::clone(&cons);
+
             // we spawn a task to reload producer when needed
             let main = async move {
                 loop {
@@ -2242,10 +3852,15 @@ impl Command {
             };
 
             info!("Waiting for Ctrl-C...");
-            tokio::select! {
+            let res = tokio::select! {
                 _ = tokio::signal::ctrl_c() => Ok(()),
                 res = main => res
-            }
+            };
+
+            // we save hash cache before leaving
+            shared_cons.write().await.save_hash_cache();
+
+            res
         })
     }
 }
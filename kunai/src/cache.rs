@@ -1,3 +1,4 @@
+use fuzzyhash::FuzzyHash;
 use gene::{FieldGetter, FieldValue};
 use gene_derive::FieldGetter;
 
@@ -8,16 +9,33 @@ use sha1::Sha1;
 use sha2::{Sha256, Sha512};
 use std::{
     borrow::Cow,
-    collections::HashMap,
-    fs::File,
-    io::{self, BufReader, Read},
-    os::unix::prelude::MetadataExt,
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, Read, Write},
+    os::{
+        fd::AsRawFd,
+        unix::{
+            fs::{DirBuilderExt, OpenOptionsExt},
+            prelude::MetadataExt,
+        },
+    },
     path::{Path, PathBuf},
-    time::SystemTime,
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 use thiserror::Error;
+use tlsh2::TlshDefaultBuilder;
 
-use crate::util::namespaces::{self, Kind, Namespace, Switcher};
+use crate::util::{
+    accounts::{self, Accounts},
+    elf::ElfMetadata,
+    getrandom, hmac_sha256,
+    namespaces::{self, unshare, Kind, Namespace, Switcher},
+};
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -35,144 +53,688 @@ pub enum Error {
     MetadataRequired,
     #[error("file not found")]
     FileNotFound,
+    #[error("not a regular file")]
+    NotRegularFile,
+    #[error("persistence: {0}")]
+    Persistence(String),
+}
+
+/// Hash algorithms which can be computed on files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgo {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Tlsh,
+    Ssdeep,
+}
+
+impl HashAlgo {
+    pub const ALL: [HashAlgo; 6] = [
+        Self::Md5,
+        Self::Sha1,
+        Self::Sha256,
+        Self::Sha512,
+        Self::Tlsh,
+        Self::Ssdeep,
+    ];
+
+    /// Algorithms computed by default, fuzzy hashes are
+    /// expensive so they must be enabled explicitly
+    pub const DEFAULT: [HashAlgo; 4] = [Self::Md5, Self::Sha1, Self::Sha256, Self::Sha512];
+}
+
+/// Status of the hashing of a file
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashStatus {
+    #[default]
+    Complete,
+    /// file is being hashed and hashes were not ready when event was emitted
+    Pending,
+    /// file is bigger than the maximum size configured
+    TooBig,
+}
+
+impl HashStatus {
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Complete => "complete",
+            Self::Pending => "pending",
+            Self::TooBig => "too_big",
+        }
+    }
+
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        matches!(self, Self::Complete)
+    }
+}
+
+impl FieldGetter for HashStatus {
+    fn get_from_iter(&self, i: core::slice::Iter<'_, std::string::String>) -> Option<FieldValue> {
+        if i.len() > 0 {
+            return None;
+        }
+        Some(self.as_str().into())
+    }
+}
+
+// files bigger than this are not hashed by default
+pub const DEFAULT_MAX_HASH_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Options driving how files are hashed
+#[derive(Debug, Clone)]
+pub struct HashOptions {
+    algorithms: HashSet<HashAlgo>,
+    max_size: u64,
+}
+
+impl Default for HashOptions {
+    fn default() -> Self {
+        Self::new(HashAlgo::DEFAULT, DEFAULT_MAX_HASH_SIZE)
+    }
+}
+
+impl HashOptions {
+    pub fn new<I: IntoIterator<Item = HashAlgo>>(algorithms: I, max_size: u64) -> Self {
+        Self {
+            algorithms: algorithms.into_iter().collect(),
+            max_size,
+        }
+    }
+
+    #[inline(always)]
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    #[inline(always)]
+    fn has(&self, algo: HashAlgo) -> bool {
+        self.algorithms.contains(&algo)
+    }
 }
 
 #[derive(Debug, Default, Clone, FieldGetter, Serialize, Deserialize)]
 pub struct Hashes {
     pub file: PathBuf,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub md5: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub sha1: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub sha256: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub sha512: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tlsh: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssdeep: Option<String>,
     pub size: usize,
+    #[serde(default)]
+    pub setuid: bool,
+    #[serde(default)]
+    pub setgid: bool,
+    /// file has capabilities (security.capability extended attribute)
+    #[serde(default)]
+    pub file_caps: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elf: Option<ElfMetadata>,
+    #[serde(default, skip_serializing_if = "HashStatus::is_complete")]
+    pub status: HashStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
+const XATTR_SECURITY_CAPABILITY: &[u8] = b"security.capability\0";
+
+#[inline]
+fn has_file_caps(f: &File) -> bool {
+    // we only query the size of the attribute to know if it exists
+    unsafe {
+        libc::fgetxattr(
+            f.as_raw_fd(),
+            XATTR_SECURITY_CAPABILITY.as_ptr() as *const libc::c_char,
+            std::ptr::null_mut(),
+            0,
+        ) > 0
+    }
+}
+
 impl Hashes {
     pub fn from_path_ref<T: AsRef<Path>>(p: T) -> Self {
         let path = p.as_ref();
+        match File::open(path) {
+            Ok(f) => Self::from_file(f, path, &HashOptions::default()),
+            Err(e) => Hashes {
+                file: path.to_path_buf(),
+                error: Some(format!("{e}")),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Returns hashes marking a file as being hashed
+    #[inline]
+    pub fn pending<T: AsRef<Path>>(p: T) -> Self {
+        Hashes {
+            file: p.as_ref().to_path_buf(),
+            status: HashStatus::Pending,
+            ..Default::default()
+        }
+    }
+
+    /// Hashes an opened file. Path is only used to fill the file field.
+    pub fn from_file<T: AsRef<Path>>(f: File, path: T, opts: &HashOptions) -> Self {
         let mut h = Hashes {
-            file: path.to_path_buf(),
+            file: path.as_ref().to_path_buf(),
             ..Hashes::default()
         };
-        let mut md5 = Md5::new();
-        let mut sha1 = Sha1::new();
-        let mut sha256 = Sha256::new();
-        let mut sha512 = Sha512::new();
-
-        if let Ok(f) = File::open(path) {
-            let mut reader = BufReader::new(f);
-            let mut buf = [0; 4096];
-            while let Ok(n) = reader.read(&mut buf[..]) {
-                if n == 0 {
-                    break;
-                }
-                md5.update(&buf[..n]);
-                sha1.update(&buf[..n]);
-                sha256.update(&buf[..n]);
-                sha512.update(&buf[..n]);
-                h.size += n;
+
+        if let Ok(meta) = f.metadata() {
+            h.setuid = meta.mode() & libc::S_ISUID == libc::S_ISUID;
+            h.setgid = meta.mode() & libc::S_ISGID == libc::S_ISGID;
+
+            if meta.size() > opts.max_size {
+                h.size = meta.size() as usize;
+                h.status = HashStatus::TooBig;
+                return h;
             }
+        }
+        h.file_caps = has_file_caps(&f);
 
-            h.md5 = hex::encode(md5.finalize());
-            h.sha1 = hex::encode(sha1.finalize());
-            h.sha256 = hex::encode(sha256.finalize());
-            h.sha512 = hex::encode(sha512.finalize());
+        let mut h = h.digest(BufReader::new(&f), opts);
+        h.elf = ElfMetadata::from_file(&f);
+        h
+    }
+
+    /// Hashes data found in memory (i.e. a kernel module image).
+    /// Path is only used to fill the file field.
+    pub fn from_bytes<T: AsRef<Path>>(path: T, data: &[u8], opts: &HashOptions) -> Self {
+        let h = Hashes {
+            file: path.as_ref().to_path_buf(),
+            ..Hashes::default()
+        };
+
+        if data.len() as u64 > opts.max_size {
+            return Hashes {
+                size: data.len(),
+                status: HashStatus::TooBig,
+                ..h
+            };
         }
 
+        let mut h = h.digest(data, opts);
+        h.elf = ElfMetadata::from_bytes(data);
         h
     }
 
+    fn digest<R: Read>(mut self, mut reader: R, opts: &HashOptions) -> Self {
+        let mut md5 = opts.has(HashAlgo::Md5).then(Md5::new);
+        let mut sha1 = opts.has(HashAlgo::Sha1).then(Sha1::new);
+        let mut sha256 = opts.has(HashAlgo::Sha256).then(Sha256::new);
+        let mut sha512 = opts.has(HashAlgo::Sha512).then(Sha512::new);
+        let mut tlsh = opts.has(HashAlgo::Tlsh).then(TlshDefaultBuilder::new);
+        let mut ssdeep = opts.has(HashAlgo::Ssdeep).then(FuzzyHash::default);
+
+        let mut buf = [0; 4096];
+        while let Ok(n) = reader.read(&mut buf[..]) {
+            if n == 0 {
+                break;
+            }
+            let chunk = &buf[..n];
+
+            md5.iter_mut().for_each(|d| d.update(chunk));
+            sha1.iter_mut().for_each(|d| d.update(chunk));
+            sha256.iter_mut().for_each(|d| d.update(chunk));
+            sha512.iter_mut().for_each(|d| d.update(chunk));
+            tlsh.iter_mut().for_each(|d| d.update(chunk));
+            ssdeep.iter_mut().for_each(|d| d.update(chunk));
+            self.size += n;
+        }
+
+        self.md5 = md5.map(|d| hex::encode(d.finalize())).unwrap_or_default();
+        self.sha1 = sha1.map(|d| hex::encode(d.finalize())).unwrap_or_default();
+        self.sha256 = sha256
+            .map(|d| hex::encode(d.finalize()))
+            .unwrap_or_default();
+        self.sha512 = sha512
+            .map(|d| hex::encode(d.finalize()))
+            .unwrap_or_default();
+        // TLSH cannot be computed on small or too uniform data
+        self.tlsh = tlsh
+            .and_then(|t| t.build())
+            .map(|t| String::from_utf8_lossy(&t.hash()).to_string());
+        self.ssdeep = ssdeep.map(|mut d| {
+            d.finalize();
+            d.to_string()
+        });
+
+        self
+    }
+
     #[inline]
     pub(crate) fn iocs(&self) -> Vec<Cow<'_, str>> {
-        vec![
-            self.file.to_string_lossy(),
-            self.md5.as_str().into(),
-            self.sha1.as_str().into(),
-            self.sha256.as_str().into(),
-            self.sha512.as_str().into(),
-        ]
+        let mut v = vec![self.file.to_string_lossy()];
+        v.extend(
+            [&self.md5, &self.sha1, &self.sha256, &self.sha512]
+                .into_iter()
+                .filter(|h| !h.is_empty())
+                .map(|h| Cow::from(h.as_str())),
+        );
+        v
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+/// Identifies file content independently of the path it is accessed
+/// from, so that it can be used across mount namespaces and restarts.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 struct Key {
-    mnt_namespace: u32,
-    path: PathBuf,
+    dev: u64,
+    ino: u64,
     size: u64,
-    modified: SystemTime,
-    created: SystemTime,
-    accessed: SystemTime,
+    mtime: i64,
+    mtime_nsec: i64,
+    ctime: i64,
+    ctime_nsec: i64,
 }
 
-impl Default for Key {
-    fn default() -> Self {
-        Key {
-            mnt_namespace: 0,
-            path: PathBuf::default(),
-            size: 0,
-            modified: SystemTime::UNIX_EPOCH,
-            created: SystemTime::UNIX_EPOCH,
-            accessed: SystemTime::UNIX_EPOCH,
-        }
+impl Key {
+    fn from_ebpf_path_ref(path: &kunai_common::path::Path) -> Result<Self, Error> {
+        Self::from_ebpf_path_with_target(path, path.to_path_buf())
     }
-}
 
-impl Key {
-    fn from_ebpf_path_ref_with_ns(
-        ns: &Namespace,
+    /// Builds a key for an eBPF path whose content is accessed
+    /// through target (i.e. a /proc/<pid>/fd/<fd> link)
+    fn from_ebpf_path_with_target<P: AsRef<Path>>(
         path: &kunai_common::path::Path,
+        target: P,
     ) -> Result<Self, Error> {
-        let pb = path.to_path_buf();
+        let target = target.as_ref();
 
         // checking if the file still exists
-        if !pb.exists() {
+        if !target.exists() {
             return Err(Error::FileNotFound);
         }
 
-        let meta = pb.metadata()?;
-
-        let mut k = Key {
-            mnt_namespace: ns.inum,
-            path: path.to_path_buf(),
-            ..Default::default()
-        };
-
         // we don't have to switch to ns here as it is done in caller
+        let meta = target.metadata()?;
         let ebpf_meta = path.metadata.ok_or(Error::MetadataRequired)?;
-        k.size = ebpf_meta.size as u64;
-        k.modified = ebpf_meta.mtime.into_system_time();
-        k.accessed = ebpf_meta.atime.into_system_time();
 
-        if k.size != meta.size() {
-            return Err(Error::FileModSinceKernelEvent("inode changed"));
+        if ebpf_meta.size as u64 != meta.size() {
+            return Err(Error::FileModSinceKernelEvent("size changed"));
         }
 
         if ebpf_meta.ino != meta.ino() {
-            return Err(Error::FileModSinceKernelEvent("sized changed"));
+            return Err(Error::FileModSinceKernelEvent("inode changed"));
         }
 
         if let Ok(mtime) = meta.modified() {
-            if mtime != k.modified {
+            if mtime != ebpf_meta.mtime.into_system_time() {
                 return Err(Error::FileModSinceKernelEvent("mtime changed"));
             }
         }
 
-        Ok(k)
+        Ok(Self::from_metadata(&meta))
+    }
+
+    /// Builds a key from the current metadata of a regular file
+    fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let meta = match path.as_ref().metadata() {
+            Ok(m) => m,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(Error::FileNotFound),
+            Err(e) => return Err(e.into()),
+        };
+
+        if !meta.is_file() {
+            return Err(Error::NotRegularFile);
+        }
+
+        Ok(Self::from_metadata(&meta))
+    }
+
+    #[inline]
+    fn from_metadata(meta: &fs::Metadata) -> Self {
+        Key {
+            dev: meta.dev(),
+            ino: meta.ino(),
+            size: meta.size(),
+            mtime: meta.mtime(),
+            mtime_nsec: meta.mtime_nsec(),
+            ctime: meta.ctime(),
+            ctime_nsec: meta.ctime_nsec(),
+        }
     }
 }
 
-unsafe impl Send for Key {}
-unsafe impl Sync for Key {}
+struct CachedAccounts {
+    accounts: Accounts,
+    // devices of the account databases, namespaces sharing
+    // a device (i.e. bind mounts) share the databases
+    devs: HashSet<u64>,
+}
 
 struct CachedNs {
-    switcher: namespaces::Switcher,
+    // shared with the hashing workers opening files in the namespace
+    switcher: Arc<namespaces::Switcher>,
+    // processes (tgids) known to live in the namespace
+    tgids: HashSet<i32>,
+}
+
+const PERSIST_MAGIC: &str = "kunai-hash-cache";
+const PERSIST_VERSION: u32 = 2;
+const PERSIST_SECRET_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize)]
+struct PersistedEntry {
+    key: Key,
+    hashes: Hashes,
+}
+
+/// On-disk persistence of the cache. The file is made of a header line
+/// containing an HMAC of the content, followed by one JSON entry per line
+/// (most recently used first). The HMAC secret is stored in a separate file,
+/// outside of the cache directory, and readable only by its owner.
+struct Persistence {
+    path: PathBuf,
+    secret: [u8; PERSIST_SECRET_LEN],
+    max_entries: usize,
+}
+
+impl Persistence {
+    fn load_or_create_secret(path: &Path) -> Result<[u8; PERSIST_SECRET_LEN], Error> {
+        let mut secret = [0u8; PERSIST_SECRET_LEN];
+
+        match File::open(path) {
+            Ok(mut f) => {
+                let meta = f.metadata()?;
+                // a secret others can read or write does not protect anything
+                if meta.uid() != unsafe { libc::geteuid() } || meta.mode() & 0o077 != 0 {
+                    return Err(Error::Persistence(format!(
+                        "secret file {} must only be accessible by its owner",
+                        path.display()
+                    )));
+                }
+                f.read_exact(&mut secret)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                secret = getrandom::<[u8; PERSIST_SECRET_LEN]>()
+                    .map_err(|e| Error::Persistence(format!("failed to generate secret: {e:?}")))?;
+                if let Some(dir) = path.parent() {
+                    fs::DirBuilder::new()
+                        .recursive(true)
+                        .mode(0o700)
+                        .create(dir)?;
+                }
+                OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .mode(0o600)
+                    .open(path)?
+                    .write_all(&secret)?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        Ok(secret)
+    }
+
+    // canonical directory of a file which may not exist yet, missing
+    // directories are appended to their closest existing ancestor
+    fn real_dir(path: &Path) -> io::Result<PathBuf> {
+        let mut dir = match path.parent() {
+            Some(d) if !d.as_os_str().is_empty() => d,
+            _ => Path::new("."),
+        };
+        let mut missing = vec![];
+
+        loop {
+            match dir.canonicalize() {
+                Ok(real) => return Ok(missing.iter().rev().fold(real, |p, m| p.join(m))),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    missing.push(dir.file_name().ok_or(e)?);
+                    dir = match dir.parent() {
+                        Some(d) if !d.as_os_str().is_empty() => d,
+                        _ => Path::new("."),
+                    };
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn new<P: AsRef<Path>, S: AsRef<Path>>(
+        path: P,
+        secret_path: S,
+        max_entries: usize,
+    ) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let secret_path = secret_path.as_ref();
+
+        // anyone able to write the cache must not be able to forge the HMAC
+        let cache_dir = Self::real_dir(&path)?;
+        let secret_dir = Self::real_dir(secret_path)?;
+        // every path is under the root directory so only
+        // the secrets stored directly in it are rejected
+        if secret_dir == cache_dir
+            || (cache_dir.parent().is_some() && secret_dir.starts_with(&cache_dir))
+        {
+            return Err(Error::Persistence(format!(
+                "secret file {} must not be in cache directory {}",
+                secret_path.display(),
+                cache_dir.display()
+            )));
+        }
+
+        Ok(Self {
+            secret: Self::load_or_create_secret(secret_path)?,
+            path,
+            max_entries,
+        })
+    }
+
+    #[inline]
+    fn header(&self, payload: &[u8]) -> String {
+        format!(
+            "{PERSIST_MAGIC} {PERSIST_VERSION} {}\n",
+            hex::encode(hmac_sha256(self.secret, payload))
+        )
+    }
+
+    fn read(&self) -> Result<Vec<PersistedEntry>, Error> {
+        let data = fs::read(&self.path)?;
+
+        let nl = data
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or(Error::Persistence("missing header".into()))?;
+        let (header, payload) = (&data[..=nl], &data[nl + 1..]);
+
+        // comparing the whole header checks magic, version and HMAC at once
+        let expected = self.header(payload);
+        if expected.len() != header.len()
+            || expected
+                .bytes()
+                .zip(header.iter())
+                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                != 0
+        {
+            return Err(Error::Persistence("integrity check failed".into()));
+        }
+
+        payload
+            .split(|&b| b == b'\n')
+            .filter(|l| !l.is_empty())
+            .take(self.max_entries)
+            .map(|l| {
+                serde_json::from_slice::<PersistedEntry>(l)
+                    .map_err(|e| Error::Persistence(format!("invalid entry: {e}")))
+            })
+            .collect()
+    }
+
+    fn write<'a, I: Iterator<Item = (&'a Key, &'a Hashes)>>(
+        &self,
+        entries: I,
+    ) -> Result<usize, Error> {
+        let mut payload = vec![];
+        let mut count = 0;
+
+        for (key, hashes) in entries.take(self.max_entries) {
+            serde_json::to_writer(
+                &mut payload,
+                &PersistedEntry {
+                    key: key.clone(),
+                    hashes: hashes.clone(),
+                },
+            )
+            .map_err(|e| Error::Persistence(format!("failed to serialize entry: {e}")))?;
+            payload.push(b'\n');
+            count += 1;
+        }
+
+        // we write into a temporary file first so that the
+        // cache file is never left half written
+        let mut tmp = self.path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+
+        let mut f = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&tmp)?;
+        f.write_all(self.header(&payload).as_bytes())?;
+        f.write_all(&payload)?;
+        f.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+
+        Ok(count)
+    }
+}
+
+struct Job {
+    key: Key,
+    // path to open, in the mount namespace of the switcher if any
+    target: PathBuf,
+    switcher: Option<Arc<Switcher>>,
+    path: PathBuf,
+}
+
+impl Job {
+    // file is opened by the worker so that the caller
+    // is not blocked by a slow filesystem
+    fn open(&self) -> io::Result<File> {
+        let Some(switcher) = self.switcher.as_ref() else {
+            return File::open(&self.target);
+        };
+
+        switcher.enter().map_err(io::Error::other)?;
+        let res = File::open(&self.target);
+        // we must be sure that we restore our namespace
+        switcher.exit().expect("failed to restore namespace");
+        res
+    }
+
+    fn hash(&self, opts: &HashOptions) -> Hashes {
+        match self.open() {
+            Ok(f) => Hashes::from_file(f, &self.path, opts),
+            // we cache the error so that we don't retry at every event
+            Err(e) => Hashes {
+                file: self.path.clone(),
+                error: Some(format!("{e}")),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Pool of threads hashing files in the background
+struct Pool {
+    jobs: SyncSender<Job>,
+    // Receiver is not Sync so it needs to be wrapped for the
+    // cache to be shared accross tasks
+    results: Mutex<Receiver<(Key, Hashes)>>,
+}
+
+impl Pool {
+    fn new(workers: usize, queue_size: usize, opts: Arc<HashOptions>) -> Result<Self, Error> {
+        let (jobs, rx) = mpsc::sync_channel::<Job>(queue_size);
+        let (tx, results) = mpsc::channel();
+        let rx = Arc::new(Mutex::new(rx));
+
+        for i in 0..workers.max(1) {
+            let rx = rx.clone();
+            let tx = tx.clone();
+            let opts = opts.clone();
+
+            thread::Builder::new()
+                .name(format!("kunai-hash-{i}"))
+                .spawn(move || {
+                    // workers must not share their fs_struct with the thread
+                    // they are spawned from, otherwise it cannot switch mnt namespace
+                    if unshare(libc::CLONE_FS).is_err() {
+                        return;
+                    }
+
+                    loop {
+                        // workers exit once the sending side is dropped
+                        let job = match rx.lock() {
+                            Ok(rx) => rx.recv(),
+                            Err(_) => break,
+                        };
+
+                        let Ok(job) = job else {
+                            break;
+                        };
+
+                        let h = job.hash(&opts);
+                        if tx.send((job.key, h)).is_err() {
+                            break;
+                        }
+                    }
+                })?;
+        }
+
+        Ok(Self {
+            jobs,
+            results: Mutex::new(results),
+        })
+    }
+}
+
+enum Lookup {
+    Cached(Box<Hashes>),
+    Pending(Key),
+    TooBig(Key),
+    Hash(Key, File),
+    // file must be opened and hashed by the pool
+    Schedule(Key, PathBuf),
 }
 
 pub struct Cache {
     namespaces: HashMap<Namespace, CachedNs>,
+    // user and group names per mount namespace
+    accounts: HashMap<Namespace, CachedAccounts>,
     hashes: LruHashMap<Key, Hashes>,
+    // keys ordered from least to most recently inserted
+    // used to persist the most recent entries first
+    recent: VecDeque<Key>,
+    cap: usize,
+    persistence: Option<Arc<Persistence>>,
+    // thread saving the cache on disk in background
+    saver: Option<thread::JoinHandle<Result<usize, Error>>>,
+    options: Arc<HashOptions>,
+    // hashing is done synchronously if there is no pool
+    pool: Option<Pool>,
+    // keys of the files being hashed by the pool
+    pending: HashSet<Key>,
+    // maximum time to wait for hashes computed by the pool
+    max_wait: Duration,
 }
 
 impl Cache {
@@ -180,24 +742,540 @@ impl Cache {
     pub fn with_max_entries(cap: usize) -> Self {
         Cache {
             namespaces: HashMap::new(),
+            accounts: HashMap::new(),
             hashes: LruHashMap::with_max_entries(cap),
+            recent: VecDeque::with_capacity(cap),
+            cap,
+            persistence: None,
+            saver: None,
+            options: Arc::new(HashOptions::default()),
+            pool: None,
+            pending: HashSet::new(),
+            max_wait: Duration::ZERO,
         }
     }
 
+    /// Sets the options used to hash files
+    pub fn with_options(mut self, opts: HashOptions) -> Self {
+        self.options = Arc::new(opts);
+        self
+    }
+
+    /// Moves hashing to a pool of workers. Callers wait at most max_wait
+    /// for hashes to be computed, after what hashes with a pending status
+    /// are returned. Hashes computed afterwards are made available by [`Cache::poll`].
+    pub fn enable_async_hashing(
+        &mut self,
+        workers: usize,
+        queue_size: usize,
+        max_wait: Duration,
+    ) -> Result<(), Error> {
+        self.pool = Some(Pool::new(workers, queue_size, self.options.clone())?);
+        self.max_wait = max_wait;
+        Ok(())
+    }
+
+    /// Inserts into the cache the hashes computed by the workers so far.
+    /// It returns the number of hashes inserted.
+    pub fn poll(&mut self) -> usize {
+        let Some(pool) = self.pool.as_ref() else {
+            return 0;
+        };
+
+        let results: Vec<(Key, Hashes)> = match pool.results.lock() {
+            Ok(rx) => rx.try_iter().collect(),
+            Err(_) => return 0,
+        };
+        let n = results.len();
+        for (key, h) in results {
+            self.complete(key, h);
+        }
+        n
+    }
+
     #[inline]
-    pub fn cache_ns(&mut self, pid: i32, ns: Namespace) -> Result<(), Error> {
-        self.namespaces.entry(ns).or_insert(CachedNs {
-            switcher: Switcher::new(ns.kind, pid as u32).map_err(Error::Namespace)?,
-        });
+    fn complete(&mut self, key: Key, hashes: Hashes) {
+        self.pending.remove(&key);
+        self.insert(key, hashes);
+    }
+
+    fn wait_for(&mut self, key: &Key) -> Option<Hashes> {
+        let deadline = Instant::now() + self.max_wait;
+
+        loop {
+            if let Some(h) = self.hashes.get(key) {
+                return Some(h.clone());
+            }
+
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                return None;
+            }
+
+            let res = self
+                .pool
+                .as_ref()?
+                .results
+                .lock()
+                .ok()?
+                .recv_timeout(timeout);
+            match res {
+                Ok((k, h)) => self.complete(k, h),
+                Err(_) => return None,
+            }
+        }
+    }
+
+    // must be called from the mount namespace the target is in
+    fn lookup(&mut self, key: Key, target: &Path) -> Lookup {
+        if let Some(h) = self.hashes.get(&key) {
+            return Lookup::Cached(Box::new(h.clone()));
+        }
+
+        if self.pending.contains(&key) {
+            return Lookup::Pending(key);
+        }
+
+        if key.size > self.options.max_size {
+            return Lookup::TooBig(key);
+        }
+
+        if self.pool.is_some() {
+            return Lookup::Schedule(key, target.to_path_buf());
+        }
+
+        match File::open(target) {
+            Ok(f) => Lookup::Hash(key, f),
+            Err(e) => {
+                // we cache the error so that we don't retry at every event
+                let h = Hashes {
+                    file: target.to_path_buf(),
+                    error: Some(format!("{e}")),
+                    ..Default::default()
+                };
+                self.insert(key, h.clone());
+                Lookup::Cached(Box::new(h))
+            }
+        }
+    }
+
+    fn resolve(
+        &mut self,
+        lookup: Lookup,
+        ns: Option<Namespace>,
+        path: PathBuf,
+        wait: bool,
+    ) -> Hashes {
+        let h = match lookup {
+            Lookup::Cached(h) => *h,
+            Lookup::Pending(key) => {
+                let h = if wait { self.wait_for(&key) } else { None };
+                h.unwrap_or_else(|| Hashes::pending(&path))
+            }
+            Lookup::TooBig(key) => {
+                let h = Hashes {
+                    file: path.clone(),
+                    size: key.size as usize,
+                    status: HashStatus::TooBig,
+                    ..Default::default()
+                };
+                self.insert(key, h.clone());
+                h
+            }
+            Lookup::Hash(key, file) => {
+                let h = Hashes::from_file(file, &path, &self.options);
+                self.insert(key, h.clone());
+                h
+            }
+            Lookup::Schedule(key, target) => {
+                // lookup only schedules jobs if there is a pool
+                let Some(pool) = self.pool.as_ref() else {
+                    return Hashes::pending(&path);
+                };
+
+                let job = Job {
+                    key: key.clone(),
+                    target,
+                    switcher: ns
+                        .and_then(|ns| self.namespaces.get(&ns))
+                        .map(|e| e.switcher.clone()),
+                    path: path.clone(),
+                };
+
+                match pool.jobs.try_send(job) {
+                    Ok(()) => {
+                        self.pending.insert(key.clone());
+                        let h = if wait { self.wait_for(&key) } else { None };
+                        h.unwrap_or_else(|| Hashes::pending(&path))
+                    }
+                    // the queue is full, file will be hashed at next event
+                    Err(TrySendError::Full(_)) => Hashes::pending(&path),
+                    // workers are gone so we fallback to synchronous hashing
+                    Err(TrySendError::Disconnected(job)) => {
+                        let h = job.hash(&self.options);
+                        self.insert(key, h.clone());
+                        h
+                    }
+                }
+            }
+        };
+
+        // the same content may be cached under another path
+        Hashes { file: path, ..h }
+    }
+
+    /// Enables on-disk persistence of the cache and loads entries previously
+    /// saved. The file is authenticated with a secret read from secret_path
+    /// (created if missing), which must be kept out of the cache directory.
+    /// It returns the number of entries loaded.
+    pub fn enable_persistence<P: AsRef<Path>, S: AsRef<Path>>(
+        &mut self,
+        path: P,
+        secret_path: S,
+        max_entries: usize,
+    ) -> Result<usize, Error> {
+        let p = Arc::new(Persistence::new(path, secret_path, max_entries)?);
+
+        let entries = match p.read() {
+            Ok(entries) => entries,
+            Err(Error::IoError(e)) if e.kind() == io::ErrorKind::NotFound => vec![],
+            Err(e) => {
+                // persistence is enabled so that the next save
+                // overwrites the invalid file
+                self.persistence = Some(p);
+                return Err(e);
+            }
+        };
+
+        let n = entries.len();
+        // entries are saved most recent first
+        for e in entries.into_iter().rev() {
+            self.insert(e.key, e.hashes);
+        }
+
+        self.persistence = Some(p);
+        Ok(n)
+    }
+
+    // entries to persist, most recent first
+    fn persisted_entries(&mut self) -> Vec<(Key, Hashes)> {
+        let mut seen = HashSet::new();
+        let mut entries = vec![];
+        for k in self.recent.iter().rev() {
+            if !seen.insert(k) {
+                continue;
+            }
+            // entry might have been evicted from LRU and we do not
+            // persist hashes of files we failed to read or skipped
+            if let Some(h) = self
+                .hashes
+                .get(k)
+                .filter(|h| h.status.is_complete() && h.error.is_none())
+            {
+                entries.push((k.clone(), h.clone()));
+            }
+        }
+        entries
+    }
+
+    #[inline]
+    fn join_saver(&mut self) -> Option<Result<usize, Error>> {
+        self.saver.take().map(|h| {
+            h.join()
+                .unwrap_or_else(|_| Err(Error::Persistence("saving thread panicked".into())))
+        })
+    }
+
+    /// Saves the cache on disk if persistence is enabled,
+    /// it returns the number of entries saved.
+    pub fn save(&mut self) -> Result<usize, Error> {
+        // a background save must not overwrite this one
+        self.join_saver();
+
+        let Some(p) = self.persistence.clone() else {
+            return Ok(0);
+        };
+
+        let entries = self.persisted_entries();
+        p.write(entries.iter().map(|(k, h)| (k, h)))
+    }
+
+    /// Saves the cache on disk in a background thread so that the caller is
+    /// not blocked by I/O. No save is started while the previous one is running.
+    /// It returns the result of the previous background save once it is over.
+    pub fn save_in_background(&mut self) -> Option<Result<usize, Error>> {
+        let p = self.persistence.clone()?;
+
+        if self.saver.as_ref().is_some_and(|h| !h.is_finished()) {
+            return None;
+        }
+
+        let prev = self.join_saver();
+        let entries = self.persisted_entries();
+
+        match thread::Builder::new()
+            .name("kunai-cache-save".into())
+            .spawn(move || p.write(entries.iter().map(|(k, h)| (k, h))))
+        {
+            Ok(h) => {
+                self.saver = Some(h);
+                prev
+            }
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+
+    #[inline]
+    fn insert(&mut self, key: Key, hashes: Hashes) {
+        if self.recent.len() >= self.cap {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(key.clone());
+        self.hashes.insert(key, hashes);
+    }
+
+    #[inline]
+    pub fn cache_ns(&mut self, pid: i32, tgid: i32, ns: Namespace) -> Result<(), Error> {
+        let entry = match self.namespaces.entry(ns) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(CachedNs {
+                switcher: Arc::new(Switcher::new(ns.kind, pid as u32).map_err(Error::Namespace)?),
+                tgids: HashSet::new(),
+            }),
+        };
+        entry.tgids.insert(tgid);
         Ok(())
     }
 
+    /// Notifies the cache that a process exited. When the last process known
+    /// to live in the namespace exits, everything cached for the namespace is
+    /// evicted so that the namespace itself can be released. It returns
+    /// true if the namespace has been evicted.
+    #[inline]
+    pub fn forget_task(&mut self, ns: Namespace, tgid: i32) -> bool {
+        let Some(entry) = self.namespaces.get_mut(&ns) else {
+            return false;
+        };
+
+        entry.tgids.remove(&tgid);
+
+        if entry.tgids.is_empty() {
+            self.namespaces.remove(&ns);
+            self.accounts.remove(&ns);
+            return true;
+        }
+
+        false
+    }
+
+    /// Returns the accounts (user and group names) found in a mount namespace.
+    /// Accounts are read once and cached until they are invalidated by
+    /// [`Cache::invalidate_accounts`] or the namespace is evicted.
+    pub fn get_or_cache_accounts_in_ns(&mut self, ns: Namespace) -> Result<&Accounts, Error> {
+        if !ns.is_kind(Kind::Mnt) {
+            return Err(Error::WrongNsKind {
+                exp: Kind::Mnt,
+                got: ns.kind,
+            });
+        }
+
+        match self.accounts.entry(ns) {
+            Entry::Occupied(e) => Ok(&e.into_mut().accounts),
+            Entry::Vacant(e) => {
+                let Some(entry) = self.namespaces.get(&ns) else {
+                    return Err(Error::UnknownNs(ns));
+                };
+
+                entry.switcher.enter()?;
+                let res = Accounts::load();
+                let devs = [accounts::PASSWD_PATH, accounts::GROUP_PATH]
+                    .iter()
+                    .filter_map(|p| fs::metadata(p).ok().map(|m| m.dev()))
+                    .collect();
+                // we must be sure that we restore our namespace
+                entry.switcher.exit().expect("failed to restore namespace");
+
+                // failed loads are not cached so that they are retried
+                Ok(&e
+                    .insert(CachedAccounts {
+                        accounts: res?,
+                        devs,
+                    })
+                    .accounts)
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn hash_options(&self) -> &HashOptions {
+        &self.options
+    }
+
+    /// Opens a file found in a mount namespace
+    pub fn open_in_ns<P: AsRef<Path>>(&mut self, ns: Namespace, path: P) -> Result<File, Error> {
+        if !ns.is_kind(Kind::Mnt) {
+            return Err(Error::WrongNsKind {
+                exp: Kind::Mnt,
+                got: ns.kind,
+            });
+        }
+
+        let Some(entry) = self.namespaces.get(&ns) else {
+            return Err(Error::UnknownNs(ns));
+        };
+
+        entry.switcher.enter()?;
+        let res = File::open(path.as_ref());
+        // we must be sure that we restore our namespace
+        entry.switcher.exit().expect("failed to restore namespace");
+
+        Ok(res?)
+    }
+
+    /// Reads the content of a file found in a mount namespace. At most
+    /// max_size + 1 bytes are read so that callers can detect bigger files.
+    pub fn read_in_ns<P: AsRef<Path>>(
+        &mut self,
+        ns: Namespace,
+        path: P,
+        max_size: u64,
+    ) -> Result<Vec<u8>, Error> {
+        let mut data = vec![];
+        self.open_in_ns(ns, path)?
+            .take(max_size + 1)
+            .read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    /// Invalidates accounts cached for the mount namespace an account database
+    /// has been written in, and for all the namespaces whose databases are on
+    /// the same device as the written file. If the device cannot be known
+    /// all the accounts cached are invalidated.
+    pub fn invalidate_accounts<P: AsRef<Path>>(&mut self, ns: Namespace, path: P) {
+        let dev = self.namespaces.get(&ns).and_then(|entry| {
+            entry.switcher.enter().ok()?;
+            let res = fs::metadata(path.as_ref()).map(|m| m.dev());
+            // we must be sure that we restore our namespace
+            entry.switcher.exit().expect("failed to restore namespace");
+            res.ok()
+        });
+
+        self.invalidate_accounts_on_dev(ns, dev);
+    }
+
+    #[inline]
+    fn invalidate_accounts_on_dev(&mut self, ns: Namespace, dev: Option<u64>) {
+        self.accounts.remove(&ns);
+        match dev {
+            Some(dev) => self.accounts.retain(|_, a| !a.devs.contains(&dev)),
+            None => self.accounts.clear(),
+        }
+    }
+
+    /// Computes the hashes of a file not reachable through its path anymore
+    /// (memfd, O_TMPFILE or deleted file) by reading it through a proxy path
+    /// like /proc/<pid>/exe or /proc/<pid>/fd/<fd>.
+    pub fn get_or_cache_with_proxy<P: AsRef<Path>>(
+        &mut self,
+        path: &kunai_common::path::Path,
+        proxy: P,
+    ) -> Result<Hashes, Error> {
+        let proxy = proxy.as_ref();
+        let key = Key::from_ebpf_path_with_target(path, proxy)?;
+        let lookup = self.lookup(key, proxy);
+        Ok(self.resolve(lookup, None, path.to_path_buf(), true))
+    }
+
+    /// Returns the hashes of a file found in a mount namespace. If hashing
+    /// is asynchronous, it waits at most the configured time and returns hashes
+    /// with a pending status if they are not yet available.
     #[inline]
     pub fn get_or_cache_in_ns(
         &mut self,
         ns: Namespace,
         path: &kunai_common::path::Path,
     ) -> Result<Hashes, Error> {
+        self.lookup_in_ns(
+            ns,
+            path.to_path_buf(),
+            |_| Key::from_ebpf_path_ref(path),
+            true,
+        )
+    }
+
+    /// Schedules the hashing of a file found in a mount namespace
+    /// without waiting for the result.
+    #[inline]
+    pub fn prefetch_in_ns(
+        &mut self,
+        ns: Namespace,
+        path: &kunai_common::path::Path,
+    ) -> Result<Hashes, Error> {
+        self.lookup_in_ns(
+            ns,
+            path.to_path_buf(),
+            |_| Key::from_ebpf_path_ref(path),
+            false,
+        )
+    }
+
+    /// Schedules the hashing of a regular file found in a mount namespace
+    /// without waiting for the result. Unlike [`Cache::prefetch_in_ns`] the
+    /// file is identified by its current metadata and not by the ones
+    /// of a kernel event.
+    #[inline]
+    pub fn prefetch_path_in_ns<P: AsRef<Path>>(
+        &mut self,
+        ns: Namespace,
+        path: P,
+    ) -> Result<Hashes, Error> {
+        self.lookup_in_ns(
+            ns,
+            path.as_ref().to_path_buf(),
+            |p| Key::from_path(p),
+            false,
+        )
+    }
+
+    /// Lists the entries of a directory found in a mount namespace
+    pub fn read_dir_in_ns<P: AsRef<Path>>(
+        &mut self,
+        ns: Namespace,
+        dir: P,
+    ) -> Result<Vec<(PathBuf, fs::FileType)>, Error> {
+        if !ns.is_kind(Kind::Mnt) {
+            return Err(Error::WrongNsKind {
+                exp: Kind::Mnt,
+                got: ns.kind,
+            });
+        }
+
+        let Some(entry) = self.namespaces.get(&ns) else {
+            return Err(Error::UnknownNs(ns));
+        };
+
+        entry.switcher.enter()?;
+        let res = fs::read_dir(dir.as_ref()).map(|rd| {
+            rd.flatten()
+                .filter_map(|e| e.file_type().ok().map(|ft| (e.path(), ft)))
+                .collect()
+        });
+        // we must be sure that we restore our namespace
+        entry.switcher.exit().expect("failed to restore namespace");
+
+        Ok(res?)
+    }
+
+    fn lookup_in_ns<F>(
+        &mut self,
+        ns: Namespace,
+        path: PathBuf,
+        key: F,
+        wait: bool,
+    ) -> Result<Hashes, Error>
+    where
+        F: FnOnce(&Path) -> Result<Key, Error>,
+    {
         // check that the namespace is a mount namespace
         if !ns.is_kind(Kind::Mnt) {
             return Err(Error::WrongNsKind {
@@ -212,21 +1290,286 @@ impl Cache {
 
         // we switch to namespace
         entry.switcher.enter()?;
-        let pb = path.to_path_buf();
 
-        let key = Key::from_ebpf_path_ref_with_ns(&ns, path)?;
+        // only the key computation and file opening are done in the namespace
+        let res = key(&path).map(|key| self.lookup(key, &path));
+
+        // we must be sure that we restore our namespace
+        self.namespaces
+            .get(&ns)
+            .expect("namespace must be cached")
+            .switcher
+            .exit()
+            .expect("failed to restore namespace");
+
+        Ok(self.resolve(res?, Some(ns), path, wait))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tmp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("kunai-cache-{}-{name}", std::process::id()))
+    }
+
+    fn cleanup(p: &Path) {
+        let _ = fs::remove_file(p);
+    }
+
+    // returns the path of a cache file and the one of
+    // its secret, which must live in another directory
+    fn persistence_paths(name: &str) -> (PathBuf, PathBuf) {
+        let dir = tmp_path(name);
+        fs::create_dir_all(&dir).unwrap();
+        (dir.join("cache"), tmp_path(&format!("{name}.key")))
+    }
+
+    fn cleanup_persistence(path: &Path, secret: &Path) {
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+        let _ = fs::remove_file(secret);
+    }
+
+    fn entry(i: u64) -> (Key, Hashes) {
+        (
+            Key {
+                ino: i,
+                size: i * 10,
+                ..Default::default()
+            },
+            Hashes {
+                file: PathBuf::from(format!("/bin/{i}")),
+                sha256: format!("{i:064x}"),
+                size: (i * 10) as usize,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_hashes_from_path() {
+        let exe = std::env::current_exe().unwrap();
+        let h = Hashes::from_path_ref(&exe);
+        // fuzzy hashes are not computed by default
+        assert!(h.tlsh.is_none() && h.ssdeep.is_none());
+        assert!(!h.setuid && !h.setgid);
+        assert_eq!(h.elf.unwrap().arch, std::env::consts::ARCH);
+
+        let opts = HashOptions::new(HashAlgo::ALL, DEFAULT_MAX_HASH_SIZE);
+        let h = Hashes::from_file(File::open(&exe).unwrap(), &exe, &opts);
+        assert!(h.tlsh.as_ref().is_some_and(|t| t.starts_with("T1")));
+        assert!(h.ssdeep.is_some());
+
+        let p = tmp_path("not-elf");
+        fs::write(&p, "#!/bin/sh\necho hello\n").unwrap();
+        let h = Hashes::from_path_ref(&p);
+        // too small for TLSH
+        assert!(h.tlsh.is_none());
+        assert!(h.elf.is_none());
+        cleanup(&p);
+    }
+
+    #[test]
+    fn test_hash_options() {
+        let p = tmp_path("hash-options");
+        let content = "#!/bin/sh\necho hello\n";
+        fs::write(&p, content).unwrap();
+
+        let opts = HashOptions::new([HashAlgo::Sha256, HashAlgo::Ssdeep], 1024);
+        let h = Hashes::from_file(File::open(&p).unwrap(), &p, &opts);
+        assert!(h.status.is_complete());
+        assert!(h.md5.is_empty() && h.sha1.is_empty() && h.sha512.is_empty());
+        assert_eq!(h.sha256.len(), 64);
+        assert!(h.ssdeep.is_some());
+        assert!(h.tlsh.is_none());
+        // disabled hashes are not iocs
+        assert_eq!(h.iocs().len(), 2);
+        // hashing data in memory must give the same result
+        let hb = Hashes::from_bytes(&p, &fs::read(&p).unwrap(), &opts);
+        assert_eq!(hb.sha256, h.sha256);
+        assert_eq!(hb.ssdeep, h.ssdeep);
+
+        let opts = HashOptions::new(HashAlgo::ALL, 8);
+        let h = Hashes::from_file(File::open(&p).unwrap(), &p, &opts);
+        assert_eq!(h.status, HashStatus::TooBig);
+        assert!(h.sha256.is_empty());
+        assert_eq!(h.size, content.len());
+
+        cleanup(&p);
+    }
+
+    #[test]
+    fn test_async_hashing() {
+        let p = tmp_path("async");
+        fs::write(&p, "#!/bin/sh\necho hello\n").unwrap();
+        let key = Key {
+            ino: 1,
+            size: 22,
+            ..Default::default()
+        };
+
+        let mut c = Cache::with_max_entries(10);
+        c.enable_async_hashing(1, 4, Duration::ZERO).unwrap();
+
+        // we don't wait so we must get a pending result
+        let l = c.lookup(key.clone(), &p);
+        let h = c.resolve(l, None, p.clone(), false);
+        assert_eq!(h.status, HashStatus::Pending);
+        assert!(c.pending.contains(&key));
+
+        // file must not be scheduled twice
+        assert!(matches!(c.lookup(key.clone(), &p), Lookup::Pending(_)));
+
+        c.max_wait = Duration::from_secs(5);
+        let l = c.lookup(key.clone(), &p);
+        let h = c.resolve(l, None, p.clone(), true);
+        assert!(h.status.is_complete());
+        assert_eq!(h.sha256.len(), 64);
+        assert!(c.pending.is_empty());
+        assert_eq!(c.poll(), 0);
+
+        // files too big are not hashed but cached with a marker
+        let mut c = Cache::with_max_entries(10).with_options(HashOptions::new(HashAlgo::ALL, 8));
+        let l = c.lookup(key.clone(), &p);
+        let h = c.resolve(l, None, p.clone(), true);
+        assert_eq!(h.status, HashStatus::TooBig);
+        assert!(c.hashes.contains_key(&key));
+
+        cleanup(&p);
+    }
+
+    #[test]
+    fn test_persistence() {
+        let (path, secret) = persistence_paths("persist");
 
-        if !self.hashes.contains_key(&key) {
-            let h = Hashes::from_path_ref(pb);
-            self.hashes.insert(key.clone(), h);
+        let mut c = Cache::with_max_entries(10);
+        assert_eq!(c.enable_persistence(&path, &secret, 3).unwrap(), 0);
+        for i in 0..5 {
+            let (k, h) = entry(i);
+            c.insert(k, h);
         }
+        // only the most recent entries are saved
+        assert_eq!(c.save().unwrap(), 3);
 
-        // we cannot panic here as we are sure the cache contains value
-        let res = Ok(self.hashes.get(&key).unwrap().clone());
+        let mut c = Cache::with_max_entries(10);
+        assert_eq!(c.enable_persistence(&path, &secret, 3).unwrap(), 3);
+        for i in 2..5 {
+            let (k, h) = entry(i);
+            assert_eq!(c.hashes.get(&k).unwrap().sha256, h.sha256);
+        }
+        assert!(!c.hashes.contains_key(&entry(0).0));
 
-        // we must be sure that we restore our namespace
-        entry.switcher.exit().expect("failed to restore namespace");
+        // saving in background
+        let (k, h) = entry(5);
+        c.insert(k, h);
+        assert!(c.save_in_background().is_none());
+        assert_eq!(c.join_saver().unwrap().unwrap(), 3);
+        let mut c = Cache::with_max_entries(10);
+        assert_eq!(c.enable_persistence(&path, &secret, 3).unwrap(), 3);
+        assert!(c.hashes.contains_key(&entry(5).0));
 
-        res
+        cleanup_persistence(&path, &secret);
+    }
+
+    #[test]
+    fn test_invalidate_accounts() {
+        let accounts = |devs: &[u64]| CachedAccounts {
+            accounts: Accounts::from_readers(&b""[..], &b""[..]).unwrap(),
+            devs: devs.iter().copied().collect(),
+        };
+        let (host, bind, other) = (Namespace::mnt(1), Namespace::mnt(2), Namespace::mnt(3));
+
+        let mut c = Cache::with_max_entries(10);
+        let reset = |c: &mut Cache| {
+            c.accounts.insert(host, accounts(&[1]));
+            c.accounts.insert(bind, accounts(&[1]));
+            c.accounts.insert(other, accounts(&[2]));
+        };
+
+        // namespaces sharing the databases are invalidated
+        reset(&mut c);
+        c.invalidate_accounts_on_dev(host, Some(1));
+        assert_eq!(c.accounts.keys().collect::<Vec<_>>(), vec![&other]);
+
+        // the namespace of the writer is always invalidated
+        reset(&mut c);
+        c.invalidate_accounts_on_dev(other, Some(3));
+        assert!(!c.accounts.contains_key(&other));
+        assert_eq!(c.accounts.len(), 2);
+
+        // unknown device
+        reset(&mut c);
+        c.invalidate_accounts_on_dev(other, None);
+        assert!(c.accounts.is_empty());
+    }
+
+    #[test]
+    fn test_persistence_secret() {
+        let (path, secret) = persistence_paths("secret");
+
+        // secret must not be stored next to the cache
+        let mut c = Cache::with_max_entries(10);
+        let next_to_cache = path.with_file_name("cache.key");
+        assert!(c.enable_persistence(&path, &next_to_cache, 10).is_err());
+        assert!(!next_to_cache.exists());
+        // whatever the way paths are written
+        fs::create_dir_all(path.with_file_name("sub")).unwrap();
+        let dotted = path.parent().unwrap().join("sub/../cache");
+        assert!(Persistence::new(dotted, &next_to_cache, 10).is_err());
+        let in_sub = path.with_file_name("sub").join("missing").join("cache.key");
+        assert!(Persistence::new(&path, in_sub, 10).is_err());
+
+        // cache path relative to the current directory
+        let p = Persistence::new("kunai-cache", &secret, 10).unwrap();
+        assert_eq!(p.path, PathBuf::from("kunai-cache"));
+        fs::remove_file(&secret).unwrap();
+        // cache stored in the root directory
+        assert!(Persistence::new("/kunai-cache", &secret, 10).is_ok());
+        assert!(Persistence::new("/kunai-cache", "/kunai-cache.key", 10).is_err());
+        fs::remove_file(&secret).unwrap();
+
+        // secret must only be accessible by its owner
+        fs::write(&secret, [0u8; PERSIST_SECRET_LEN]).unwrap();
+        fs::set_permissions(&secret, std::os::unix::fs::PermissionsExt::from_mode(0o644)).unwrap();
+        assert!(c.enable_persistence(&path, &secret, 10).is_err());
+
+        cleanup_persistence(&path, &secret);
+    }
+
+    #[test]
+    fn test_persistence_tampered() {
+        let (path, secret) = persistence_paths("tampered");
+
+        let mut c = Cache::with_max_entries(10);
+        c.enable_persistence(&path, &secret, 10).unwrap();
+        let (k, h) = entry(42);
+        c.insert(k, h);
+        c.save().unwrap();
+
+        // we change the hash of the entry
+        let data = fs::read_to_string(&path).unwrap();
+        fs::write(
+            &path,
+            data.replace(&format!("{:064x}", 42), &format!("{:064x}", 43)),
+        )
+        .unwrap();
+
+        let mut c = Cache::with_max_entries(10);
+        assert!(matches!(
+            c.enable_persistence(&path, &secret, 10),
+            Err(Error::Persistence(_))
+        ));
+        assert!(!c.hashes.contains_key(&entry(42).0));
+
+        // a new secret must invalidate the file
+        let mut c = Cache::with_max_entries(10);
+        c.enable_persistence(&path, &secret, 10).unwrap_err();
+        c.save().unwrap();
+        fs::remove_file(&secret).unwrap();
+        let mut c = Cache::with_max_entries(10);
+        assert!(c.enable_persistence(&path, &secret, 10).is_err());
+
+        cleanup_persistence(&path, &secret);
     }
 }
@@ -141,6 +141,8 @@ pub struct Program<'a> {
     pub compat: Compatibility,
     pub program: &'a mut programs::Program,
     pub enable: bool,
+    // an optional program failing to load or attach is not fatal
+    pub optional: bool,
     pub link_id: Option<LinkId>,
     pub loaded: bool,
     pub attached: bool,
@@ -155,6 +157,7 @@ impl<'a> Program<'a> {
             program: p,
             compat: Compatibility::default(),
             enable: true,
+            optional: false,
             link_id: None,
             loaded: false,
             attached: false,
@@ -254,6 +257,11 @@ impl<'a> Program<'a> {
         self
     }
 
+    pub fn optional(&mut self) -> &mut Self {
+        self.optional = true;
+        self
+    }
+
     pub fn disable_if(&mut self, condition: bool) -> &mut Self {
         if condition {
             self.enable = false
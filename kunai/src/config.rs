@@ -2,12 +2,18 @@ use core::str::FromStr;
 use huby::ByteSize;
 use kunai_common::{
     bpf_events,
-    config::{BpfConfig, Filter, Loader},
+    config::{BpfConfig, Filter, Loader, PathFilter, PathPattern},
+    path as kpath,
 };
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::{collections::HashSet, fs, path::PathBuf};
 use thiserror::Error;
 
+use crate::{
+    cache::{HashAlgo, HashOptions, DEFAULT_MAX_HASH_SIZE},
+    containers::{self, RuntimeRule},
+};
+
 pub const DEFAULT_SEND_DATA_MIN_LEN: u64 = 256;
 pub const DEFAULT_MAX_BUFFERED_EVENTS: u16 = 1024;
 
@@ -17,6 +23,10 @@ pub enum Error {
     InvalidOutput(String),
     #[error("invalid event {0}")]
     InvalidEvent(String),
+    #[error("invalid path pattern {0}")]
+    InvalidPathPattern(String),
+    #[error("invalid container runtime rule: {0}")]
+    InvalidRuntimeRule(#[from] containers::Error),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -45,6 +55,237 @@ pub struct FileSettings {
     pub max_size: ByteSize,
 }
 
+/// Settings of Kubernetes metadata enrichment
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KubernetesSettings {
+    pub enable: bool,
+    /// directories where container runtimes (containerd, CRI-O) keep container state
+    pub state_dirs: Vec<PathBuf>,
+}
+
+impl Default for KubernetesSettings {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            state_dirs: crate::containers::k8s::Resolver::default_state_dirs(),
+        }
+    }
+}
+
+/// Default file holding the secret authenticating the hash cache
+pub const DEFAULT_HASH_CACHE_SECRET: &str = "/etc/kunai/hash_cache.key";
+
+fn default_hash_cache_secret() -> PathBuf {
+    PathBuf::from(DEFAULT_HASH_CACHE_SECRET)
+}
+
+/// Settings of the on-disk hash cache
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HashCacheSettings {
+    pub path: PathBuf,
+    /// file holding the secret authenticating the cache, created if
+    /// missing. It must be kept out of the cache directory, in a
+    /// location only writable by root.
+    #[serde(default = "default_hash_cache_secret")]
+    pub secret_path: PathBuf,
+    /// maximum number of entries persisted
+    pub max_entries: usize,
+}
+
+/// Settings of file hashing
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HashingSettings {
+    /// hash algorithms to compute, fuzzy hashes (tlsh and ssdeep)
+    /// are expensive and not computed unless listed here
+    pub algorithms: Vec<HashAlgo>,
+    /// size in bytes above which files are not hashed
+    pub max_size: u64,
+    /// number of threads hashing files
+    pub workers: usize,
+    /// maximum number of files waiting to be hashed
+    pub queue_size: usize,
+    /// maximum time in milliseconds an event waits for
+    /// hashes before being sent with pending hashes
+    pub max_wait_ms: u64,
+}
+
+impl Default for HashingSettings {
+    fn default() -> Self {
+        Self {
+            algorithms: HashAlgo::DEFAULT.to_vec(),
+            max_size: DEFAULT_MAX_HASH_SIZE,
+            workers: 2,
+            queue_size: 1024,
+            max_wait_ms: 50,
+        }
+    }
+}
+
+impl HashingSettings {
+    pub fn options(&self) -> HashOptions {
+        HashOptions::new(self.algorithms.iter().copied(), self.max_size)
+    }
+}
+
+/// Environment variables always captured when environment capture is enabled
+pub const DEFAULT_ENV_VARIABLES: [&str; 13] = [
+    "LD_PRELOAD",
+    "LD_LIBRARY_PATH",
+    "PATH",
+    "http_proxy",
+    "https_proxy",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "all_proxy",
+    "ALL_PROXY",
+    "no_proxy",
+    "NO_PROXY",
+    "ftp_proxy",
+    "FTP_PROXY",
+];
+
+/// Settings of environment capture in execve events. Only the beginning
+/// of big environments is captured, env_truncated is set in events when
+/// some variables could not be looked at.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct EnvCaptureSettings {
+    pub enable: bool,
+    /// variables to capture in addition to DEFAULT_ENV_VARIABLES
+    pub variables: Vec<String>,
+}
+
+impl EnvCaptureSettings {
+    /// returns the names of all the variables to capture
+    pub fn variables(&self) -> HashSet<String> {
+        DEFAULT_ENV_VARIABLES
+            .iter()
+            .map(|s| s.to_string())
+            .chain(self.variables.iter().cloned())
+            .collect()
+    }
+}
+
+/// Settings of eBPF program introspection in bpf_prog_load events
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct BpfProgSettings {
+    /// adds the disassembly of loaded programs
+    pub disassemble: bool,
+}
+
+/// Paths of configuration files read or written by default
+pub const DEFAULT_CONFIG_FILES: [&str; 3] = [
+    "/etc/",
+    "/root/.ssh/authorized_keys",
+    "/home/*/.ssh/authorized_keys",
+];
+/// Paths never generating read events by default
+pub const DEFAULT_EXCLUDED_READ_FILES: [&str; 1] = ["/proc/"];
+
+/// Settings classifying file read and write events. Patterns match
+/// the beginning of paths and `*` matches any characters but `/`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConfigFilesSettings {
+    /// files generating read_config and write_config events
+    pub include: Vec<String>,
+    /// files not generating read events
+    pub exclude_read: Vec<String>,
+    /// files not generating write events
+    #[serde(default)]
+    pub exclude_write: Vec<String>,
+}
+
+impl Default for ConfigFilesSettings {
+    fn default() -> Self {
+        Self {
+            include: DEFAULT_CONFIG_FILES.iter().map(|s| s.to_string()).collect(),
+            exclude_read: DEFAULT_EXCLUDED_READ_FILES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            exclude_write: vec![],
+        }
+    }
+}
+
+impl TryFrom<&ConfigFilesSettings> for PathFilter {
+    type Error = Error;
+
+    fn try_from(value: &ConfigFilesSettings) -> Result<Self, Error> {
+        let mut filter = PathFilter::default();
+
+        for p in value.include.iter() {
+            if !filter.include(p) {
+                return Err(Error::InvalidPathPattern(p.clone()));
+            }
+        }
+
+        for p in value.exclude_read.iter() {
+            if !filter.exclude_read(p) {
+                return Err(Error::InvalidPathPattern(p.clone()));
+            }
+        }
+
+        for p in value.exclude_write.iter() {
+            if !filter.exclude_write(p) {
+                return Err(Error::InvalidPathPattern(p.clone()));
+            }
+        }
+
+        Ok(filter)
+    }
+}
+
+/// Files whose writes allow to tamper with the running kernel
+/// (tracing, bpf, kexec and module loading restrictions)
+pub const KERNEL_TAMPER_FILES: [&str; 8] = [
+    "/sys/kernel/tracing/",
+    "/sys/kernel/debug/tracing/",
+    "/proc/sys/kernel/unprivileged_bpf_disabled",
+    "/proc/sys/net/core/bpf_jit_",
+    "/proc/sys/kernel/kexec_load_disabled",
+    "/proc/sys/kernel/modules_disabled",
+    "/proc/sys/kernel/ftrace_enabled",
+    "/proc/sys/kernel/kptr_restrict",
+];
+
+/// Files monitored by default by file integrity monitoring. Files
+/// outside of [`DEFAULT_CONFIG_FILES`] (i.e. /usr/bin/sudo) are
+/// only monitored if write events are enabled.
+pub const DEFAULT_FIM_PATHS: [&str; 8] = [
+    "/usr/bin/sudo",
+    "/usr/bin/su",
+    "/etc/passwd",
+    "/etc/shadow",
+    "/etc/group",
+    "/etc/sudoers",
+    "/etc/sudoers.d/",
+    "/etc/pam.d/",
+];
+
+/// Settings of file integrity monitoring. Paths follow
+/// the same syntax as [`ConfigFilesSettings`] patterns. Changes are
+/// detected from write_config events, or from write events for paths
+/// not included in config files, so write events must be enabled to
+/// monitor such paths.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FimSettings {
+    pub enable: bool,
+    pub paths: Vec<String>,
+    /// time in milliseconds without modification before
+    /// a file is re-hashed
+    pub settle_ms: u64,
+}
+
+impl Default for FimSettings {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            paths: DEFAULT_FIM_PATHS.iter().map(|s| s.to_string()).collect(),
+            settle_ms: 1000,
+        }
+    }
+}
+
 /// Kunai configuration structure to be used in userland
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
@@ -57,6 +298,16 @@ pub struct Config {
     pub rules: Vec<String>,
     pub iocs: Vec<String>,
     pub harden: bool,
+    pub env_capture: Option<EnvCaptureSettings>,
+    pub hash_cache: Option<HashCacheSettings>,
+    pub hashing: Option<HashingSettings>,
+    pub bpf_prog: Option<BpfProgSettings>,
+    /// default settings are used if not set
+    pub config_files: Option<ConfigFilesSettings>,
+    pub fim: Option<FimSettings>,
+    pub kubernetes: Option<KubernetesSettings>,
+    /// container runtime detection rules, built-in rules are used if not set
+    pub container_runtimes: Option<Vec<RuntimeRule>>,
     pub events: Vec<Event>,
 }
 
@@ -85,6 +336,14 @@ impl Default for Config {
             rules: vec![],
             iocs: vec![],
             harden: false,
+            env_capture: Some(EnvCaptureSettings::default()),
+            hash_cache: None,
+            hashing: Some(HashingSettings::default()),
+            bpf_prog: Some(BpfProgSettings::default()),
+            config_files: Some(ConfigFilesSettings::default()),
+            fim: Some(FimSettings::default()),
+            kubernetes: Some(KubernetesSettings::default()),
+            container_runtimes: None,
             events,
         }
     }
@@ -136,6 +395,15 @@ impl Config {
     }
 
     pub fn validate(&self) -> Result<(), Error> {
+        self.container_detector()?;
+        self.config_files_filter()?;
+
+        if let Some(fim) = self.fim.as_ref() {
+            if let Some(p) = fim.paths.iter().find(|p| PathPattern::new(p).is_none()) {
+                return Err(Error::InvalidPathPattern(p.clone()));
+            }
+        }
+
         for e in self.events.iter() {
             let Ok(ty) = bpf_events::Type::from_str(&e.name) else {
                 return Err(Error::InvalidEvent(e.name.clone()));
@@ -148,6 +416,58 @@ impl Config {
         Ok(())
     }
 
+    /// Builds the container runtime detector out of the configured rules
+    pub fn container_detector(&self) -> Result<containers::Detector, Error> {
+        match self.container_runtimes.as_ref() {
+            Some(rules) => Ok(containers::Detector::try_from(rules.as_slice())?),
+            None => Ok(containers::Detector::default()),
+        }
+    }
+
+    /// Builds the filter classifying file read and write events
+    pub fn config_files_filter(&self) -> Result<PathFilter, Error> {
+        match self.config_files.as_ref() {
+            Some(s) => s.try_into(),
+            None => (&ConfigFilesSettings::default()).try_into(),
+        }
+    }
+
+    /// Returns the file integrity monitoring paths which do not generate
+    /// any event because they are not configuration files and write
+    /// events are disabled.
+    pub fn fim_paths_without_events(&self) -> Result<Vec<&str>, Error> {
+        let Some(fim) = self.fim.as_ref().filter(|f| f.enable) else {
+            return Ok(vec![]);
+        };
+
+        if Filter::try_from(self)?.is_enabled(bpf_events::Type::Write) {
+            return Ok(vec![]);
+        }
+
+        let config_files = self.config_files_filter()?;
+        Ok(fim
+            .paths
+            .iter()
+            .map(|p| p.as_str())
+            .filter(|p| {
+                let mut path = kpath::Path::default();
+                path.copy_from_str(p, kpath::Mode::Append).is_ok()
+                    && !config_files.is_included(&path)
+            })
+            .collect())
+    }
+
+    /// Builds the filter of files generating kernel_tamper events
+    pub fn tamper_files_filter(&self) -> Result<PathFilter, Error> {
+        let mut filter = PathFilter::default();
+        for p in KERNEL_TAMPER_FILES {
+            if !filter.include(p) {
+                return Err(Error::InvalidPathPattern(p.into()));
+            }
+        }
+        Ok(filter)
+    }
+
     pub fn enable_all(&mut self) {
         self.events.iter_mut().for_each(|e| e.enable())
     }
@@ -201,6 +521,11 @@ impl TryFrom<&Config> for BpfConfig {
             loader: Loader::from_own_pid(),
             filter: value.try_into()?,
             send_data_min_len: value.send_data_min_len.unwrap_or(DEFAULT_SEND_DATA_MIN_LEN),
+            capture_env: value.env_capture.as_ref().is_some_and(|e| e.enable),
+            config_files: value.config_files_filter()?,
+            tamper_files: value.tamper_files_filter()?,
+            // must be resolved from kernel symbols by the loader
+            bpf_prog_fops: 0,
         })
     }
 }
@@ -221,6 +546,46 @@ mod test {
         println!("{}", toml::to_string_pretty(&config).unwrap());
     }
 
+    #[test]
+    fn test_config_files() {
+        let mut config = Config::default();
+        assert!(config.config_files_filter().is_ok());
+
+        config.config_files = Some(ConfigFilesSettings {
+            include: vec!["/home/*/.ssh/authorized_keys".into()],
+            exclude_read: vec!["".into()],
+            exclude_write: vec![],
+        });
+        assert!(matches!(
+            config.validate(),
+            Err(Error::InvalidPathPattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_fim_paths_without_events() {
+        let mut config = Config::default();
+        assert!(config.fim_paths_without_events().unwrap().is_empty());
+
+        config.fim = Some(FimSettings {
+            enable: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            config.fim_paths_without_events().unwrap(),
+            vec!["/usr/bin/sudo", "/usr/bin/su"]
+        );
+
+        config.enable_all();
+        assert!(config.fim_paths_without_events().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tamper_files() {
+        let config = Config::default();
+        assert!(config.tamper_files_filter().is_ok());
+    }
+
     #[test]
     fn test_machine_uuid() {
         let uuid = host_uuid();
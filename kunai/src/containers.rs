@@ -3,7 +3,10 @@ use gene::FieldGetter;
 use kunai_common::cgroup::Cgroup;
 use kunai_macros::StrEnum;
 use serde::{Deserialize, Serialize};
-use std::path::{self};
+use std::fmt;
+use thiserror::Error;
+
+pub mod k8s;
 
 #[derive(StrEnum, Debug, PartialEq, Clone, Copy)]
 pub enum Container {
@@ -15,6 +18,20 @@ pub enum Container {
     Firejail,
     #[str("podman")]
     Podman,
+    #[str("containerd")]
+    Containerd,
+    #[str("crio")]
+    Crio,
+    #[str("nspawn")]
+    Nspawn,
+    #[str("bubblewrap")]
+    Bubblewrap,
+    #[str("flatpak")]
+    Flatpak,
+    #[str("snap")]
+    Snap,
+    #[str("gvisor")]
+    Gvisor,
 }
 
 impl Serialize for Container {
@@ -67,50 +84,340 @@ impl FieldGetter for Container {
     }
 }
 
-impl Container {
-    fn from_split_cgroup<S: AsRef<str>>(cgroup: Vec<S>) -> Option<Container> {
-        if let Some(last) = cgroup.last() {
-            if last.as_ref().starts_with("docker-") {
-                return Some(Container::Docker);
+// length of container ids generated by docker, containerd, cri-o and podman
+const CONTAINER_ID_LEN: usize = 64;
+// maximum number of steps done to match a string against a pattern
+const MAX_MATCH_STEPS: usize = 1 << 16;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("unclosed placeholder in pattern: {0}")]
+    UnclosedPlaceholder(String),
+    #[error("unknown placeholder {{{1}}} in pattern: {0}")]
+    UnknownPlaceholder(String, String),
+    #[error("too many captures in pattern: {0}")]
+    TooManyCaptures(String),
+}
+
+/// unescapes systemd unit names (i.e. \x2d is turned into -)
+fn systemd_unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find("\\x") {
+        out.push_str(&rest[..i]);
+        let hex = rest.get(i + 2..i + 4);
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(b) => {
+                out.push(b as char);
+                rest = &rest[i + 4..];
+            }
+            None => {
+                out.push_str(&rest[i..i + 2]);
+                rest = &rest[i + 2..];
             }
         }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    // matches any sequence of characters
+    Star,
+    // matches a container id
+    Id,
+    // matches a non empty sequence of characters not containing /
+    Name,
+}
+
+/// Simple glob pattern used to match cgroups and ancestors. Besides `*`
+/// a pattern may contain one placeholder capturing the container identifier:
+/// `{id}` matching a 64 hex chars container id or `{name}` matching a path
+/// component (systemd escaped characters are unescaped).
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    src: String,
+    tokens: Vec<Token>,
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.src)
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = Error;
 
-        if let Some(first) = cgroup.get(1) {
-            if first.as_ref().starts_with("lxc.payload.") {
-                return Some(Container::Lxc);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = vec![];
+        let mut lit = String::new();
+        let mut captures = 0;
+        let mut chars = s.chars();
+
+        while let Some(c) = chars.next() {
+            let tok = match c {
+                '*' => Token::Star,
+                '{' => {
+                    let mut ph = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => ph.push(c),
+                            None => return Err(Error::UnclosedPlaceholder(s.into())),
+                        }
+                    }
+                    captures += 1;
+                    match ph.as_str() {
+                        "id" => Token::Id,
+                        "name" => Token::Name,
+                        _ => return Err(Error::UnknownPlaceholder(s.into(), ph)),
+                    }
+                }
+                _ => {
+                    lit.push(c);
+                    continue;
+                }
+            };
+
+            if !lit.is_empty() {
+                tokens.push(Token::Literal(lit.clone()));
+                lit.clear();
             }
+            tokens.push(tok);
         }
 
-        None
+        if !lit.is_empty() {
+            tokens.push(Token::Literal(lit));
+        }
+
+        if captures > 1 {
+            return Err(Error::TooManyCaptures(s.into()));
+        }
+
+        Ok(Self {
+            src: s.into(),
+            tokens,
+        })
     }
+}
 
-    #[inline]
-    pub fn from_cgroup(cgrp: &Cgroup) -> Option<Container> {
-        Self::from_split_cgroup(cgrp.to_vec())
+impl Pattern {
+    fn match_tokens(tokens: &[Token], s: &str, steps: &mut usize) -> Option<Option<String>> {
+        // stars and names backtrack so we bound the work done on long strings
+        *steps = steps.checked_sub(1)?;
+
+        let Some((tok, rest)) = tokens.split_first() else {
+            return s.is_empty().then_some(None);
+        };
+
+        match tok {
+            Token::Literal(l) => Self::match_tokens(rest, s.strip_prefix(l.as_str())?, steps),
+            Token::Star => s
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain([s.len()])
+                .find_map(|i| Self::match_tokens(rest, &s[i..], steps)),
+            Token::Id => {
+                let id = s.get(..CONTAINER_ID_LEN)?;
+                if !id.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return None;
+                }
+                Self::match_tokens(rest, &s[CONTAINER_ID_LEN..], steps).map(|_| Some(id.into()))
+            }
+            Token::Name => {
+                // we take the shortest name matching
+                let end = s.find('/').unwrap_or(s.len());
+                s[..end]
+                    .char_indices()
+                    .skip(1)
+                    .map(|(i, _)| i)
+                    .chain([end])
+                    .filter(|&i| i > 0)
+                    .find_map(|i| {
+                        Self::match_tokens(rest, &s[i..], steps)
+                            .map(|_| Some(systemd_unescape(&s[..i])))
+                    })
+            }
+        }
     }
 
+    /// Returns `None` if the pattern does not match, `Some(capture)` otherwise.
+    /// Matching gives up (no match) after [MAX_MATCH_STEPS] steps.
     #[inline]
-    pub fn from_cgroups(cgroups: &Vec<String>) -> Option<Container> {
-        for c in cgroups {
-            if let Some(c) = Self::from_split_cgroup(c.split(path::MAIN_SEPARATOR).collect()) {
-                return Some(c);
+    pub fn matches<S: AsRef<str>>(&self, s: S) -> Option<Option<String>> {
+        let mut steps = MAX_MATCH_STEPS;
+        Self::match_tokens(&self.tokens, s.as_ref(), &mut steps)
+    }
+}
+
+/// Configuration of a container runtime detection rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeRule {
+    pub runtime: Container,
+    /// patterns matching the cgroups of a task
+    #[serde(default)]
+    pub cgroups: Vec<String>,
+    /// patterns matching the executable of task's ancestors
+    #[serde(default)]
+    pub ancestors: Vec<String>,
+}
+
+impl RuntimeRule {
+    fn new(runtime: Container, cgroups: &[&str], ancestors: &[&str]) -> Self {
+        Self {
+            runtime,
+            cgroups: cgroups.iter().map(|s| s.to_string()).collect(),
+            ancestors: ancestors.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// Built-in container runtime detection rules
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self::new(
+                Container::Docker,
+                &[
+                    "*/docker-{id}.scope*",
+                    "*/docker/{id}*",
+                    // kubelet cgroupfs driver does not tell which runtime is used,
+                    // we keep the one given by containerd-shim ancestor
+                    "*/kubepods*/pod*/{id}*",
+                ],
+                // docker containers run under containerd-shim too
+                &["*/containerd-shim*"],
+            ),
+            Self::new(
+                Container::Containerd,
+                &["*/cri-containerd-{id}.scope*"],
+                &[],
+            ),
+            // conmon-<id> are not matched as conmon is not a hex string
+            Self::new(Container::Crio, &["*/crio-{id}.scope*", "*/crio-{id}"], &[]),
+            Self::new(
+                Container::Podman,
+                &["*/libpod-{id}.scope*", "*/libpod_parent/libpod-{id}*"],
+                &["*/podman"],
+            ),
+            Self::new(
+                Container::Nspawn,
+                &[
+                    "*/machine.slice/systemd-nspawn@{name}.service*",
+                    "*/machine.slice/machine-{name}.scope*",
+                ],
+                &["*/systemd-nspawn"],
+            ),
+            Self::new(
+                Container::Lxc,
+                &[
+                    "*/lxc.payload.{name}/*",
+                    "*/lxc.payload.{name}",
+                    "*/lxc.payload/{name}/*",
+                    "*/lxc.payload/{name}",
+                ],
+                &["/snap/lxd/*/bin/lxd", "*/lxc-start"],
+            ),
+            Self::new(
+                Container::Flatpak,
+                &["*/app-flatpak-{name}-*.scope*"],
+                &["*/flatpak"],
+            ),
+            Self::new(Container::Snap, &["*/snap.{name}.*"], &[]),
+            Self::new(Container::Gvisor, &[], &["*/runsc", "*/runsc-sandbox"]),
+            Self::new(Container::Firejail, &[], &["*/firejail"]),
+            // flatpak uses bubblewrap so it must come after
+            Self::new(Container::Bubblewrap, &[], &["*/bwrap"]),
+        ]
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    runtime: Container,
+    cgroups: Vec<Pattern>,
+    ancestors: Vec<Pattern>,
+}
+
+impl TryFrom<&RuntimeRule> for CompiledRule {
+    type Error = Error;
+
+    fn try_from(value: &RuntimeRule) -> Result<Self, Self::Error> {
+        Ok(Self {
+            runtime: value.runtime,
+            cgroups: value
+                .cgroups
+                .iter()
+                .map(|p| Pattern::from_str(p))
+                .collect::<Result<_, _>>()?,
+            ancestors: value
+                .ancestors
+                .iter()
+                .map(|p| Pattern::from_str(p))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Detects container runtimes out of task cgroups and ancestors,
+/// rules are evaluated in order and the first match wins.
+#[derive(Debug, Clone)]
+pub struct Detector {
+    rules: Vec<CompiledRule>,
+}
+
+impl Default for Detector {
+    fn default() -> Self {
+        // builtin rules are tested so this cannot fail
+        Self::try_from(RuntimeRule::builtin().as_slice()).unwrap()
+    }
+}
+
+impl TryFrom<&[RuntimeRule]> for Detector {
+    type Error = Error;
+
+    fn try_from(value: &[RuntimeRule]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            rules: value
+                .iter()
+                .map(CompiledRule::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl Detector {
+    /// Returns the container type and id (if any) found in a list of cgroups
+    pub fn from_cgroups<S: AsRef<str>>(
+        &self,
+        cgroups: &[S],
+    ) -> Option<(Container, Option<String>)> {
+        for r in self.rules.iter() {
+            for p in r.cgroups.iter() {
+                for c in cgroups {
+                    if let Some(id) = p.matches(c) {
+                        return Some((r.runtime, id));
+                    }
+                }
             }
         }
         None
     }
 
+    /// Returns the container type and id (if any) associated to a cgroup
     #[inline]
-    pub fn from_ancestors(ancestors: &Vec<String>) -> Option<Container> {
-        for a in ancestors {
-            match a.as_str() {
-                "/usr/bin/firejail" => return Some(Container::Firejail),
-                "/usr/bin/containerd-shim-runc-v2" => return Some(Container::Docker),
-                "/usr/bin/podman" => return Some(Container::Podman),
-                _ => {}
-            };
+    pub fn from_cgroup(&self, cgrp: &Cgroup) -> Option<(Container, Option<String>)> {
+        self.from_cgroups(&[cgrp.to_string()])
+    }
 
-            if a.starts_with("/snap/lxd/") && a.ends_with("/bin/lxd/") {
-                return Some(Container::Lxc);
+    /// Returns the container type out of the executables of task's ancestors
+    pub fn from_ancestors<S: AsRef<str>>(&self, ancestors: &[S]) -> Option<Container> {
+        for r in self.rules.iter() {
+            for p in r.ancestors.iter() {
+                if ancestors.iter().any(|a| p.matches(a).is_some()) {
+                    return Some(r.runtime);
+                }
             }
         }
         None
@@ -133,4 +440,265 @@ mod test {
         // this is an unknown variant so we should get an error
         assert!(serde_json::from_str::<'_, Container>(r#""lxk""#).is_err());
     }
+
+    const ID: &str = "3d1b2c8a0a9c5e1f0b4f0e8c6a2d7e9b1c3f5a7d9e0b2c4d6f8a1b3c5d7e9f0a";
+
+    fn from_cgroup_str(cgroup: &str) -> Option<(Container, Option<String>)> {
+        Detector::default().from_cgroups(&[cgroup])
+    }
+
+    fn from_ancestor_str(ancestor: &str) -> Option<Container> {
+        Detector::default().from_ancestors(&["/usr/lib/systemd/systemd", ancestor])
+    }
+
+    #[test]
+    fn test_pattern() {
+        let p = Pattern::from_str("*/docker-{id}.scope").unwrap();
+        assert_eq!(
+            p.matches(format!("/system.slice/docker-{ID}.scope")),
+            Some(Some(ID.into()))
+        );
+        assert_eq!(p.matches(format!("/system.slice/docker-{ID}")), None);
+        // id is too short
+        assert_eq!(p.matches("/system.slice/docker-3d1b2c8a.scope"), None);
+
+        let p = Pattern::from_str("/usr/*/bin/*").unwrap();
+        assert_eq!(p.matches("/usr/local/bin/runc"), Some(None));
+        assert_eq!(p.matches("/usr/bin/runc"), None);
+
+        // backtracking is bounded on long strings
+        let p = Pattern::from_str("*a*a*a*b").unwrap();
+        assert_eq!(p.matches("a".repeat(4096)), None);
+
+        assert!(Pattern::from_str("*/docker-{id").is_err());
+        assert!(Pattern::from_str("*/docker-{uuid}").is_err());
+        assert!(Pattern::from_str("*/{name}/{id}").is_err());
+    }
+
+    #[test]
+    fn test_docker() {
+        let id = Some(ID.to_string());
+        assert_eq!(
+            from_cgroup_str(&format!("/system.slice/docker-{ID}.scope")),
+            Some((Container::Docker, id.clone()))
+        );
+        assert_eq!(
+            from_cgroup_str(&format!("/docker/{ID}")),
+            Some((Container::Docker, id.clone()))
+        );
+        // kubelet cgroupfs driver
+        assert_eq!(
+            from_cgroup_str(&format!(
+                "/kubepods/burstable/pod6c1a8b2e-2f0d-4b7a-9c1e-0f2b3a4c5d6e/{ID}"
+            )),
+            Some((Container::Docker, id.clone()))
+        );
+        assert_eq!(
+            from_cgroup_str(&format!(
+                "/kubepods/pod6c1a8b2e-2f0d-4b7a-9c1e-0f2b3a4c5d6e/{ID}"
+            )),
+            Some((Container::Docker, id.clone()))
+        );
+        // docker daemon is not a container
+        assert_eq!(from_cgroup_str("/system.slice/docker.service"), None);
+        assert_eq!(
+            from_ancestor_str("/usr/bin/containerd-shim-runc-v2"),
+            Some(Container::Docker)
+        );
+    }
+
+    #[test]
+    fn test_containerd() {
+        let id = Some(ID.to_string());
+        assert_eq!(
+            from_cgroup_str(&format!(
+                "/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod6c1a8b2e_2f0d_4b7a_9c1e_0f2b3a4c5d6e.slice/cri-containerd-{ID}.scope"
+            )),
+            Some((Container::Containerd, id.clone()))
+        );
+        // pod cgroup alone is not a container
+        assert_eq!(
+            from_cgroup_str("/kubepods/burstable/pod6c1a8b2e-2f0d-4b7a-9c1e-0f2b3a4c5d6e"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_crio() {
+        assert_eq!(
+            from_cgroup_str(&format!(
+                "/kubepods.slice/kubepods-besteffort.slice/kubepods-besteffort-pod6c1a8b2e_2f0d_4b7a_9c1e_0f2b3a4c5d6e.slice/crio-{ID}.scope"
+            )),
+            Some((Container::Crio, Some(ID.into())))
+        );
+        // cgroupfs driver
+        assert_eq!(
+            from_cgroup_str(&format!(
+                "/kubepods/besteffort/pod6c1a8b2e-2f0d-4b7a-9c1e-0f2b3a4c5d6e/crio-{ID}"
+            )),
+            Some((Container::Crio, Some(ID.into())))
+        );
+        // conmon is not running inside the container
+        assert_eq!(
+            from_cgroup_str(&format!("/machine.slice/crio-conmon-{ID}.scope")),
+            None
+        );
+        assert_eq!(
+            from_cgroup_str(&format!(
+                "/kubepods/besteffort/pod6c1a8b2e-2f0d-4b7a-9c1e-0f2b3a4c5d6e/crio-conmon-{ID}"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn test_podman() {
+        assert_eq!(
+            from_cgroup_str(&format!(
+                "/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{ID}.scope/container"
+            )),
+            Some((Container::Podman, Some(ID.into())))
+        );
+        assert_eq!(
+            from_cgroup_str(&format!("/machine.slice/libpod-{ID}.scope")),
+            Some((Container::Podman, Some(ID.into())))
+        );
+        assert_eq!(
+            from_cgroup_str(&format!("/libpod_parent/libpod-{ID}")),
+            Some((Container::Podman, Some(ID.into())))
+        );
+        assert_eq!(
+            from_cgroup_str(&format!("/machine.slice/libpod-conmon-{ID}.scope")),
+            None
+        );
+        assert_eq!(
+            from_ancestor_str("/usr/local/bin/podman"),
+            Some(Container::Podman)
+        );
+    }
+
+    #[test]
+    fn test_nspawn() {
+        assert_eq!(
+            from_cgroup_str("/machine.slice/systemd-nspawn@debian.service/payload"),
+            Some((Container::Nspawn, Some("debian".into())))
+        );
+        assert_eq!(
+            from_cgroup_str("/machine.slice/machine-my\\x2dbox.scope/payload"),
+            Some((Container::Nspawn, Some("my-box".into())))
+        );
+        assert_eq!(
+            from_ancestor_str("/usr/bin/systemd-nspawn"),
+            Some(Container::Nspawn)
+        );
+    }
+
+    #[test]
+    fn test_lxc() {
+        assert_eq!(
+            from_cgroup_str("/lxc.payload.ubuntu/system.slice/cron.service"),
+            Some((Container::Lxc, Some("ubuntu".into())))
+        );
+        assert_eq!(
+            from_cgroup_str("/lxc.payload.ubuntu"),
+            Some((Container::Lxc, Some("ubuntu".into())))
+        );
+        assert_eq!(
+            from_cgroup_str("/lxc.payload/ubuntu/init.scope"),
+            Some((Container::Lxc, Some("ubuntu".into())))
+        );
+        assert_eq!(
+            from_cgroup_str("/lxc.payload/ubuntu"),
+            Some((Container::Lxc, Some("ubuntu".into())))
+        );
+        // lxc monitor runs outside of the container
+        assert_eq!(from_cgroup_str("/lxc.monitor.ubuntu"), None);
+        assert_eq!(
+            from_ancestor_str("/snap/lxd/27037/bin/lxd"),
+            Some(Container::Lxc)
+        );
+    }
+
+    #[test]
+    fn test_flatpak() {
+        assert_eq!(
+            from_cgroup_str(
+                "/user.slice/user-1000.slice/user@1000.service/app.slice/app-flatpak-org.mozilla.firefox-12345.scope"
+            ),
+            Some((Container::Flatpak, Some("org.mozilla.firefox".into())))
+        );
+        // flatpak runs bwrap so it must take precedence
+        assert_eq!(
+            Detector::default().from_ancestors(&["/usr/bin/bwrap", "/usr/bin/flatpak"]),
+            Some(Container::Flatpak)
+        );
+    }
+
+    #[test]
+    fn test_snap() {
+        assert_eq!(
+            from_cgroup_str(
+                "/user.slice/user-1000.slice/user@1000.service/app.slice/snap.firefox.firefox-2f1c0c3e-6d4e-4c7b-8b1a-0e5d2c3b4a69.scope"
+            ),
+            Some((Container::Snap, Some("firefox".into())))
+        );
+    }
+
+    #[test]
+    fn test_sandboxes() {
+        assert_eq!(
+            from_ancestor_str("/usr/local/bin/runsc"),
+            Some(Container::Gvisor)
+        );
+        assert_eq!(
+            from_ancestor_str("/usr/bin/firejail"),
+            Some(Container::Firejail)
+        );
+        assert_eq!(
+            from_ancestor_str("/usr/bin/bwrap"),
+            Some(Container::Bubblewrap)
+        );
+        assert_eq!(from_ancestor_str("/usr/bin/bash"), None);
+    }
+
+    #[test]
+    fn test_host() {
+        assert_eq!(
+            from_cgroup_str("/user.slice/user-1000.slice/session-2.scope"),
+            None
+        );
+        assert_eq!(from_cgroup_str("/init.scope"), None);
+        assert_eq!(from_cgroup_str("/"), None);
+        assert_eq!(from_cgroup_str(""), None);
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let rules: Vec<RuntimeRule> = toml::from_str::<toml::Value>(
+            r#"
+            [[rules]]
+            runtime = "docker"
+            cgroups = ["/custom/ctr-{id}"]
+            ancestors = ["/opt/*/shim"]
+            "#,
+        )
+        .unwrap()
+        .get("rules")
+        .unwrap()
+        .clone()
+        .try_into()
+        .unwrap();
+
+        let d = Detector::try_from(rules.as_slice()).unwrap();
+        assert_eq!(
+            d.from_cgroups(&[format!("/custom/ctr-{ID}")]),
+            Some((Container::Docker, Some(ID.into())))
+        );
+        assert_eq!(
+            d.from_ancestors(&["/opt/runtime/shim"]),
+            Some(Container::Docker)
+        );
+        // builtin rules are not used anymore
+        assert_eq!(d.from_cgroups(&[format!("/docker/{ID}")]), None);
+    }
 }
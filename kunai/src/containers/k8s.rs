@@ -0,0 +1,337 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use gene::FieldGetter;
+use lru_st::collections::LruHashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Default directory where containerd stores the OCI bundles of containers
+/// created through its CRI plugin
+pub const CONTAINERD_STATE_DIR: &str = "/run/containerd/io.containerd.runtime.v2.task/k8s.io";
+/// Default directories where CRI-O stores container runtime state
+pub const CRIO_STATE_DIRS: [&str; 2] = [
+    "/run/containers/storage/overlay-containers",
+    "/var/lib/containers/storage/overlay-containers",
+];
+
+const DEFAULT_MAX_ENTRIES: usize = 1024;
+// delay after which a failed lookup is retried, runtime state
+// may not be fully written when the first event of a container
+// gets processed
+const DEFAULT_MISS_TTL: Duration = Duration::from_secs(5);
+
+// annotations set by containerd CRI plugin
+const CONTAINERD_POD_NAME: &str = "io.kubernetes.cri.sandbox-name";
+const CONTAINERD_POD_NAMESPACE: &str = "io.kubernetes.cri.sandbox-namespace";
+const CONTAINERD_POD_UID: &str = "io.kubernetes.cri.sandbox-uid";
+const CONTAINERD_CONTAINER_NAME: &str = "io.kubernetes.cri.container-name";
+const CONTAINERD_IMAGE: &str = "io.kubernetes.cri.image-name";
+
+// annotations set by CRI-O
+const CRIO_POD_NAME: &str = "io.kubernetes.pod.name";
+const CRIO_POD_NAMESPACE: &str = "io.kubernetes.pod.namespace";
+const CRIO_POD_UID: &str = "io.kubernetes.pod.uid";
+const CRIO_CONTAINER_NAME: &str = "io.kubernetes.container.name";
+const CRIO_IMAGE: &str = "io.kubernetes.cri-o.ImageName";
+const CRIO_LABELS: &str = "io.kubernetes.cri-o.Labels";
+
+// labels used internally by kubelet, we do not report them
+// as labels since they are already part of PodInfo
+const KUBERNETES_LABEL_PREFIX: &str = "io.kubernetes.";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("no kubernetes annotation")]
+    NoAnnotation,
+}
+
+/// Kubernetes labels, they can be accessed by name from rules
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Labels(BTreeMap<String, String>);
+
+impl Labels {
+    #[inline]
+    pub fn get(&self, k: &str) -> Option<&String> {
+        self.0.get(k)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FieldGetter for Labels {
+    fn get_from_iter(
+        &self,
+        mut i: core::slice::Iter<'_, std::string::String>,
+    ) -> Option<gene::FieldValue> {
+        let k = i.next()?;
+        if i.len() > 0 {
+            return None;
+        }
+        self.0.get(k).map(|v| v.as_str().into())
+    }
+}
+
+/// Kubernetes metadata of a container
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PodInfo {
+    pub name: String,
+    pub namespace: String,
+    pub uid: Option<String>,
+    pub container_name: Option<String>,
+    pub image: Option<String>,
+    pub labels: Labels,
+}
+
+#[derive(Deserialize)]
+struct OciSpec {
+    #[serde(default)]
+    annotations: HashMap<String, String>,
+}
+
+impl PodInfo {
+    /// Builds PodInfo out of the annotations of an OCI runtime spec,
+    /// both containerd and CRI-O annotations are supported. Pod labels
+    /// are only exposed by CRI-O.
+    fn from_annotations(mut a: HashMap<String, String>) -> Result<Self, Error> {
+        if let (Some(name), Some(namespace)) = (
+            a.remove(CONTAINERD_POD_NAME),
+            a.remove(CONTAINERD_POD_NAMESPACE),
+        ) {
+            return Ok(Self {
+                name,
+                namespace,
+                uid: a.remove(CONTAINERD_POD_UID),
+                container_name: a.remove(CONTAINERD_CONTAINER_NAME),
+                image: a.remove(CONTAINERD_IMAGE),
+                labels: Labels::default(),
+            });
+        }
+
+        if let (Some(name), Some(namespace)) =
+            (a.remove(CRIO_POD_NAME), a.remove(CRIO_POD_NAMESPACE))
+        {
+            let labels = a
+                .remove(CRIO_LABELS)
+                .map(|l| serde_json::from_str::<BTreeMap<String, String>>(&l))
+                .transpose()?
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(k, _)| !k.starts_with(KUBERNETES_LABEL_PREFIX))
+                .collect();
+
+            return Ok(Self {
+                name,
+                namespace,
+                uid: a.remove(CRIO_POD_UID),
+                container_name: a.remove(CRIO_CONTAINER_NAME),
+                image: a.remove(CRIO_IMAGE),
+                labels: Labels(labels),
+            });
+        }
+
+        Err(Error::NoAnnotation)
+    }
+
+    /// Builds PodInfo from an OCI config.json file
+    pub fn from_oci_config<P: AsRef<Path>>(p: P) -> Result<Self, Error> {
+        let spec: OciSpec = serde_json::from_reader(io::BufReader::new(fs::File::open(p)?))?;
+        Self::from_annotations(spec.annotations)
+    }
+}
+
+struct CacheEntry {
+    pod: Option<Arc<PodInfo>>,
+    time: Instant,
+}
+
+/// Resolves container ids to Kubernetes metadata by reading the
+/// state container runtimes keep on the local filesystem.
+pub struct Resolver {
+    state_dirs: Vec<PathBuf>,
+    // misses are cached only for miss_ttl
+    miss_ttl: Duration,
+    cache: LruHashMap<String, CacheEntry>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::with_state_dirs(Self::default_state_dirs())
+    }
+}
+
+impl Resolver {
+    pub fn default_state_dirs() -> Vec<PathBuf> {
+        let mut v = vec![PathBuf::from(CONTAINERD_STATE_DIR)];
+        v.extend(CRIO_STATE_DIRS.iter().map(PathBuf::from));
+        v
+    }
+
+    pub fn with_state_dirs(state_dirs: Vec<PathBuf>) -> Self {
+        Self {
+            state_dirs,
+            miss_ttl: DEFAULT_MISS_TTL,
+            cache: LruHashMap::with_max_entries(DEFAULT_MAX_ENTRIES),
+        }
+    }
+
+    #[inline]
+    fn config_paths<'a>(&'a self, id: &'a str) -> impl Iterator<Item = PathBuf> + 'a {
+        self.state_dirs.iter().flat_map(move |d| {
+            [
+                // containerd bundle
+                d.join(id).join("config.json"),
+                // CRI-O container userdata
+                d.join(id).join("userdata").join("config.json"),
+            ]
+        })
+    }
+
+    fn load(&self, id: &str) -> Option<Arc<PodInfo>> {
+        // prevents path traversal through a crafted id
+        if id.is_empty() || id.contains(['/', '.']) {
+            return None;
+        }
+
+        self.config_paths(id)
+            .filter(|p| p.is_file())
+            .find_map(|p| PodInfo::from_oci_config(p).ok())
+            .map(Arc::new)
+    }
+
+    /// Returns PodInfo for a container id, lookup result is cached
+    /// and failed lookups are retried once miss_ttl has expired
+    pub fn get(&mut self, id: &str) -> Option<Arc<PodInfo>> {
+        let id = id.to_string();
+        if let Some(e) = self.cache.get(&id) {
+            if e.pod.is_some() || e.time.elapsed() < self.miss_ttl {
+                return e.pod.clone();
+            }
+        }
+
+        let pod = self.load(&id);
+        self.cache.insert(
+            id,
+            CacheEntry {
+                pod: pod.clone(),
+                time: Instant::now(),
+            },
+        );
+        pod
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ID: &str = "3d1b2c8a0a9c5e1f0b4f0e8c6a2d7e9b1c3f5a7d9e0b2c4d6f8a1b3c5d7e9f0a";
+
+    fn tmp_dir(name: &str) -> PathBuf {
+        let d = std::env::temp_dir().join(format!("kunai-k8s-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&d);
+        fs::create_dir_all(&d).unwrap();
+        d
+    }
+
+    #[test]
+    fn test_containerd_state() {
+        let root = tmp_dir("containerd");
+        let bundle = root.join(ID);
+        fs::create_dir_all(&bundle).unwrap();
+        fs::write(
+            bundle.join("config.json"),
+            r#"{"ociVersion":"1.1.0","annotations":{
+                "io.kubernetes.cri.container-type":"container",
+                "io.kubernetes.cri.container-name":"nginx",
+                "io.kubernetes.cri.image-name":"docker.io/library/nginx:latest",
+                "io.kubernetes.cri.sandbox-name":"web-7d9c6b8f9-x2x4k",
+                "io.kubernetes.cri.sandbox-namespace":"default",
+                "io.kubernetes.cri.sandbox-uid":"6c1a8b2e-2f0d-4b7a-9c1e-0f2b3a4c5d6e"}}"#,
+        )
+        .unwrap();
+
+        let mut r = Resolver::with_state_dirs(vec![root.clone()]);
+        let pi = r.get(ID).unwrap();
+        assert_eq!(pi.name, "web-7d9c6b8f9-x2x4k");
+        assert_eq!(pi.namespace, "default");
+        assert_eq!(pi.container_name.as_deref(), Some("nginx"));
+        assert_eq!(pi.image.as_deref(), Some("docker.io/library/nginx:latest"));
+        assert!(pi.labels.is_empty());
+
+        // info must be served from cache once state is gone
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(r.get(ID), Some(pi));
+    }
+
+    #[test]
+    fn test_miss_ttl() {
+        let root = tmp_dir("miss");
+        let bundle = root.join(ID);
+        let mut r = Resolver::with_state_dirs(vec![root.clone()]);
+
+        // state not written yet
+        assert_eq!(r.get(ID), None);
+
+        fs::create_dir_all(&bundle).unwrap();
+        fs::write(
+            bundle.join("config.json"),
+            r#"{"annotations":{
+                "io.kubernetes.cri.sandbox-name":"web",
+                "io.kubernetes.cri.sandbox-namespace":"default"}}"#,
+        )
+        .unwrap();
+
+        // miss is still cached
+        assert_eq!(r.get(ID), None);
+
+        r.miss_ttl = Duration::ZERO;
+        assert_eq!(r.get(ID).map(|pi| pi.name.clone()).as_deref(), Some("web"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_crio_state() {
+        let root = tmp_dir("crio");
+        let userdata = root.join(ID).join("userdata");
+        fs::create_dir_all(&userdata).unwrap();
+        fs::write(
+            userdata.join("config.json"),
+            r#"{"ociVersion":"1.0.2","annotations":{
+                "io.kubernetes.pod.name":"web-7d9c6b8f9-x2x4k",
+                "io.kubernetes.pod.namespace":"prod",
+                "io.kubernetes.pod.uid":"6c1a8b2e-2f0d-4b7a-9c1e-0f2b3a4c5d6e",
+                "io.kubernetes.container.name":"nginx",
+                "io.kubernetes.cri-o.ImageName":"docker.io/library/nginx:latest",
+                "io.kubernetes.cri-o.Labels":"{\"app\":\"web\",\"io.kubernetes.pod.name\":\"web-7d9c6b8f9-x2x4k\"}"}}"#,
+        )
+        .unwrap();
+
+        let mut r = Resolver::with_state_dirs(vec![root.clone()]);
+        let pi = r.get(ID).unwrap();
+        assert_eq!(pi.namespace, "prod");
+        assert_eq!(pi.labels.get("app").map(|s| s.as_str()), Some("web"));
+        assert_eq!(pi.labels.get("io.kubernetes.pod.name"), None);
+
+        // unknown container
+        assert_eq!(r.get(&ID.replace('3', "4")), None);
+        // path traversal
+        assert_eq!(r.get("../../etc"), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     borrow::Cow,
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     net::{IpAddr, Ipv4Addr},
     path::PathBuf,
 };
@@ -13,8 +13,13 @@ use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{
     cache::Hashes,
-    containers::Container,
-    info::{ContainerInfo, StdEventInfo},
+    containers::{
+        k8s::{Labels, PodInfo},
+        Container,
+    },
+    info::{AccountNames, ContainerInfo, StdEventInfo},
+    sessions::Session,
+    util::kmod::ModuleInfo,
 };
 
 #[derive(Debug, Default, Serialize, Deserialize, FieldGetter)]
@@ -28,12 +33,37 @@ impl From<PathBuf> for File {
     }
 }
 
+#[derive(FieldGetter, Serialize, Deserialize)]
+pub struct PodSection {
+    pub name: String,
+    pub namespace: String,
+    pub uid: Option<String>,
+    pub container: Option<String>,
+    pub image: Option<String>,
+    pub labels: Labels,
+}
+
+impl From<&PodInfo> for PodSection {
+    fn from(value: &PodInfo) -> Self {
+        Self {
+            name: value.name.clone(),
+            namespace: value.namespace.clone(),
+            uid: value.uid.clone(),
+            container: value.container_name.clone(),
+            image: value.image.clone(),
+            labels: value.labels.clone(),
+        }
+    }
+}
+
 #[derive(FieldGetter, Serialize, Deserialize)]
 #[getter(use_serde_rename)]
 pub struct ContainerSection {
     pub name: String,
     #[serde(rename = "type")]
     pub ty: Option<Container>,
+    pub id: Option<String>,
+    pub pod: Option<PodSection>,
 }
 
 impl From<ContainerInfo> for ContainerSection {
@@ -41,6 +71,35 @@ impl From<ContainerInfo> for ContainerSection {
         Self {
             name: value.name,
             ty: value.ty,
+            id: value.id,
+            pod: value.pod.as_deref().map(PodSection::from),
+        }
+    }
+}
+
+#[derive(FieldGetter, Serialize, Deserialize)]
+pub struct SessionSection {
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loginuid: Option<u32>,
+    pub origin: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+}
+
+impl From<Session> for SessionSection {
+    fn from(value: Session) -> Self {
+        Self {
+            id: value.id,
+            loginuid: value.loginuid,
+            origin: value.origin.as_str().into(),
+            tty: value.tty,
+            ip: value.peer.map(|p| p.ip),
+            port: value.peer.map(|p| p.port),
         }
     }
 }
@@ -93,11 +152,31 @@ pub struct TaskSection {
     guuid: String,
     uid: u32,
     gid: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    euid: u32,
+    suid: u32,
+    fsuid: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    loginuid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sessionid: Option<u32>,
     namespaces: Option<NamespaceInfo>,
     #[serde(with = "u32_hex")]
     flags: u32,
 }
 
+impl TaskSection {
+    #[inline]
+    fn with_names(mut self, names: AccountNames) -> Self {
+        self.user = names.user;
+        self.group = names.group;
+        self
+    }
+}
+
 impl From<kunai_common::bpf_events::TaskInfo> for TaskSection {
     fn from(value: kunai_common::bpf_events::TaskInfo) -> Self {
         Self {
@@ -107,12 +186,20 @@ impl From<kunai_common::bpf_events::TaskInfo> for TaskSection {
             guuid: value.tg_uuid.into_uuid().hyphenated().to_string(),
             uid: value.uid,
             gid: value.gid,
+            user: None,
+            group: None,
+            euid: value.euid,
+            suid: value.suid,
+            fsuid: value.fsuid,
+            loginuid: value.loginuid(),
+            sessionid: value.sessionid(),
             namespaces: value.namespaces.map(|ns| ns.into()),
             flags: value.flags,
         }
     }
 }
 
+#[derive(Debug)]
 pub struct UtcDateTime(DateTime<Utc>);
 
 impl From<DateTime<Utc>> for UtcDateTime {
@@ -135,6 +222,15 @@ where
     serializer.serialize_str(&ts.0.to_rfc3339_opts(SecondsFormat::Nanos, true))
 }
 
+impl Serialize for UtcDateTime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_utc_ts(self, serializer)
+    }
+}
+
 impl<'de> Deserialize<'de> for UtcDateTime {
     fn deserialize<D>(deserializer: D) -> Result<UtcDateTime, D::Error>
     where
@@ -179,6 +275,8 @@ pub struct EventInfo {
     pub event: EventSection,
     pub task: TaskSection,
     pub parent_task: TaskSection,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<SessionSection>,
     #[serde(serialize_with = "serialize_utc_ts")]
     pub utc_time: UtcDateTime,
 }
@@ -198,8 +296,10 @@ impl From<StdEventInfo> for EventInfo {
                 uuid: value.info.uuid.into_uuid().hyphenated().to_string(),
                 batch: value.info.batch,
             },
-            task: value.info.process.into(),
-            parent_task: value.info.parent.into(),
+            task: TaskSection::from(value.info.process).with_names(value.additional.task_names),
+            parent_task: TaskSection::from(value.info.parent)
+                .with_names(value.additional.parent_names),
+            session: value.additional.session.map(SessionSection::from),
             utc_time: value.utc_timestamp.into(),
         }
     }
@@ -406,13 +506,160 @@ macro_rules! def_user_data {
             };
         }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvValue {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// Environment variables captured at execution, they
+/// can be accessed by name from rules
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Environment(BTreeMap<String, EnvValue>);
+
+impl Environment {
+    /// Builds an Environment out of a raw environment block (NUL separated
+    /// NAME=value strings) keeping only the variables listed in names. If the
+    /// block is truncated, the last variable is marked as such. Variables past
+    /// the truncation cannot be known, this is reported by
+    /// [ExecveData::env_truncated].
+    pub fn from_block(block: &[u8], truncated: bool, names: &HashSet<String>) -> Self {
+        let mut env = BTreeMap::new();
+        let mut vars = block.split(|&b| b == b'\0').peekable();
+
+        while let Some(var) = vars.next() {
+            // last variable is incomplete if block is truncated in the middle of it
+            let last = vars.peek().map(|v| v.is_empty()).unwrap_or(true);
+            let Some(i) = var.iter().position(|&b| b == b'=') else {
+                continue;
+            };
+
+            let name = String::from_utf8_lossy(&var[..i]);
+            if !names.contains(name.as_ref()) {
+                continue;
+            }
+
+            env.insert(
+                name.into(),
+                EnvValue {
+                    value: String::from_utf8_lossy(&var[i + 1..]).into(),
+                    truncated: truncated && last && !block.ends_with(&[0]),
+                },
+            );
+        }
+
+        Self(env)
+    }
+
+    #[inline]
+    pub fn get(&self, k: &str) -> Option<&EnvValue> {
+        self.0.get(k)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FieldGetter for Environment {
+    fn get_from_iter(
+        &self,
+        mut i: core::slice::Iter<'_, std::string::String>,
+    ) -> Option<gene::FieldValue> {
+        let k = i.next()?;
+        if i.len() > 0 {
+            return None;
+        }
+        self.0.get(k).map(|v| v.value.as_str().into())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FieldGetter)]
+#[getter(use_serde_rename)]
+pub struct SocketPeer {
+    pub domain: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// process at the other end of AF_UNIX sockets when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_exe: Option<PathBuf>,
+}
+
+/// Information about what a standard file descriptor points to
+#[derive(Debug, Serialize, Deserialize, FieldGetter)]
+#[getter(use_serde_rename)]
+pub struct FdSection {
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socket: Option<SocketPeer>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FieldGetter)]
+pub struct StdioSection {
+    pub stdin: FdSection,
+    pub stdout: FdSection,
+    pub stderr: FdSection,
+}
+
+/// Describes a file with no (or no more) link on a filesystem
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, FieldGetter)]
+pub struct FilelessSection {
+    pub memfd: bool,
+    pub tmpfile: bool,
+    pub deleted: bool,
+}
+
+impl FilelessSection {
+    /// Builds a section out of eBPF FILELESS_* flags,
+    /// None is returned if the file is not fileless
+    pub fn from_flags(flags: u8) -> Option<Self> {
+        use kunai_common::bpf_events::{FILELESS_DELETED, FILELESS_MEMFD, FILELESS_TMPFILE};
+
+        if flags == 0 {
+            return None;
+        }
+
+        Some(Self {
+            memfd: flags & FILELESS_MEMFD == FILELESS_MEMFD,
+            tmpfile: flags & FILELESS_TMPFILE == FILELESS_TMPFILE,
+            deleted: flags & FILELESS_DELETED == FILELESS_DELETED,
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, FieldGetter)]
 pub struct ExecveData {
     pub ancestors: String,
     pub parent_exe: String,
     pub command_line: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub command_line_truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<Environment>,
+    /// environment did not fit in the event, variables
+    /// located after the limit are missing from env
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub env_truncated: bool,
+    pub cwd: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tty: Option<String>,
+    pub stdio: StdioSection,
     pub exe: Hashes,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub fileless: Option<FilelessSection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub interpreter: Option<Hashes>,
 }
 
@@ -459,6 +706,25 @@ def_user_data!(
 
 impl_std_iocs!(PrctlData);
 
+def_user_data!(
+    pub struct ExecveFailedData {
+        pub attempted_exe: Hashes,
+        pub attempted_command_line: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        pub attempted_command_line_truncated: bool,
+        pub errno: String,
+        pub rc: i32,
+    }
+);
+
+impl IocGetter for ExecveFailedData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        let mut v = vec![self.exe.file.to_string_lossy()];
+        v.extend(self.attempted_exe.iocs());
+        v
+    }
+}
+
 #[derive(Debug, FieldGetter, Serialize, Deserialize)]
 pub struct TargetTask {
     pub command_line: String,
@@ -475,26 +741,121 @@ def_user_data!(
 
 impl_std_iocs!(KillData);
 
+def_user_data!(
+    pub struct ProcessAccessData {
+        /// ptrace, process_vm_writev or proc_mem_write
+        pub operation: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub ptrace_request: Option<String>,
+        pub target: TargetTask,
+        pub success: bool,
+    }
+);
+
+impl_std_iocs!(ProcessAccessData);
+
 def_user_data!(
     pub struct MmapExecData {
-        pub mapped: Hashes,
+        /// mapped file, None for anonymous mappings
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub mapped: Option<Hashes>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub fileless: Option<FilelessSection>,
+        pub anonymous: bool,
+        pub len: u64,
+        #[serde(with = "u64_hex")]
+        pub prot: u64,
+        #[serde(with = "u64_hex")]
+        pub flags: u64,
     }
 );
 
 impl IocGetter for MmapExecData {
     fn iocs(&mut self) -> Vec<Cow<'_, str>> {
         let mut v = vec![self.exe.file.to_string_lossy()];
-        v.extend(self.mapped.iocs());
+        if let Some(mapped) = self.mapped.as_ref() {
+            v.extend(mapped.iocs());
+        }
         v
     }
 }
 
+def_user_data!(
+    pub struct MemfdCreateData {
+        pub name: String,
+        #[serde(with = "u32_hex")]
+        pub flags: u32,
+        // file descriptor or negative errno
+        pub fd: i64,
+        pub success: bool,
+    }
+);
+
+impl_std_iocs!(MemfdCreateData);
+
+/// Value of via fields for operations done through io_uring
+pub const VIA_IO_URING: &str = "io_uring";
+
+def_user_data!(
+    pub struct IoUringSetupData {
+        pub entries: u32,
+        pub sq_entries: u32,
+        pub cq_entries: u32,
+        #[serde(with = "u32_hex")]
+        pub flags: u32,
+        /// submission queue polled by a kernel thread
+        pub sqpoll: bool,
+        // file descriptor or negative errno
+        pub fd: i64,
+        pub success: bool,
+    }
+);
+
+impl_std_iocs!(IoUringSetupData);
+
+def_user_data!(
+    pub struct IoUringOpData {
+        pub operation: String,
+        pub opcode: u8,
+        /// file the operation is done on or path opened
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub path: Option<PathBuf>,
+    }
+);
+
+impl IocGetter for IoUringOpData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        let mut v = vec![self.exe.file.to_string_lossy()];
+        if let Some(p) = self.path.as_ref() {
+            v.push(p.to_string_lossy());
+        }
+        v
+    }
+}
+
+/// Memory area an operation applies to
+#[derive(Debug, Default, Serialize, Deserialize, FieldGetter)]
+pub struct VmaSection {
+    /// backing file path or one of [anon], [heap], [stack]
+    pub backing: String,
+    #[serde(with = "u64_hex")]
+    pub start: u64,
+    pub size: u64,
+    /// protections before the operation
+    #[serde(with = "u64_hex")]
+    pub prot: u64,
+}
+
 def_user_data!(
     pub struct MprotectData {
         #[serde(with = "u64_hex")]
         pub addr: u64,
+        pub len: u64,
         #[serde(with = "u64_hex")]
         pub prot: u64,
+        /// missing if mprotect failed before memory area was known
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub vma: Option<VmaSection>,
     }
 );
 
@@ -534,16 +895,48 @@ impl IocGetter for NetworkInfo {
     }
 }
 
+#[derive(Debug, Default, Serialize, Deserialize, FieldGetter)]
+#[getter(use_serde_rename)]
+pub struct UnixSocketInfo {
+    /// socket path, abstract socket names are prefixed with '@'
+    pub path: String,
+    #[serde(rename = "abstract")]
+    pub is_abstract: bool,
+    /// process listening on the socket when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_exe: Option<PathBuf>,
+}
+
 def_user_data!(
     pub struct ConnectData {
-        pub dst: NetworkInfo,
+        /// destination of AF_INET and AF_INET6 connections
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub dst: Option<NetworkInfo>,
+        /// destination of AF_UNIX connections
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub unix: Option<UnixSocketInfo>,
         pub connected: bool,
+        /// set when connection does not come from connect syscall
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub via: Option<String>,
     }
 );
 
 impl IocGetter for ConnectData {
     fn iocs(&mut self) -> Vec<Cow<'_, str>> {
-        self.dst.iocs()
+        let mut v = vec![];
+
+        if let Some(dst) = self.dst.as_mut() {
+            v.extend(dst.iocs());
+        }
+
+        if let Some(unix) = self.unix.as_ref() {
+            v.push(unix.path.as_str().into());
+        }
+
+        v
     }
 }
 
@@ -634,18 +1027,69 @@ pub struct InitModuleData {
     pub syscall: String,
     pub module_name: String,
     pub args: String,
+    /// module file given to finit_module is compressed
+    pub compressed: bool,
+    /// hashes of the module file (finit_module) or of
+    /// the module image in memory (init_module)
+    pub image: Hashes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modinfo: Option<ModuleInfo>,
     pub loaded: bool,
 }
 
 impl IocGetter for InitModuleData {
     fn iocs(&mut self) -> Vec<Cow<'_, str>> {
-        vec![self.exe.file.to_string_lossy()]
+        let mut v = vec![self.exe.file.to_string_lossy()];
+        v.extend(self.image.iocs());
+        v
+    }
+}
+
+def_user_data!(
+    pub struct DeleteModuleData {
+        pub module_name: String,
+        #[serde(with = "u32_hex")]
+        pub flags: u32,
+        pub success: bool,
+    }
+);
+
+impl_std_iocs!(DeleteModuleData);
+
+def_user_data!(
+    pub struct KernelTamperData {
+        pub kind: String,
+        /// file written or kernel image loaded
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub path: Option<PathBuf>,
+        /// probed symbol or binary, data written
+        /// or kexec command line
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub data: Option<String>,
+        #[serde(with = "u64_hex")]
+        pub addr: u64,
+        #[serde(with = "u64_hex")]
+        pub flags: u64,
+        pub retprobe: bool,
+    }
+);
+
+impl IocGetter for KernelTamperData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        let mut v = vec![self.exe.file.to_string_lossy()];
+        if let Some(p) = self.path.as_ref() {
+            v.push(p.to_string_lossy());
+        }
+        v
     }
 }
 
 def_user_data!(
     pub struct RWData {
         pub path: PathBuf,
+        /// set when access does not come from read/write syscalls
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub via: Option<String>,
     }
 );
 
@@ -685,12 +1129,257 @@ impl IocGetter for FileRenameData {
     }
 }
 
+def_user_data!(
+    pub struct FileChmodData {
+        pub path: PathBuf,
+        /// modes are octal strings of permission bits
+        pub old_mode: String,
+        pub new_mode: String,
+        /// setuid or setgid bit is added by the change
+        pub setid_added: bool,
+        /// any execute bit is added by the change
+        pub exec_added: bool,
+        pub success: bool,
+    }
+);
+
+impl FileChmodData {
+    const PERM_MASK: u16 = 0o7777;
+    const SETID_MASK: u16 = 0o6000;
+    const EXEC_MASK: u16 = 0o111;
+
+    pub fn set_modes(&mut self, old: u16, new: u16) {
+        let (old, new) = (old & Self::PERM_MASK, new & Self::PERM_MASK);
+        let added = new & !old;
+        self.old_mode = format!("{old:04o}");
+        self.new_mode = format!("{new:04o}");
+        self.setid_added = added & Self::SETID_MASK != 0;
+        self.exec_added = added & Self::EXEC_MASK != 0;
+    }
+}
+
+impl IocGetter for FileChmodData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        vec![self.exe.file.to_string_lossy(), self.path.to_string_lossy()]
+    }
+}
+
+def_user_data!(
+    pub struct FileChownData {
+        pub path: PathBuf,
+        pub old_uid: u32,
+        pub old_gid: u32,
+        /// None if owner is unchanged
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub new_uid: Option<u32>,
+        /// None if group is unchanged
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub new_gid: Option<u32>,
+        pub success: bool,
+    }
+);
+
+impl IocGetter for FileChownData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        vec![self.exe.file.to_string_lossy(), self.path.to_string_lossy()]
+    }
+}
+
+def_user_data!(
+    pub struct FileXattrData {
+        pub path: PathBuf,
+        pub operation: String,
+        pub name: String,
+        /// size of the value set
+        pub size: u64,
+        pub success: bool,
+    }
+);
+
+impl IocGetter for FileXattrData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        vec![self.exe.file.to_string_lossy(), self.path.to_string_lossy()]
+    }
+}
+
+def_user_data!(
+    pub struct FileTimesData {
+        pub path: PathBuf,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub old_atime: Option<UtcDateTime>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub old_mtime: Option<UtcDateTime>,
+        /// None if access time is not changed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub new_atime: Option<UtcDateTime>,
+        /// None if modification time is not changed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub new_mtime: Option<UtcDateTime>,
+        pub success: bool,
+    }
+);
+
+impl IocGetter for FileTimesData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        vec![self.exe.file.to_string_lossy(), self.path.to_string_lossy()]
+    }
+}
+
+def_user_data!(
+    pub struct FileLinkData {
+        pub target: PathBuf,
+        pub link: PathBuf,
+        /// target is not under the directory of the link
+        pub outside_dir: bool,
+        /// target matches the configured config_files patterns
+        pub sensitive_target: bool,
+    }
+);
+
+impl IocGetter for FileLinkData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        vec![
+            self.exe.file.to_string_lossy(),
+            self.target.to_string_lossy(),
+            self.link.to_string_lossy(),
+        ]
+    }
+}
+
+def_user_data!(
+    pub struct FileSymlinkData {
+        /// target as given to symlink
+        pub target: PathBuf,
+        /// target resolved from the directory of the link
+        pub resolved_target: PathBuf,
+        pub link: PathBuf,
+        /// target is not under the directory of the link
+        pub outside_dir: bool,
+        /// target matches the configured config_files patterns
+        pub sensitive_target: bool,
+    }
+);
+
+impl IocGetter for FileSymlinkData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        vec![
+            self.exe.file.to_string_lossy(),
+            self.resolved_target.to_string_lossy(),
+            self.link.to_string_lossy(),
+        ]
+    }
+}
+
+def_user_data!(
+    pub struct FileIntegrityData {
+        pub path: PathBuf,
+        /// created, modified or deleted
+        pub operation: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub old: Option<Hashes>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub new: Option<Hashes>,
+    }
+);
+
+impl IocGetter for FileIntegrityData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        let mut v = vec![self.exe.file.to_string_lossy(), self.path.to_string_lossy()];
+        for h in [self.old.as_ref(), self.new.as_ref()].into_iter().flatten() {
+            v.extend(h.iocs());
+        }
+        v
+    }
+}
+
+def_user_data!(
+    pub struct AccountData {
+        pub file: PathBuf,
+        pub user: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub uid: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub gid: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub home: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub shell: Option<String>,
+        pub locked: bool,
+        /// account fields modified (uid, gid, home, shell, password, locked)
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        #[getter(skip)]
+        pub changes: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub old_uid: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub old_gid: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub old_home: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub old_shell: Option<String>,
+    }
+);
+
+impl IocGetter for AccountData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        vec![self.exe.file.to_string_lossy(), self.file.to_string_lossy()]
+    }
+}
+
+def_user_data!(
+    pub struct SudoersData {
+        pub file: PathBuf,
+        /// normalized rules added
+        #[getter(skip)]
+        pub added: Vec<String>,
+        /// normalized rules removed
+        #[getter(skip)]
+        pub removed: Vec<String>,
+    }
+);
+
+impl IocGetter for SudoersData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        vec![self.exe.file.to_string_lossy(), self.file.to_string_lossy()]
+    }
+}
+
+def_user_data!(
+    pub struct SshKeyData {
+        pub file: PathBuf,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub user: Option<String>,
+        pub key_type: String,
+        pub fingerprint: String,
+        pub comment: String,
+        pub options: String,
+    }
+);
+
+impl IocGetter for SshKeyData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        vec![
+            self.exe.file.to_string_lossy(),
+            self.file.to_string_lossy(),
+            self.fingerprint.as_str().into(),
+        ]
+    }
+}
+
 #[derive(Debug, FieldGetter, Serialize, Deserialize)]
 pub struct BpfProgTypeInfo {
     pub id: u32,
     pub name: String,
 }
 
+#[derive(Debug, FieldGetter, Serialize, Deserialize)]
+pub struct BpfMapInfo {
+    pub id: u32,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub name: String,
+}
+
 #[derive(Debug, FieldGetter, Serialize, Deserialize)]
 pub struct BpfProgInfo {
     pub md5: String,
@@ -698,6 +1387,24 @@ pub struct BpfProgInfo {
     pub sha256: String,
     pub sha512: String,
     pub size: usize,
+    // analysis fields are missing from older logs
+    #[serde(default)]
+    pub insn_count: u32,
+    /// helper functions called by the program
+    #[serde(default)]
+    #[getter(skip)]
+    pub helpers: Vec<String>,
+    /// helpers allowing to alter the system
+    #[serde(default)]
+    #[getter(skip)]
+    pub dangerous_helpers: Vec<String>,
+    /// maps referenced by the program
+    #[serde(default)]
+    #[getter(skip)]
+    pub maps: Vec<BpfMapInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[getter(skip)]
+    pub disassembly: Option<Vec<String>>,
 }
 
 def_user_data!(
@@ -727,6 +1434,40 @@ impl IocGetter for BpfProgLoadData {
 }
 
 #[derive(Debug, FieldGetter, Serialize, Deserialize)]
+pub struct BpfAttachTypeInfo {
+    pub id: u32,
+    pub name: String,
+}
+
+def_user_data!(
+    pub struct BpfProgAttachData {
+        pub id: u32,
+        pub prog_type: BpfProgTypeInfo,
+        pub name: String,
+        pub attached_func: String,
+        pub attach_type: BpfAttachTypeInfo,
+        #[serde(with = "u32_hex")]
+        pub flags: u32,
+        /// file descriptor or interface index the program is attached to
+        pub target_fd: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub target: Option<PathBuf>,
+        pub success: bool,
+    }
+);
+
+impl IocGetter for BpfProgAttachData {
+    fn iocs(&mut self) -> Vec<Cow<'_, str>> {
+        let mut v = vec![self.exe.file.to_string_lossy()];
+        if let Some(p) = self.target.as_ref() {
+            v.push(p.to_string_lossy());
+        }
+        v
+    }
+}
+
+#[derive(Debug, FieldGetter, Serialize, Deserialize)]
+#[getter(use_serde_rename)]
 pub struct SocketInfo {
     pub domain: String,
     #[serde(rename = "type")]
@@ -763,6 +1504,17 @@ impl IocGetter for BpfSocketFilterData {
     }
 }
 
+def_user_data!(
+    pub struct SocketCreateData {
+        pub socket: SocketInfo,
+        pub protocol: u16,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub protocol_name: Option<String>,
+    }
+);
+
+impl_std_iocs!(SocketCreateData);
+
 def_user_data!(
     pub struct ExitData {
         pub error_code: u64,
@@ -770,3 +1522,48 @@ def_user_data!(
 );
 
 impl_std_iocs!(ExitData);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_environment_from_block() {
+        let names: HashSet<String> = ["PATH", "LD_PRELOAD", "http_proxy"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let block = b"HOME=/root\0PATH=/usr/bin:/bin\0LD_PRELOAD=/tmp/x.so\0";
+        let env = Environment::from_block(block, false, &names);
+        assert_eq!(env.get("HOME"), None);
+        assert_eq!(env.get("PATH").unwrap().value, "/usr/bin:/bin");
+        assert!(!env.get("LD_PRELOAD").unwrap().truncated);
+
+        // block cut in the middle of the last variable
+        let block = b"PATH=/usr/bin\0http_proxy=http://prox";
+        let env = Environment::from_block(block, true, &names);
+        assert!(!env.get("PATH").unwrap().truncated);
+        let proxy = env.get("http_proxy").unwrap();
+        assert_eq!(proxy.value, "http://prox");
+        assert!(proxy.truncated);
+
+        // block truncated right after a variable
+        let block = b"PATH=/usr/bin\0";
+        let env = Environment::from_block(block, true, &names);
+        assert!(!env.get("PATH").unwrap().truncated);
+    }
+
+    #[test]
+    fn test_fileless_from_flags() {
+        use kunai_common::bpf_events::{FILELESS_DELETED, FILELESS_MEMFD};
+
+        assert!(FilelessSection::from_flags(0).is_none());
+
+        let f = FilelessSection::from_flags(FILELESS_MEMFD).unwrap();
+        assert!(f.memfd && !f.tmpfile && !f.deleted);
+
+        let f = FilelessSection::from_flags(FILELESS_DELETED).unwrap();
+        assert!(!f.memfd && !f.tmpfile && f.deleted);
+    }
+}
@@ -0,0 +1,499 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::FileType,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use kunai_common::{
+    config::PathPattern,
+    path::{self as kpath, Mode},
+};
+use thiserror::Error;
+
+use crate::{
+    cache::{self, Cache, HashStatus, Hashes},
+    util::namespaces::Namespace,
+};
+
+// maximum depth of directories walked to build the baseline
+const MAX_WALK_DEPTH: usize = 16;
+// maximum number of files in the baseline of a namespace
+const MAX_BASELINE_FILES: usize = 65536;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid path pattern {0}")]
+    InvalidPattern(String),
+}
+
+/// Kind of change detected on a monitored file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Modified => "modified",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+// patterns without `*` and not ending with `/` designate a single file
+// and must not match by prefix (i.e. /etc/passwd must not match /etc/passwd-)
+enum Pattern {
+    Exact(String),
+    Match(PathPattern),
+}
+
+impl Pattern {
+    fn parse(s: &str) -> Option<Self> {
+        let p = PathPattern::new(s)?;
+        if !s.contains('*') && !s.ends_with('/') {
+            return Some(Self::Exact(s.into()));
+        }
+        Some(Self::Match(p))
+    }
+
+    #[inline]
+    fn matches(&self, path: &kpath::Path) -> bool {
+        match self {
+            Self::Exact(s) => path.as_slice() == s.as_bytes(),
+            Self::Match(p) => p.matches(path),
+        }
+    }
+}
+
+/// Change of a monitored file, T is the context given
+/// when the change has been scheduled (i.e. the writer).
+#[derive(Debug)]
+pub struct Change<T> {
+    pub path: PathBuf,
+    pub operation: Operation,
+    pub old: Option<Hashes>,
+    pub new: Option<Hashes>,
+    pub context: T,
+}
+
+/// Access to the files of mount namespaces
+pub trait FileSource {
+    /// Lists the entries of a directory
+    fn read_dir(&mut self, ns: Namespace, dir: &Path) -> Vec<(PathBuf, FileType)>;
+
+    /// Returns the hashes of a regular file, with a pending status if they
+    /// are still being computed, or None if there is no such regular file.
+    fn hash(&mut self, ns: Namespace, path: &Path) -> Option<Hashes>;
+}
+
+// files are hashed by the hashing workers of the cache if any
+impl FileSource for Cache {
+    fn read_dir(&mut self, ns: Namespace, dir: &Path) -> Vec<(PathBuf, FileType)> {
+        self.read_dir_in_ns(ns, dir).unwrap_or_default()
+    }
+
+    fn hash(&mut self, ns: Namespace, path: &Path) -> Option<Hashes> {
+        match self.prefetch_path_in_ns(ns, path) {
+            Ok(h) => Some(h),
+            Err(cache::Error::FileNotFound | cache::Error::NotRegularFile) => None,
+            Err(e) => Some(Hashes {
+                file: path.to_path_buf(),
+                error: Some(format!("{e}")),
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+/// File Integrity Monitoring. It keeps a baseline of the hashes of
+/// monitored files for every mount namespace and reports changes when
+/// files are re-hashed. Files are re-hashed after a settle time following
+/// the last modification so that hashes are not computed on partially
+/// written files. Hashing is delegated to a [`FileSource`] and changes
+/// are reported once hashes are complete.
+pub struct Fim<T> {
+    raw_patterns: Vec<String>,
+    patterns: Vec<Pattern>,
+    settle: Duration,
+    baselines: HashMap<Namespace, HashMap<PathBuf, Hashes>>,
+    // baseline files whose hashes are still being computed
+    incomplete: HashSet<(Namespace, PathBuf)>,
+    pending: HashMap<(Namespace, PathBuf), (Instant, T)>,
+}
+
+#[inline]
+fn same_content(a: &Hashes, b: &Hashes) -> bool {
+    a.size == b.size
+        && a.status == b.status
+        && a.md5 == b.md5
+        && a.sha1 == b.sha1
+        && a.sha256 == b.sha256
+        && a.sha512 == b.sha512
+}
+
+impl<T> Fim<T> {
+    /// Creates a new Fim monitoring files matching patterns. Patterns
+    /// follow the syntax of [`PathPattern`], except that patterns without
+    /// `*` and not ending with `/` must match a path exactly.
+    pub fn new<I, S>(patterns: I, settle: Duration) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let raw_patterns: Vec<String> = patterns.into_iter().map(|p| p.as_ref().into()).collect();
+        let patterns = raw_patterns
+            .iter()
+            .map(|p| Pattern::parse(p).ok_or(Error::InvalidPattern(p.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            raw_patterns,
+            patterns,
+            settle,
+            baselines: HashMap::new(),
+            incomplete: HashSet::new(),
+            pending: HashMap::new(),
+        })
+    }
+
+    /// Returns true if an eBPF path is monitored
+    #[inline]
+    pub fn is_monitored(&self, path: &kpath::Path) -> bool {
+        self.patterns.iter().any(|p| p.matches(path))
+    }
+
+    /// Returns true if a path is monitored
+    pub fn is_monitored_path<P: AsRef<Path>>(&self, path: P) -> bool {
+        let mut p = kpath::Path::default();
+        match p.copy_from_str(path.as_ref().to_string_lossy(), Mode::Append) {
+            Ok(_) => self.is_monitored(&p),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns true if the baseline of a namespace has been built
+    #[inline]
+    pub fn has_baseline(&self, ns: Namespace) -> bool {
+        self.baselines.contains_key(&ns)
+    }
+
+    /// Number of files in all the baselines
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.baselines.values().map(|b| b.len()).sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // directory from which files matching pattern must be searched
+    fn walk_root(pattern: &str) -> PathBuf {
+        let fixed = pattern.split('*').next().unwrap_or_default();
+        match fixed.rfind('/') {
+            Some(i) => PathBuf::from(&fixed[..=i]),
+            None => PathBuf::from("/"),
+        }
+    }
+
+    fn walk<S: FileSource>(
+        &self,
+        src: &mut S,
+        ns: Namespace,
+        dir: &Path,
+        depth: usize,
+        files: &mut HashSet<PathBuf>,
+    ) {
+        if depth > MAX_WALK_DEPTH {
+            return;
+        }
+
+        for (path, ft) in src.read_dir(ns, dir) {
+            if files.len() >= MAX_BASELINE_FILES {
+                return;
+            }
+
+            // we do not follow symlinks
+            if ft.is_dir() {
+                self.walk(src, ns, &path, depth + 1, files);
+            } else if ft.is_file() && self.is_monitored_path(&path) {
+                files.insert(path);
+            }
+        }
+    }
+
+    /// Builds the baseline of the files monitored in a mount namespace
+    /// and returns the number of files in it. Patterns ending with a `/`
+    /// or containing a `*` are searched by walking directories, the
+    /// other ones are expected to be files.
+    pub fn build_baseline<S: FileSource>(&mut self, src: &mut S, ns: Namespace) -> usize {
+        let mut files = HashSet::new();
+
+        for (raw, p) in self.raw_patterns.iter().zip(self.patterns.iter()) {
+            match p {
+                Pattern::Exact(path) => {
+                    files.insert(PathBuf::from(path));
+                }
+                Pattern::Match(_) => self.walk(src, ns, &Self::walk_root(raw), 0, &mut files),
+            }
+        }
+
+        let mut baseline = HashMap::new();
+        for path in files {
+            let Some(h) = src.hash(ns, &path) else {
+                continue;
+            };
+
+            if h.status == HashStatus::Pending {
+                self.incomplete.insert((ns, path.clone()));
+            }
+            baseline.insert(path, h);
+        }
+
+        let n = baseline.len();
+        self.baselines.insert(ns, baseline);
+        n
+    }
+
+    /// Drops everything known about a mount namespace
+    pub fn forget_ns(&mut self, ns: Namespace) {
+        self.baselines.remove(&ns);
+        self.incomplete.retain(|(n, _)| *n != ns);
+        self.pending.retain(|(n, _), _| *n != ns);
+    }
+
+    /// Schedules the re-hashing of a file modified by a writer. If the
+    /// file is already scheduled, deadline and context are updated.
+    pub fn schedule(&mut self, ns: Namespace, path: PathBuf, context: T) {
+        self.pending
+            .insert((ns, path), (Instant::now() + self.settle, context));
+    }
+
+    /// Reports the deletion of a file (i.e. unlinked or renamed). None
+    /// is returned if the file was not in the baseline.
+    pub fn delete<P: AsRef<Path>>(
+        &mut self,
+        ns: Namespace,
+        path: P,
+        context: T,
+    ) -> Option<Change<T>> {
+        let key = (ns, path.as_ref().to_path_buf());
+        self.pending.remove(&key);
+        self.incomplete.remove(&key);
+
+        let (_, path) = key;
+        let old = self.baselines.get_mut(&ns)?.remove(&path)?;
+        Some(Change {
+            path,
+            operation: Operation::Deleted,
+            old: Some(old),
+            new: None,
+            context,
+        })
+    }
+
+    // updates the baseline files whose hashes were not complete
+    fn complete_baseline<S: FileSource>(&mut self, src: &mut S) {
+        if self.incomplete.is_empty() {
+            return;
+        }
+
+        let incomplete: Vec<(Namespace, PathBuf)> = self.incomplete.iter().cloned().collect();
+        for (ns, path) in incomplete {
+            let new = src.hash(ns, &path);
+            if new
+                .as_ref()
+                .is_some_and(|h| h.status == HashStatus::Pending)
+            {
+                continue;
+            }
+
+            self.incomplete.remove(&(ns, path.clone()));
+            let Some(baseline) = self.baselines.get_mut(&ns) else {
+                continue;
+            };
+
+            match new {
+                Some(h) => baseline.insert(path, h),
+                None => baseline.remove(&path),
+            };
+        }
+    }
+
+    /// Re-hashes files whose settle time elapsed and returns the ones
+    /// whose content changed since the baseline. Files whose hashes
+    /// are not complete yet are checked again at the next poll.
+    pub fn poll<S: FileSource>(&mut self, src: &mut S) -> Vec<Change<T>> {
+        self.complete_baseline(src);
+
+        let now = Instant::now();
+
+        let due: Vec<(Namespace, PathBuf)> = self
+            .pending
+            .iter()
+            .filter(|(_, (deadline, _))| *deadline <= now)
+            .map(|(k, _)| k.clone())
+            .collect();
+
+        let mut changes = vec![];
+        for key in due {
+            // we cannot compare against an incomplete baseline
+            if self.incomplete.contains(&key) {
+                continue;
+            }
+
+            let (ns, path) = &key;
+
+            let Some(new) = src.hash(*ns, path) else {
+                // file has been removed before we could hash it
+                let Some((_, context)) = self.pending.remove(&key) else {
+                    continue;
+                };
+                changes.extend(self.delete(*ns, path, context));
+                continue;
+            };
+
+            if new.status == HashStatus::Pending {
+                continue;
+            }
+
+            let Some((_, context)) = self.pending.remove(&key) else {
+                continue;
+            };
+
+            let (ns, path) = key;
+            let old = self
+                .baselines
+                .entry(ns)
+                .or_default()
+                .insert(path.clone(), new.clone());
+
+            let operation = match old.as_ref() {
+                Some(o) if same_content(o, &new) => continue,
+                // we cannot tell if a too big file has changed
+                Some(o) if o.status == HashStatus::TooBig && o.size == new.size => continue,
+                Some(_) => Operation::Modified,
+                None => Operation::Created,
+            };
+
+            changes.push(Change {
+                path,
+                operation,
+                old,
+                new: Some(new),
+                context,
+            });
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cache::HashOptions;
+    use std::fs::{self, File};
+
+    // reads files of the current namespace, hashes are reported
+    // as pending the given number of times before being computed
+    #[derive(Default)]
+    struct Host {
+        pending: usize,
+    }
+
+    impl FileSource for Host {
+        fn read_dir(&mut self, _ns: Namespace, dir: &Path) -> Vec<(PathBuf, FileType)> {
+            fs::read_dir(dir)
+                .map(|rd| {
+                    rd.flatten()
+                        .filter_map(|e| e.file_type().ok().map(|ft| (e.path(), ft)))
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+
+        fn hash(&mut self, _ns: Namespace, path: &Path) -> Option<Hashes> {
+            if !path.is_file() {
+                return None;
+            }
+
+            if self.pending > 0 {
+                self.pending -= 1;
+                return Some(Hashes::pending(path));
+            }
+
+            let f = File::open(path).ok()?;
+            Some(Hashes::from_file(f, path, &HashOptions::default()))
+        }
+    }
+
+    #[test]
+    fn test_fim() {
+        let dir = std::env::temp_dir().join(format!("kunai-fim-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let monitored = dir.join("sub").join("file");
+        fs::write(&monitored, "original").unwrap();
+        fs::write(dir.join("other"), "other").unwrap();
+
+        let ns = Namespace::mnt(42);
+        let mut src = Host::default();
+
+        let patterns = [format!("{}/*/", dir.to_string_lossy())];
+        let mut fim: Fim<u32> = Fim::new(patterns, Duration::from_millis(0)).unwrap();
+
+        assert!(!fim.has_baseline(ns));
+        assert_eq!(fim.build_baseline(&mut src, ns), 1);
+        assert!(fim.has_baseline(ns));
+        assert!(fim.is_monitored_path(&monitored));
+        assert!(!fim.is_monitored_path(dir.join("other")));
+
+        // patterns designating a file match exactly
+        let exact: Fim<u32> = Fim::new(["/etc/passwd"], Duration::from_millis(0)).unwrap();
+        assert!(exact.is_monitored_path("/etc/passwd"));
+        assert!(!exact.is_monitored_path("/etc/passwd-"));
+        assert!(!exact.is_monitored_path("/etc/passwd/file"));
+
+        // no change
+        fim.schedule(ns, monitored.clone(), 1);
+        assert!(fim.poll(&mut src).is_empty());
+
+        // content modified
+        fs::write(&monitored, "modified").unwrap();
+        fim.schedule(ns, monitored.clone(), 2);
+        let changes = fim.poll(&mut src);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].operation, Operation::Modified);
+        assert_eq!(changes[0].context, 2);
+        assert_ne!(
+            changes[0].old.as_ref().unwrap().sha256,
+            changes[0].new.as_ref().unwrap().sha256
+        );
+
+        // new file whose hashes are not ready at first poll
+        let created = dir.join("sub").join("created");
+        fs::write(&created, "created").unwrap();
+        fim.schedule(ns, created.clone(), 3);
+        src.pending = 1;
+        assert!(fim.poll(&mut src).is_empty());
+        assert_eq!(fim.poll(&mut src)[0].operation, Operation::Created);
+
+        // deleted file
+        let change = fim.delete(ns, &monitored, 4).unwrap();
+        assert_eq!(change.operation, Operation::Deleted);
+        assert!(change.new.is_none());
+        assert!(fim.delete(ns, &monitored, 5).is_none());
+
+        // changes are tracked per namespace
+        fim.forget_ns(ns);
+        assert!(fim.is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use std::io;
+use std::{io, sync::Arc};
 
 use chrono::{DateTime, Utc};
 use kunai_common::{
@@ -7,7 +7,11 @@ use kunai_common::{
 };
 use thiserror::Error;
 
-use crate::{containers::Container, util::get_clk_tck};
+use crate::{
+    containers::{k8s::PodInfo, Container},
+    sessions::Session,
+    util::get_clk_tck,
+};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct TaskKey {
@@ -58,12 +62,25 @@ pub struct HostInfo {
 pub struct ContainerInfo {
     pub name: String,
     pub ty: Option<Container>,
+    pub id: Option<String>,
+    pub pod: Option<Arc<PodInfo>>,
+}
+
+/// User and group names of a task resolved
+/// in its mount namespace
+#[derive(Default, Debug, Clone)]
+pub struct AccountNames {
+    pub user: Option<String>,
+    pub group: Option<String>,
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct AdditionalInfo {
     pub host: HostInfo,
     pub container: Option<ContainerInfo>,
+    pub session: Option<Session>,
+    pub task_names: AccountNames,
+    pub parent_names: AccountNames,
 }
 
 #[derive(Default, Debug, Clone)]
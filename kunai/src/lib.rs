@@ -1,15 +1,18 @@
 #![deny(unused_imports)]
 use compat::Programs;
 use config::Config;
-use kunai_common::{kernel, version::KernelVersion};
+use kunai_common::{bpf_events::Type, config::Filter, kernel, version::KernelVersion};
 
+pub mod auth_files;
 pub mod cache;
 pub mod compat;
 pub mod config;
 pub mod containers;
 pub mod events;
+pub mod fim;
 pub mod info;
 pub mod ioc;
+pub mod sessions;
 pub mod util;
 
 /// function that responsible of probe priorities and compatibily across kernels
@@ -49,10 +52,88 @@ pub fn configure_probes(conf: &Config, programs: &mut Programs, target: KernelVe
         .expect_mut("syscalls_sys_exit_execveat")
         .max_kernel(kernel!(5, 9));
 
+    // failed executions are tracked from syscall enter to syscall exit
+    // so enter probes are useless if execve_failed events are disabled
+    let execve_failed = Filter::try_from(conf).is_ok_and(|f| f.is_enabled(Type::ExecveFailed));
+    for p in [
+        "execve_failed_syscalls_sys_enter_execve",
+        "execve_failed_syscalls_sys_enter_execveat",
+    ] {
+        programs.expect_mut(p).prio(20).disable_if(!execve_failed);
+    }
+
+    // exit probes must always run as they clean up execution tracking
+    // of failed executions, otherwise the next execution of the task
+    // is reported with the executable of the failed one
+    for p in [
+        "execve_failed_syscalls_sys_exit_execve",
+        "execve_failed_syscalls_sys_exit_execveat",
+    ] {
+        programs.expect_mut(p).prio(20);
+    }
+
     // bpf probes
     programs.expect_mut("entry_security_bpf_prog").prio(90);
     programs.expect_mut("exit_bpf_prog_load").prio(100);
 
     // mmap probe
     programs.expect_mut("syscalls_sys_enter_mmap").prio(90);
+
+    // vfs_utimes does not exist before 5.9
+    programs
+        .expect_mut("fs_vfs_utimes")
+        .min_kernel(kernel!(5, 9));
+
+    // fchmodat2 appeared in 6.6
+    programs
+        .expect_mut("fs_syscalls_sys_exit_fchmodat2")
+        .min_kernel(kernel!(6, 6));
+
+    // legacy syscalls only exist on some architectures (not on arm64)
+    for p in [
+        "fs_syscalls_sys_exit_chmod",
+        "fs_syscalls_sys_exit_chown",
+        "fs_syscalls_sys_exit_lchown",
+        "fs_syscalls_sys_exit_utime",
+        "fs_syscalls_sys_exit_utimes",
+        "fs_syscalls_sys_exit_futimesat",
+    ] {
+        programs.expect_mut(p).optional();
+    }
+
+    // perf_kprobe_init and perf_uprobe_init only exist if kernel is
+    // compiled with CONFIG_KPROBE_EVENTS and CONFIG_UPROBE_EVENTS
+    let kernel_tamper = Filter::try_from(conf).is_ok_and(|f| f.is_enabled(Type::KernelTamper));
+    for p in [
+        "kernel_tamper_perf_kprobe_init",
+        "kernel_tamper_perf_uprobe_init",
+    ] {
+        programs.expect_mut(p).optional().disable_if(!kernel_tamper);
+    }
+
+    // io_uring appeared in 5.1
+    for p in [
+        "syscalls_sys_enter_io_uring_setup",
+        "syscalls_sys_exit_io_uring_setup",
+        "syscalls_sys_enter_io_uring_enter",
+        "syscalls_sys_exit_io_uring_enter",
+    ] {
+        programs.expect_mut(p).min_kernel(kernel!(5, 1));
+    }
+
+    // io_issue_sqe is only used to report io_uring operations
+    let io_uring_op = Filter::try_from(conf).is_ok_and(|f| f.is_enabled(Type::IoUringOp));
+    programs
+        .expect_mut("io_uring_enter_io_issue_sqe")
+        .disable_if(!io_uring_op);
+
+    // io_issue_sqe and __sys_connect_file do not exist before 5.5
+    for p in [
+        "io_uring_enter_io_issue_sqe",
+        "net_enter_sys_connect_file",
+        "net_exit_sys_connect_file",
+        "fs_rw_verify_area",
+    ] {
+        programs.expect_mut(p).min_kernel(kernel!(5, 5));
+    }
 }
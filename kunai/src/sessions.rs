@@ -0,0 +1,219 @@
+use std::net::IpAddr;
+
+use lru_st::collections::LruHashMap;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_MAX_SESSIONS: usize = 4096;
+
+/// Where a login session comes from
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    #[default]
+    Unknown,
+    /// session opened through sshd
+    Ssh,
+    /// session bound to a network peer not handled by sshd (i.e. telnet, inetd)
+    Network,
+    /// pseudo terminal without any known network peer
+    Pty,
+    /// virtual console
+    Console,
+    /// serial line
+    Serial,
+}
+
+impl Origin {
+    /// guesses the origin of a session from a tty name (i.e. pts/0, tty1)
+    pub fn from_tty<S: AsRef<str>>(tty: S) -> Self {
+        let tty = tty.as_ref();
+
+        if tty.starts_with("pts") {
+            return Self::Pty;
+        }
+
+        if ["ttyS", "ttyAMA", "ttyUSB", "ttyACM", "hvc"]
+            .iter()
+            .any(|p| tty.starts_with(p))
+        {
+            return Self::Serial;
+        }
+
+        if tty == "console"
+            || tty
+                .strip_prefix("tty")
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        {
+            return Self::Console;
+        }
+
+        Self::Unknown
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Ssh => "ssh",
+            Self::Network => "network",
+            Self::Pty => "pty",
+            Self::Console => "console",
+            Self::Serial => "serial",
+        }
+    }
+
+    #[inline]
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown)
+    }
+}
+
+/// Network peer of a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Peer {
+    pub ip: IpAddr,
+    pub port: u16,
+    /// the peer is handled by sshd
+    pub ssh: bool,
+}
+
+/// Audit login session information
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub id: u32,
+    pub loginuid: Option<u32>,
+    pub origin: Origin,
+    pub tty: Option<String>,
+    pub peer: Option<Peer>,
+}
+
+impl Session {
+    fn new(id: u32, loginuid: Option<u32>) -> Self {
+        Self {
+            id,
+            loginuid,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the network peer of the session, a network peer
+    /// always takes precedence over any tty information
+    fn set_peer(&mut self, peer: Peer) {
+        if self.peer.is_none() {
+            self.origin = if peer.ssh {
+                Origin::Ssh
+            } else {
+                Origin::Network
+            };
+            self.peer = Some(peer);
+        }
+    }
+
+    fn set_tty<S: AsRef<str>>(&mut self, tty: S) {
+        if self.tty.is_none() {
+            let tty = tty.as_ref();
+            if self.origin.is_unknown() {
+                self.origin = Origin::from_tty(tty);
+            }
+            self.tty = Some(tty.into());
+        }
+    }
+}
+
+/// Keeps track of audit sessions and their origins
+pub struct Sessions {
+    sessions: LruHashMap<u32, Session>,
+}
+
+impl Default for Sessions {
+    fn default() -> Self {
+        Self::with_max_entries(DEFAULT_MAX_SESSIONS)
+    }
+}
+
+impl Sessions {
+    pub fn with_max_entries(cap: usize) -> Self {
+        Self {
+            sessions: LruHashMap::with_max_entries(cap),
+        }
+    }
+
+    /// Returns true if a network peer is already known for session
+    #[inline]
+    pub fn has_peer(&mut self, id: u32) -> bool {
+        self.sessions.get(&id).is_some_and(|s| s.peer.is_some())
+    }
+
+    /// Updates session with the information known about one of its tasks
+    /// and returns the current session state
+    pub fn update(
+        &mut self,
+        id: u32,
+        loginuid: Option<u32>,
+        peer: Option<Peer>,
+        tty: Option<&str>,
+    ) -> Session {
+        if !self.sessions.contains_key(&id) {
+            self.sessions.insert(id, Session::new(id, loginuid));
+        }
+
+        let s = self
+            .sessions
+            .get_mut(&id)
+            .expect("session must have been inserted");
+
+        if let Some(peer) = peer {
+            s.set_peer(peer);
+        }
+
+        if let Some(tty) = tty {
+            s.set_tty(tty);
+        }
+
+        s.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    #[test]
+    fn test_origin_from_tty() {
+        assert_eq!(Origin::from_tty("pts/3"), Origin::Pty);
+        assert_eq!(Origin::from_tty("tty1"), Origin::Console);
+        assert_eq!(Origin::from_tty("console"), Origin::Console);
+        assert_eq!(Origin::from_tty("ttyS0"), Origin::Serial);
+        assert_eq!(Origin::from_tty("tty"), Origin::Unknown);
+    }
+
+    #[test]
+    fn test_sessions() {
+        let mut s = Sessions::default();
+        let peer = Peer {
+            ip: IpAddr::V4(Ipv4Addr::new(192, 168, 1, 42)),
+            port: 51234,
+            ssh: true,
+        };
+
+        // session first seen with a tty
+        let sess = s.update(3, Some(1000), None, Some("pts/0"));
+        assert_eq!(sess.origin, Origin::Pty);
+        assert!(!s.has_peer(3));
+
+        // network peer takes precedence
+        let sess = s.update(3, Some(1000), Some(peer), None);
+        assert_eq!(sess.origin, Origin::Ssh);
+        assert_eq!(sess.peer, Some(peer));
+        assert_eq!(sess.tty.as_deref(), Some("pts/0"));
+
+        // origin does not change afterwards
+        let sess = s.update(3, Some(1000), None, Some("tty1"));
+        assert_eq!(sess.origin, Origin::Ssh);
+        assert_eq!(sess.tty.as_deref(), Some("pts/0"));
+        assert_eq!(sess.loginuid, Some(1000));
+        assert!(s.has_peer(3));
+    }
+}
@@ -1,15 +1,26 @@
 use anyhow::anyhow;
-use aya::{include_bytes_aligned, BpfLoader, Btf, VerifierLogLevel};
+use aya::{
+    include_bytes_aligned,
+    maps::{HashMap, MapData},
+    Bpf, BpfLoader, Btf, VerifierLogLevel,
+};
 use env_logger::Builder;
 use kunai::{
     compat::Programs,
     config::Config,
     util::{is_bpf_lsm_enabled, uname::Utsname},
 };
-use kunai_common::kernel;
+use kunai_common::{
+    bpf_events::{ExecveEvent, Type},
+    kernel,
+};
 use libc::{rlimit, LINUX_REBOOT_CMD_POWER_OFF, RLIMIT_MEMLOCK, RLIM_INFINITY};
 use log::{error, info, warn};
-use std::{ffi::CString, panic};
+use std::{ffi::CString, fs, os::unix::fs::PermissionsExt, panic, process::Command};
+
+// argument making the test binary exit right away, used
+// to run a successful execution from the test itself
+const EXIT_ARG: &str = "--exit";
 
 fn mount(src: &str, target: &str, filesystem_type: &str) -> anyhow::Result<()> {
     // Paths and options
@@ -68,7 +79,13 @@ fn integration() -> anyhow::Result<()> {
 
     let btf = Btf::from_sys_fs()?;
     let mut programs = Programs::with_bpf(&mut bpf).with_elf_info(bpf_elf)?;
-    let conf = Config::default_hardened();
+    let mut conf = Config::default_hardened();
+    // execution tracking must be cleaned up even if failed
+    // executions are not reported
+    conf.events
+        .iter_mut()
+        .filter(|e| e.name() == Type::ExecveFailed.as_str())
+        .for_each(|e| e.disable());
 
     if conf.harden {
         if current_kernel < kernel!(5, 7, 0) {
@@ -112,6 +129,50 @@ fn integration() -> anyhow::Result<()> {
         p.load_and_attach(&btf)?;
     }
 
+    check_execve_tracking(&bpf)?;
+
+    Ok(())
+}
+
+fn execve_tracking_len(bpf: &Bpf) -> anyhow::Result<usize> {
+    let map: HashMap<&MapData, u128, [u8; core::mem::size_of::<ExecveEvent>()]> =
+        HashMap::try_from(
+            bpf.map("EXECVE_TRACKING")
+                .ok_or(anyhow!("EXECVE_TRACKING map not found"))?,
+        )?;
+    Ok(map.keys().count())
+}
+
+fn check_execve_tracking(bpf: &Bpf) -> anyhow::Result<()> {
+    let self_exe = std::env::args().next().ok_or(anyhow!("no argv[0]"))?;
+    let bad_exe = "/bad_exe";
+
+    info!("checking execution tracking of a failed execution");
+    // file is executable but not in a known binary format so
+    // execution fails after the executable has been tracked
+    fs::write(bad_exe, [0u8; 64])?;
+    fs::set_permissions(bad_exe, fs::Permissions::from_mode(0o755))?;
+    if Command::new(bad_exe).status().is_ok() {
+        return Err(anyhow!("execution of {bad_exe} should have failed"));
+    }
+
+    let n = execve_tracking_len(bpf)?;
+    if n != 0 {
+        return Err(anyhow!(
+            "{n} stale execution(s) tracked after failed execution"
+        ));
+    }
+
+    info!("checking execution tracking of a successful execution");
+    if !Command::new(&self_exe).arg(EXIT_ARG).status()?.success() {
+        return Err(anyhow!("execution of {self_exe} failed"));
+    }
+
+    let n = execve_tracking_len(bpf)?;
+    if n != 0 {
+        return Err(anyhow!("{n} stale execution(s) tracked after execution"));
+    }
+
     Ok(())
 }
 
@@ -145,6 +206,11 @@ fn custom_panic_handler(info: &panic::PanicInfo) {
 }
 
 fn main() -> ! {
+    // we are executed by the integration test
+    if std::env::args().nth(1).as_deref() == Some(EXIT_ARG) {
+        std::process::exit(0);
+    }
+
     panic::set_hook(Box::new(custom_panic_handler));
 
     println!("initializing logger");
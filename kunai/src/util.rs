@@ -3,10 +3,16 @@ use ip_network::IpNetwork;
 use md5::{Digest, Md5};
 use sha1::Sha1;
 use sha2::{Sha256, Sha512};
-use std::{fs, io, net::IpAddr};
+use std::{
+    fs, io,
+    net::IpAddr,
+    path::{Component, Path, PathBuf},
+};
 
+pub mod accounts;
 pub mod bpf;
 pub mod elf;
+pub mod kmod;
 pub mod namespaces;
 pub mod uname;
 
@@ -100,6 +106,107 @@ pub fn sha512_data<T: AsRef<[u8]>>(data: T) -> String {
     hex::encode(h.finalize())
 }
 
+/// Computes HMAC-SHA256 (RFC 2104) of data
+pub fn hmac_sha256<K: AsRef<[u8]>, T: AsRef<[u8]>>(key: K, data: T) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+
+    let key = key.as_ref();
+    let mut k = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        k[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        k[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(k.map(|b| b ^ 0x36));
+    inner.update(data.as_ref());
+
+    let mut outer = Sha256::new();
+    outer.update(k.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+
+    outer.finalize().into()
+}
+
+/// Returns the symbolic name of an errno value, a negative
+/// value (i.e. kernel return code) is accepted.
+pub fn errno_name(errno: i32) -> Option<&'static str> {
+    let name = match errno.wrapping_abs() {
+        libc::EPERM => "EPERM",
+        libc::ENOENT => "ENOENT",
+        libc::ESRCH => "ESRCH",
+        libc::EINTR => "EINTR",
+        libc::EIO => "EIO",
+        libc::ENXIO => "ENXIO",
+        libc::E2BIG => "E2BIG",
+        libc::ENOEXEC => "ENOEXEC",
+        libc::EBADF => "EBADF",
+        libc::EAGAIN => "EAGAIN",
+        libc::ENOMEM => "ENOMEM",
+        libc::EACCES => "EACCES",
+        libc::EFAULT => "EFAULT",
+        libc::EBUSY => "EBUSY",
+        libc::EEXIST => "EEXIST",
+        libc::ENODEV => "ENODEV",
+        libc::ENOTDIR => "ENOTDIR",
+        libc::EISDIR => "EISDIR",
+        libc::EINVAL => "EINVAL",
+        libc::ENFILE => "ENFILE",
+        libc::EMFILE => "EMFILE",
+        libc::ETXTBSY => "ETXTBSY",
+        libc::ENOSPC => "ENOSPC",
+        libc::EROFS => "EROFS",
+        libc::ENAMETOOLONG => "ENAMETOOLONG",
+        libc::ELOOP => "ELOOP",
+        libc::ELIBBAD => "ELIBBAD",
+        libc::ENOSYS => "ENOSYS",
+        libc::EOPNOTSUPP => "EOPNOTSUPP",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Lexically normalizes a path, removing `.` and resolving `..`
+/// components without accessing the filesystem. Symlinks found
+/// in path are thus not followed.
+pub fn normalize_path<P: AsRef<Path>>(p: P) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in p.as_ref().components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => match out.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    out.pop();
+                }
+                // parent of root is root
+                Some(Component::RootDir) => {}
+                _ => out.push(c),
+            },
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// address of a symbol in kallsyms content
+fn find_ksym(kallsyms: &str, name: &str) -> Option<u64> {
+    kallsyms.lines().find_map(|l| {
+        let mut it = l.split_whitespace();
+        let addr = it.next()?;
+        let _ty = it.next()?;
+        (it.next()? == name)
+            .then(|| u64::from_str_radix(addr, 16).ok())
+            .flatten()
+    })
+}
+
+/// Returns the address of a kernel symbol read from /proc/kallsyms, addresses
+/// are zero when they are hidden to the current user (see kptr_restrict)
+pub fn kallsyms_lookup(name: &str) -> Result<Option<u64>, io::Error> {
+    Ok(find_ksym(&fs::read_to_string("/proc/kallsyms")?, name))
+}
+
 #[inline]
 pub fn is_bpf_lsm_enabled() -> Result<bool, io::Error> {
     Ok(fs::read_to_string("/sys/kernel/security/lsm")?
@@ -116,4 +223,52 @@ mod test {
         println!("PAGE_SIZE: {}", page_size().unwrap());
         println!("PAGE_SHIFT: {}", page_shift().unwrap());
     }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path("/tmp/../etc/./shadow"),
+            Path::new("/etc/shadow")
+        );
+        assert_eq!(normalize_path("/../../etc"), Path::new("/etc"));
+        assert_eq!(normalize_path("a/b/../c"), Path::new("a/c"));
+        assert_eq!(normalize_path("../a"), Path::new("../a"));
+        assert_eq!(normalize_path("../../a"), Path::new("../../a"));
+    }
+
+    #[test]
+    fn test_find_ksym() {
+        let kallsyms = "ffffffff81000000 T _stext
+ffffffff82a1b2c0 D bpf_prog_fops
+ffffffffc0a01000 t foo\t[module]";
+        assert_eq!(
+            find_ksym(kallsyms, "bpf_prog_fops"),
+            Some(0xffffffff82a1b2c0)
+        );
+        assert_eq!(find_ksym(kallsyms, "foo"), Some(0xffffffffc0a01000));
+        assert_eq!(find_ksym(kallsyms, "bpf_prog"), None);
+    }
+
+    #[test]
+    fn test_errno_name() {
+        assert_eq!(errno_name(-libc::ENOENT), Some("ENOENT"));
+        assert_eq!(errno_name(libc::EACCES), Some("EACCES"));
+        assert_eq!(errno_name(-100000), None);
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        // RFC 4231 test cases 2 and 6
+        assert_eq!(
+            hex::encode(hmac_sha256("Jefe", "what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex::encode(hmac_sha256(
+                [0xaa; 131],
+                "Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
 }
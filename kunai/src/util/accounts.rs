@@ -0,0 +1,118 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+pub const PASSWD_PATH: &str = "/etc/passwd";
+pub const GROUP_PATH: &str = "/etc/group";
+
+/// Returns true if path is one of the files accounts are read from
+#[inline]
+pub fn is_account_file<P: AsRef<Path>>(p: P) -> bool {
+    let p = p.as_ref();
+    p == Path::new(PASSWD_PATH) || p == Path::new(GROUP_PATH)
+}
+
+/// User and group names of a system, as found in
+/// /etc/passwd and /etc/group
+#[derive(Debug, Default, Clone)]
+pub struct Accounts {
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+/// Iterates over the records of colon separated databases
+/// (passwd, group, shadow), empty lines and comments are skipped
+pub fn db_records(data: &str) -> impl Iterator<Item = Vec<&str>> {
+    data.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| l.split(':').collect())
+}
+
+// parses colon separated databases where the
+// first field is the name and the third one the id
+fn parse_db<R: BufRead>(mut r: R) -> io::Result<HashMap<u32, String>> {
+    let mut out = HashMap::new();
+    let mut data = String::new();
+    r.read_to_string(&mut data)?;
+
+    for fields in db_records(&data) {
+        let (Some(name), Some(id)) = (fields.first(), fields.get(2)) else {
+            continue;
+        };
+
+        if let Ok(id) = id.parse::<u32>() {
+            // first entry wins like getpwuid does
+            out.entry(id).or_insert_with(|| name.to_string());
+        }
+    }
+
+    Ok(out)
+}
+
+fn parse_file<P: AsRef<Path>>(p: P) -> io::Result<HashMap<u32, String>> {
+    match File::open(p) {
+        Ok(f) => parse_db(BufReader::new(f)),
+        // a missing file (i.e. distroless containers) is not an error
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(e) => Err(e),
+    }
+}
+
+impl Accounts {
+    /// Reads accounts from the current mount namespace
+    pub fn load() -> io::Result<Self> {
+        Ok(Self {
+            users: parse_file(PASSWD_PATH)?,
+            groups: parse_file(GROUP_PATH)?,
+        })
+    }
+
+    pub fn from_readers<P: BufRead, G: BufRead>(passwd: P, group: G) -> io::Result<Self> {
+        Ok(Self {
+            users: parse_db(passwd)?,
+            groups: parse_db(group)?,
+        })
+    }
+
+    #[inline]
+    pub fn user(&self, uid: u32) -> Option<&String> {
+        self.users.get(&uid)
+    }
+
+    #[inline]
+    pub fn group(&self, gid: u32) -> Option<&String> {
+        self.groups.get(&gid)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_accounts() {
+        let passwd = r#"root:x:0:0:root:/root:/bin/bash
+# comment
+daemon:x:1:1:daemon:/usr/sbin:/usr/sbin/nologin
+bob:x:1000:1000::/home/bob:/bin/sh
+invalid line
+toor:x:0:0:root:/root:/bin/bash
+"#;
+        let group = "root:x:0:\nbob:x:1000:\nwheel:x:10:bob\n";
+
+        let a = Accounts::from_readers(passwd.as_bytes(), group.as_bytes()).unwrap();
+        assert_eq!(a.user(0).map(String::as_str), Some("root"));
+        assert_eq!(a.user(1000).map(String::as_str), Some("bob"));
+        assert_eq!(a.user(42), None);
+        assert_eq!(a.group(10).map(String::as_str), Some("wheel"));
+        assert_eq!(a.group(1), None);
+
+        assert!(is_account_file("/etc/passwd"));
+        assert!(is_account_file("/etc/group"));
+        assert!(!is_account_file("/etc/shadow"));
+    }
+}
@@ -1,9 +1,13 @@
-use aya_obj::generated::{bpf_attr, bpf_cmd, bpf_prog_info, bpf_prog_type};
+use aya_obj::generated::{
+    bpf_attach_type, bpf_attr, bpf_cmd, bpf_map_info, bpf_map_type, bpf_prog_info, bpf_prog_type,
+};
 use core::ffi::c_long;
 use std::io;
-use std::os::fd::RawFd;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
 use thiserror::Error;
 
+pub mod insn;
+
 pub(crate) type SysResult = Result<c_long, io::Error>;
 
 #[inline]
@@ -45,6 +49,30 @@ pub(crate) fn bpf_prog_get_info_by_fd(prog_fd: RawFd) -> Result<bpf_prog_info, i
     Ok(info)
 }
 
+fn bpf_map_get_fd_by_id(map_id: u32) -> Result<OwnedFd, io::Error> {
+    let mut attr = unsafe { core::mem::zeroed::<bpf_attr>() };
+
+    attr.__bindgen_anon_6.__bindgen_anon_1.map_id = map_id;
+
+    let fd = sys_bpf(bpf_cmd::BPF_MAP_GET_FD_BY_ID, &attr)?;
+    // we own the file descriptor returned by the kernel
+    Ok(unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+pub fn bpf_map_get_info_by_id(map_id: u32) -> Result<bpf_map_info, io::Error> {
+    let fd = bpf_map_get_fd_by_id(map_id)?;
+    // info gets entirely populated by the kernel
+    let info = unsafe { core::mem::zeroed::<bpf_map_info>() };
+    let mut attr = unsafe { core::mem::zeroed::<bpf_attr>() };
+
+    attr.info.bpf_fd = fd.as_raw_fd() as u32;
+    attr.info.info = &info as *const _ as u64;
+    attr.info.info_len = core::mem::size_of::<bpf_map_info>() as u32;
+
+    sys_bpf(bpf_cmd::BPF_OBJ_GET_INFO_BY_FD, &attr)?;
+    Ok(info)
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("wrong tag")]
@@ -97,3 +125,24 @@ pub fn bpf_type_to_string(t: u32) -> String {
         .to_ascii_lowercase()
         .replace("bpf_prog_type_", "")
 }
+
+pub fn bpf_map_type_to_string(t: u32) -> String {
+    if t > bpf_map_type::BPF_MAP_TYPE_CGRP_STORAGE as u32 {
+        return "unknown".into();
+    }
+
+    let map_type: bpf_map_type = unsafe { core::mem::transmute(t) };
+    format!("{map_type:?}")
+        .to_ascii_lowercase()
+        .replace("bpf_map_type_", "")
+}
+
+pub fn bpf_attach_type_to_string(t: u32) -> String {
+    if t >= bpf_attach_type::__MAX_BPF_ATTACH_TYPE as u32 {
+        return "unknown".into();
+    }
+
+    let attach_type: bpf_attach_type = unsafe { core::mem::transmute(t) };
+    let s = format!("{attach_type:?}").to_ascii_lowercase();
+    s.strip_prefix("bpf_").map(String::from).unwrap_or(s)
+}
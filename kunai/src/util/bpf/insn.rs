@@ -0,0 +1,480 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+// instruction classes
+const BPF_LD: u8 = 0x00;
+const BPF_LDX: u8 = 0x01;
+const BPF_ST: u8 = 0x02;
+const BPF_STX: u8 = 0x03;
+const BPF_ALU: u8 = 0x04;
+const BPF_JMP: u8 = 0x05;
+const BPF_JMP32: u8 = 0x06;
+const BPF_ALU64: u8 = 0x07;
+
+// load and store modes
+const BPF_IMM: u8 = 0x00;
+const BPF_ABS: u8 = 0x20;
+const BPF_IND: u8 = 0x40;
+const BPF_MEM: u8 = 0x60;
+const BPF_ATOMIC: u8 = 0xc0;
+
+// operand source
+const BPF_X: u8 = 0x08;
+
+// jump operations needing a special treatment
+const BPF_JA: u8 = 0x00;
+const BPF_CALL: u8 = 0x80;
+const BPF_EXIT: u8 = 0x90;
+
+// alu operations needing a special treatment
+const BPF_NEG: u8 = 0x80;
+const BPF_END: u8 = 0xd0;
+
+// src_reg values of BPF_LD | BPF_IMM | BPF_DW instructions
+// as dumped by the kernel (see bpf_insn_prepare_dump)
+const BPF_PSEUDO_MAP_FD: u8 = 1;
+const BPF_PSEUDO_MAP_VALUE: u8 = 2;
+
+// src_reg values of BPF_JMP | BPF_CALL instructions
+const BPF_PSEUDO_CALL: u8 = 1;
+const BPF_PSEUDO_KFUNC_CALL: u8 = 2;
+
+// the kernel rewrites tail calls as a call to this helper id
+const BPF_FUNC_TAIL_CALL: i32 = 12;
+
+/// Helpers allowing programs to alter the behaviour of
+/// the system rather than observing it
+pub const DANGEROUS_HELPERS: [&str; 4] = [
+    "bpf_probe_write_user",
+    "bpf_override_return",
+    "bpf_send_signal",
+    "bpf_send_signal_thread",
+];
+
+pub const INSN_SIZE: usize = 8;
+
+/// Decoded struct bpf_insn (include/uapi/linux/bpf.h)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Insn {
+    pub code: u8,
+    pub dst_reg: u8,
+    pub src_reg: u8,
+    pub off: i16,
+    pub imm: i32,
+}
+
+impl Insn {
+    fn from_bytes(b: &[u8]) -> Self {
+        Self {
+            code: b[0],
+            dst_reg: b[1] & 0x0f,
+            src_reg: b[1] >> 4,
+            off: i16::from_le_bytes([b[2], b[3]]),
+            imm: i32::from_le_bytes([b[4], b[5], b[6], b[7]]),
+        }
+    }
+
+    #[inline]
+    fn class(&self) -> u8 {
+        self.code & 0x07
+    }
+
+    #[inline]
+    fn mode(&self) -> u8 {
+        self.code & 0xe0
+    }
+
+    #[inline]
+    fn op(&self) -> u8 {
+        self.code & 0xf0
+    }
+
+    #[inline]
+    fn size(&self) -> &'static str {
+        match self.code & 0x18 {
+            0x00 => "u32",
+            0x08 => "u16",
+            0x10 => "u8",
+            _ => "u64",
+        }
+    }
+
+    #[inline]
+    fn is_ld_imm64(&self) -> bool {
+        self.code == BPF_LD | BPF_IMM | 0x18
+    }
+
+    /// Returns true if instruction is a call to a kernel helper
+    #[inline]
+    pub fn is_helper_call(&self) -> bool {
+        self.code == BPF_JMP | BPF_CALL && self.src_reg == 0
+    }
+
+    /// Returns the id of the map referenced by the instruction if any
+    #[inline]
+    pub fn map_id(&self) -> Option<u32> {
+        if self.is_ld_imm64() && matches!(self.src_reg, BPF_PSEUDO_MAP_FD | BPF_PSEUDO_MAP_VALUE) {
+            return Some(self.imm as u32);
+        }
+        None
+    }
+}
+
+/// Decodes instructions out of a program dump. Trailing
+/// bytes not making a full instruction are ignored.
+pub fn decode(insns: &[u8]) -> Vec<Insn> {
+    insns
+        .chunks_exact(INSN_SIZE)
+        .map(Insn::from_bytes)
+        .collect()
+}
+
+/// Result of the analysis of program instructions
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Analysis {
+    /// number of instructions, wide instructions count for two
+    pub insn_count: usize,
+    /// immediate values of helper calls, in order of appearance
+    pub helpers: Vec<i32>,
+    /// ids of the maps referenced, in order of appearance
+    pub maps: Vec<u32>,
+}
+
+impl Analysis {
+    pub fn from_insns(insns: &[Insn]) -> Self {
+        let mut a = Analysis {
+            insn_count: insns.len(),
+            ..Default::default()
+        };
+
+        for i in insns {
+            if i.is_helper_call() && !a.helpers.contains(&i.imm) {
+                a.helpers.push(i.imm);
+            } else if let Some(id) = i.map_id() {
+                if !a.maps.contains(&id) {
+                    a.maps.push(id);
+                }
+            }
+        }
+
+        a
+    }
+}
+
+/// Resolves helper calls of xlated programs. The immediate value
+/// of such calls is the offset of the helper to __bpf_call_base.
+#[derive(Debug, Default)]
+pub struct HelperResolver {
+    names: HashMap<i32, String>,
+}
+
+impl HelperResolver {
+    /// Builds a resolver out of kallsyms content, all the kernel
+    /// functions reachable from __bpf_call_base are kept.
+    pub fn from_kallsyms(kallsyms: &str) -> Self {
+        let mut r = Self::default();
+
+        let symbols = kallsyms.lines().filter_map(|l| {
+            let mut it = l.split_whitespace();
+            let addr = u64::from_str_radix(it.next()?, 16).ok()?;
+            let ty = it.next()?;
+            Some((addr, ty, it.next()?))
+        });
+
+        let Some(base) = symbols
+            .clone()
+            .find(|(_, _, name)| *name == "__bpf_call_base")
+            .map(|(addr, _, _)| addr)
+        else {
+            return r;
+        };
+
+        // addresses are all zeros when they are hidden
+        if base == 0 {
+            return r;
+        }
+
+        // helpers are functions of the kernel text
+        for (addr, _, name) in symbols.filter(|(_, ty, _)| matches!(*ty, "t" | "T")) {
+            if let Ok(imm) = i32::try_from(addr.wrapping_sub(base) as i64) {
+                r.names.entry(imm).or_insert_with(|| name.to_string());
+            }
+        }
+
+        r
+    }
+
+    /// Builds a resolver out of /proc/kallsyms
+    pub fn from_proc() -> Result<Self, io::Error> {
+        Ok(Self::from_kallsyms(&fs::read_to_string("/proc/kallsyms")?))
+    }
+
+    /// Returns the name of the helper called with imm
+    pub fn resolve(&self, imm: i32) -> Option<&str> {
+        if let Some(name) = self.names.get(&imm) {
+            return Some(name.as_str());
+        }
+        (imm == BPF_FUNC_TAIL_CALL).then_some("bpf_tail_call")
+    }
+}
+
+fn alu_op(op: u8) -> Option<&'static str> {
+    Some(match op {
+        0x00 => "+=",
+        0x10 => "-=",
+        0x20 => "*=",
+        0x30 => "/=",
+        0x40 => "|=",
+        0x50 => "&=",
+        0x60 => "<<=",
+        0x70 => ">>=",
+        0x90 => "%=",
+        0xa0 => "^=",
+        0xb0 => "=",
+        0xc0 => "s>>=",
+        _ => return None,
+    })
+}
+
+fn jmp_op(op: u8) -> Option<&'static str> {
+    Some(match op {
+        0x10 => "==",
+        0x20 => ">",
+        0x30 => ">=",
+        0x40 => "&",
+        0x50 => "!=",
+        0x60 => "s>",
+        0x70 => "s>=",
+        0xa0 => "<",
+        0xb0 => "<=",
+        0xc0 => "s<",
+        0xd0 => "s<=",
+        _ => return None,
+    })
+}
+
+fn atomic_op(imm: i32) -> Option<&'static str> {
+    // BPF_FETCH flag (0x01) is ignored
+    Some(match imm & !0x01 {
+        0x00 => "+=",
+        0x40 => "|=",
+        0x50 => "&=",
+        0xa0 => "^=",
+        0xe0 => "xchg",
+        0xf0 => "cmpxchg",
+        _ => return None,
+    })
+}
+
+#[inline]
+fn fmt_off(off: i16) -> String {
+    format!("{off:+}")
+}
+
+/// Disassembles instructions with a syntax close to bpftool's one.
+/// Lines are prefixed with the index of the instruction so that
+/// jump targets can be followed across wide instructions.
+pub fn disassemble(insns: &[Insn], helpers: &HelperResolver) -> Vec<String> {
+    let mut out = Vec::with_capacity(insns.len());
+    let mut i = 0;
+
+    while i < insns.len() {
+        let insn = &insns[i];
+        let (dst, src) = (insn.dst_reg, insn.src_reg);
+        let (w, reg) = match insn.class() {
+            BPF_ALU | BPF_JMP32 => ("w", 'w'),
+            _ => ("r", 'r'),
+        };
+
+        let line = match insn.class() {
+            BPF_ALU | BPF_ALU64 => match insn.op() {
+                BPF_NEG => format!("{w}{dst} = -{w}{dst}"),
+                BPF_END => {
+                    let order = if insn.code & BPF_X != 0 { "be" } else { "le" };
+                    format!("r{dst} = {order}{} r{dst}", insn.imm)
+                }
+                op => match alu_op(op) {
+                    Some(s) if insn.code & BPF_X != 0 => format!("{w}{dst} {s} {reg}{src}"),
+                    Some(s) => format!("{w}{dst} {s} {}", insn.imm),
+                    None => format!("unknown alu 0x{:02x}", insn.code),
+                },
+            },
+
+            BPF_JMP | BPF_JMP32 => match insn.op() {
+                BPF_JA => format!("goto pc{}", fmt_off(insn.off)),
+                BPF_EXIT => "exit".into(),
+                BPF_CALL => match src {
+                    BPF_PSEUDO_CALL => format!("call pc{:+}", insn.imm),
+                    BPF_PSEUDO_KFUNC_CALL => format!("call kernel-function#{}", insn.imm),
+                    _ => match helpers.resolve(insn.imm) {
+                        Some(name) => format!("call {name}#{}", insn.imm),
+                        None => format!("call unknown#{}", insn.imm),
+                    },
+                },
+                op => match jmp_op(op) {
+                    Some(s) if insn.code & BPF_X != 0 => {
+                        format!("if {reg}{dst} {s} {reg}{src} goto pc{}", fmt_off(insn.off))
+                    }
+                    Some(s) => format!(
+                        "if {reg}{dst} {s} 0x{:x} goto pc{}",
+                        insn.imm,
+                        fmt_off(insn.off)
+                    ),
+                    None => format!("unknown jmp 0x{:02x}", insn.code),
+                },
+            },
+
+            BPF_LDX if insn.mode() == BPF_MEM => format!(
+                "r{dst} = *({} *)(r{src} {})",
+                insn.size(),
+                fmt_off(insn.off)
+            ),
+
+            BPF_ST if insn.mode() == BPF_MEM => format!(
+                "*({} *)(r{dst} {}) = {}",
+                insn.size(),
+                fmt_off(insn.off),
+                insn.imm
+            ),
+
+            BPF_STX if insn.mode() == BPF_MEM => format!(
+                "*({} *)(r{dst} {}) = r{src}",
+                insn.size(),
+                fmt_off(insn.off)
+            ),
+
+            BPF_STX if insn.mode() == BPF_ATOMIC => match atomic_op(insn.imm) {
+                Some(op) => format!(
+                    "lock *({} *)(r{dst} {}) {op} r{src}",
+                    insn.size(),
+                    fmt_off(insn.off)
+                ),
+                None => format!("unknown atomic 0x{:x}", insn.imm),
+            },
+
+            BPF_LD if insn.is_ld_imm64() => {
+                // second half of the instruction holds the upper 32 bits
+                let next = insns.get(i + 1).map(|n| n.imm).unwrap_or_default();
+                let line = match src {
+                    BPF_PSEUDO_MAP_FD => format!("r{dst} = map[id:{}]", insn.imm as u32),
+                    BPF_PSEUDO_MAP_VALUE => {
+                        format!("r{dst} = map[id:{}][0]+{}", insn.imm as u32, next)
+                    }
+                    _ => {
+                        let imm = ((next as u32 as u64) << 32) | insn.imm as u32 as u64;
+                        format!("r{dst} = 0x{imm:x}")
+                    }
+                };
+                out.push(format!("{i}: {line}"));
+                i += 2;
+                continue;
+            }
+
+            BPF_LD if matches!(insn.mode(), BPF_ABS | BPF_IND) => {
+                let src = if insn.mode() == BPF_IND {
+                    format!("r{src} + ")
+                } else {
+                    "".into()
+                };
+                format!("r0 = *({} *)skb[{src}{}]", insn.size(), insn.imm)
+            }
+
+            _ => format!("unknown 0x{:02x}", insn.code),
+        };
+
+        out.push(format!("{i}: {line}"));
+        i += 1;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn insn(code: u8, dst: u8, src: u8, off: i16, imm: i32) -> [u8; 8] {
+        let mut b = [0u8; 8];
+        b[0] = code;
+        b[1] = (src << 4) | dst;
+        b[2..4].copy_from_slice(&off.to_le_bytes());
+        b[4..8].copy_from_slice(&imm.to_le_bytes());
+        b
+    }
+
+    fn prog() -> Vec<u8> {
+        [
+            // r1 = map[id:42]
+            insn(0x18, 1, BPF_PSEUDO_MAP_FD, 0, 42),
+            insn(0, 0, 0, 0, 0),
+            // r2 = r10
+            insn(0xbf, 2, 10, 0, 0),
+            // r2 += -8
+            insn(0x07, 2, 0, 0, -8),
+            // call helper
+            insn(0x85, 0, 0, 0, 1234),
+            // if r0 == 0x0 goto pc+1
+            insn(0x15, 0, 0, 1, 0),
+            // r0 = *(u64 *)(r0 +0)
+            insn(0x79, 0, 0, 0, 0),
+            // call helper again
+            insn(0x85, 0, 0, 0, 1234),
+            // bpf to bpf call
+            insn(0x85, 0, BPF_PSEUDO_CALL, 0, 3),
+            // exit
+            insn(0x95, 0, 0, 0, 0),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_analysis() {
+        let insns = decode(&prog());
+        let a = Analysis::from_insns(&insns);
+        assert_eq!(a.insn_count, 10);
+        assert_eq!(a.helpers, vec![1234]);
+        assert_eq!(a.maps, vec![42]);
+
+        // incomplete instructions are ignored
+        assert_eq!(decode(&prog()[..12]).len(), 1);
+    }
+
+    #[test]
+    fn test_helper_resolver() {
+        let kallsyms = "ffffffff81000000 T _stext
+ffffffff81200000 T __bpf_call_base
+ffffffff812004d2 T bpf_probe_write_user
+ffffffff81200100 t bpf_map_lookup_elem";
+
+        let r = HelperResolver::from_kallsyms(kallsyms);
+        assert_eq!(r.resolve(1234), Some("bpf_probe_write_user"));
+        assert_eq!(r.resolve(256), Some("bpf_map_lookup_elem"));
+        assert_eq!(r.resolve(5), None);
+        assert_eq!(r.resolve(BPF_FUNC_TAIL_CALL), Some("bpf_tail_call"));
+
+        // hidden addresses
+        let r = HelperResolver::from_kallsyms("0000000000000000 T __bpf_call_base");
+        assert_eq!(r.resolve(0), None);
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let kallsyms = "ffffffff81200000 T __bpf_call_base
+ffffffff812004d2 T bpf_probe_write_user";
+        let insns = decode(&prog());
+        let r = HelperResolver::from_kallsyms(kallsyms);
+        let d = disassemble(&insns, &r);
+
+        // wide instruction takes two slots
+        assert_eq!(d.len(), insns.len() - 1);
+        assert_eq!(d[0], "0: r1 = map[id:42]");
+        assert_eq!(d[1], "2: r2 = r10");
+        assert_eq!(d[2], "3: r2 += -8");
+        assert_eq!(d[3], "4: call bpf_probe_write_user#1234");
+        assert_eq!(d[4], "5: if r0 == 0x0 goto pc+1");
+        assert_eq!(d[5], "6: r0 = *(u64 *)(r0 +0)");
+        assert_eq!(d[7], "8: call pc+3");
+        assert_eq!(d[8], "9: exit");
+    }
+}
@@ -1,5 +1,17 @@
-use object::{self, Object, ObjectSection, ObjectSymbol};
-use std::collections::HashMap;
+use gene::{FieldGetter, FieldValue};
+use gene_derive::FieldGetter;
+use object::{
+    self, elf,
+    elf::{FileHeader32, FileHeader64},
+    read::elf::{Dyn, FileHeader, NoteIterator, ProgramHeader, SectionHeader},
+    Endianness, FileKind, Object, ObjectSection, ObjectSymbol, ReadCache, ReadRef,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    os::unix::fs::FileExt,
+};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -46,6 +58,316 @@ impl ElfInfo {
     }
 }
 
+pub const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+
+// entropy above which section data is considered packed or encrypted
+pub const HIGH_ENTROPY_THRESHOLD: f32 = 7.2;
+// sections smaller than this are too small to have a meaningful entropy
+const MIN_ENTROPY_DATA_SIZE: u64 = 512;
+// maximum amount of data read to compute the entropy of all the sections,
+// it bounds the work done on files declaring many (overlapping) sections
+const MAX_ENTROPY_DATA_SIZE: u64 = 32 * 1024 * 1024;
+// size of the areas at the start and end of file where UPX writes its magic
+const UPX_SEARCH_AREA: u64 = 4096;
+const UPX_MAGIC: &[u8; 4] = b"UPX!";
+// size of the chunks section data is read by
+const CHUNK_SIZE: usize = 4096;
+
+/// Shannon entropy computed over data read by chunks
+struct Entropy {
+    freq: [u64; 256],
+    len: u64,
+}
+
+impl Entropy {
+    #[inline]
+    fn new() -> Self {
+        Self {
+            freq: [0; 256],
+            len: 0,
+        }
+    }
+
+    #[inline]
+    fn update(&mut self, data: &[u8]) {
+        data.iter().for_each(|b| self.freq[*b as usize] += 1);
+        self.len += data.len() as u64;
+    }
+
+    #[inline]
+    fn value(&self) -> f32 {
+        let mut entropy = 0.0;
+        for &f in self.freq.iter().filter(|&&f| f != 0) {
+            let p = f as f32 / self.len as f32;
+            entropy -= p * p.log2();
+        }
+        entropy
+    }
+}
+
+#[inline]
+pub fn shannon_entropy(data: &[u8]) -> f32 {
+    let mut e = Entropy::new();
+    e.update(data);
+    e.value()
+}
+
+#[inline]
+fn machine_name(machine: u16) -> String {
+    match machine {
+        3 => "x86".into(),
+        8 => "mips".into(),
+        20 => "powerpc".into(),
+        21 => "powerpc64".into(),
+        22 => "s390".into(),
+        40 => "arm".into(),
+        62 => "x86_64".into(),
+        183 => "aarch64".into(),
+        243 => "riscv".into(),
+        247 => "bpf".into(),
+        258 => "loongarch".into(),
+        m => format!("unknown({m})"),
+    }
+}
+
+/// Metadata of an ELF file useful to characterize a binary
+#[derive(Debug, Default, Clone, PartialEq, FieldGetter, Serialize, Deserialize)]
+pub struct ElfMetadata {
+    pub arch: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_id: Option<String>,
+    /// static or dynamic
+    pub linkage: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interpreter: Option<String>,
+    pub section_headers: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packer: Option<String>,
+    pub max_entropy: f32,
+    /// names of the sections (or segments if there is no section
+    /// headers) with an entropy above HIGH_ENTROPY_THRESHOLD
+    pub high_entropy_sections: String,
+}
+
+impl ElfMetadata {
+    const SEP: &'static str = ";";
+
+    /// Parses ELF metadata out of an opened file. Only headers are
+    /// kept in memory, section data is read by chunks to compute entropy.
+    pub fn from_file(f: &File) -> Option<Self> {
+        let cache = ReadCache::new(f);
+
+        Self::parse(&cache, |offset, size, entropy| {
+            let mut buf = [0u8; CHUNK_SIZE];
+            let mut off = offset;
+            let end = offset.checked_add(size)?;
+
+            while off < end {
+                let n = (end - off).min(CHUNK_SIZE as u64) as usize;
+                f.read_exact_at(&mut buf[..n], off).ok()?;
+                entropy.update(&buf[..n]);
+                off += n as u64;
+            }
+
+            Some(())
+        })
+    }
+
+    /// Parses ELF metadata out of a full ELF file found in memory
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        Self::parse(data, |offset, size, entropy| {
+            entropy.update(data.read_bytes_at(offset, size).ok()?);
+            Some(())
+        })
+    }
+
+    /// Parses ELF metadata, it returns None if data is not an ELF file or
+    /// if headers cannot be read. Section data is never read through data,
+    /// read_entropy must feed the entropy with the data at a given offset and size.
+    fn parse<'data, R, F>(data: R, read_entropy: F) -> Option<Self>
+    where
+        R: ReadRef<'data>,
+        F: Fn(u64, u64, &mut Entropy) -> Option<()>,
+    {
+        if data.read_bytes_at(0, ELF_MAGIC.len() as u64).ok()? != ELF_MAGIC {
+            return None;
+        }
+
+        let Headers {
+            meta: mut m,
+            entropy_areas,
+        } = match FileKind::parse(data).ok()? {
+            FileKind::Elf32 => Self::parse_headers::<FileHeader32<Endianness>, _>(data)?,
+            FileKind::Elf64 => Self::parse_headers::<FileHeader64<Endianness>, _>(data)?,
+            _ => return None,
+        };
+
+        // entropy is computed on sections if any, on loadable segments otherwise
+        let mut high_entropy = vec![];
+        let mut seen = HashSet::new();
+        let mut budget = MAX_ENTROPY_DATA_SIZE;
+        for (name, offset, size) in entropy_areas {
+            // the same area is never read twice
+            if size < MIN_ENTROPY_DATA_SIZE || !seen.insert((offset, size)) {
+                continue;
+            }
+
+            // areas are truncated once the budget is exhausted
+            let size = size.min(budget);
+            if size < MIN_ENTROPY_DATA_SIZE {
+                break;
+            }
+            budget -= size;
+
+            let mut entropy = Entropy::new();
+            if read_entropy(offset, size, &mut entropy).is_none() {
+                continue;
+            }
+
+            let e = entropy.value();
+            m.max_entropy = m.max_entropy.max(e);
+            if e > HIGH_ENTROPY_THRESHOLD {
+                high_entropy.push(name);
+            }
+        }
+        m.high_entropy_sections = high_entropy.join(Self::SEP);
+
+        let len = data.len().ok()?;
+        let head = data
+            .read_bytes_at(0, len.min(UPX_SEARCH_AREA))
+            .unwrap_or_default();
+        let tail_off = len.saturating_sub(UPX_SEARCH_AREA);
+        let tail = data
+            .read_bytes_at(tail_off, len - tail_off)
+            .unwrap_or_default();
+        if [head, tail]
+            .iter()
+            .any(|area| area.windows(UPX_MAGIC.len()).any(|w| w == UPX_MAGIC))
+        {
+            m.packer = Some("upx".into());
+        }
+
+        Some(m)
+    }
+
+    fn parse_headers<'data, Elf, R>(data: R) -> Option<Headers>
+    where
+        Elf: FileHeader<Endian = Endianness>,
+        R: ReadRef<'data>,
+    {
+        let elf = Elf::parse(data).ok()?;
+        let endian = elf.endian().ok()?;
+
+        // truncated or malformed tables are handled as empty
+        let segments = elf.program_headers(endian, data).unwrap_or_default();
+        let sections = elf.sections(endian, data).ok().filter(|s| !s.is_empty());
+
+        let mut m = ElfMetadata {
+            arch: machine_name(elf.e_machine(endian)),
+            section_headers: sections.is_some(),
+            ..Default::default()
+        };
+
+        let mut needed = false;
+        for s in segments.iter() {
+            if let Ok(Some(interp)) = s.interpreter(endian, data) {
+                m.interpreter = Some(String::from_utf8_lossy(interp).to_string());
+            }
+
+            if let Ok(Some(dynamic)) = s.dynamic(endian, data) {
+                needed |= dynamic
+                    .iter()
+                    .map(|d| d.d_tag(endian).into())
+                    .take_while(|&tag| tag != u64::from(elf::DT_NULL))
+                    .any(|tag| tag == u64::from(elf::DT_NEEDED));
+            }
+
+            if m.build_id.is_none() {
+                m.build_id = s
+                    .notes(endian, data)
+                    .ok()
+                    .flatten()
+                    .and_then(|notes| Self::build_id(notes, endian));
+            }
+        }
+
+        // build-id note may only be referenced by sections
+        if m.build_id.is_none() {
+            m.build_id = sections.as_ref().and_then(|t| {
+                t.iter().find_map(|s| {
+                    s.notes(endian, data)
+                        .ok()
+                        .flatten()
+                        .and_then(|notes| Self::build_id(notes, endian))
+                })
+            });
+        }
+
+        m.linkage = if m.interpreter.is_some() || needed {
+            "dynamic".into()
+        } else {
+            "static".into()
+        };
+
+        let entropy_areas = if let Some(sections) = sections.as_ref() {
+            // hash tables and compressed sections are naturally high entropy
+            sections
+                .iter()
+                .filter(|s| {
+                    !matches!(
+                        s.sh_type(endian),
+                        elf::SHT_NOBITS | elf::SHT_HASH | elf::SHT_GNU_HASH
+                    ) && s.sh_flags(endian).into() & u64::from(elf::SHF_COMPRESSED) == 0
+                })
+                .filter_map(|s| {
+                    let (offset, size) = s.file_range(endian)?;
+                    let name = sections
+                        .section_name(endian, s)
+                        .map(|n| String::from_utf8_lossy(n).to_string())
+                        .unwrap_or_default();
+                    Some((name, offset, size))
+                })
+                .collect()
+        } else {
+            segments
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.p_type(endian) == elf::PT_LOAD)
+                .map(|(i, s)| {
+                    let (offset, size) = s.file_range(endian);
+                    (format!("segment{i}"), offset, size)
+                })
+                .collect()
+        };
+
+        Some(Headers {
+            meta: m,
+            entropy_areas,
+        })
+    }
+
+    /// walks notes to find the GNU build-id
+    fn build_id<Elf: FileHeader>(
+        mut notes: NoteIterator<'_, Elf>,
+        endian: Elf::Endian,
+    ) -> Option<String> {
+        while let Ok(Some(note)) = notes.next() {
+            if note.n_type(endian) == elf::NT_GNU_BUILD_ID && note.name() == elf::ELF_NOTE_GNU {
+                return Some(hex::encode(note.desc()));
+            }
+        }
+        None
+    }
+}
+
+/// Metadata parsed out of ELF headers along with the
+/// areas of the file entropy must be computed on
+struct Headers {
+    meta: ElfMetadata,
+    // name, offset and size of the areas
+    entropy_areas: Vec<(String, u64, u64)>,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -55,4 +377,26 @@ mod test {
         let data = include_bytes!("../../../target/bpfel-unknown-none/debug/kunai-ebpf");
         println!("{:#?}", ElfInfo::from_raw_elf(data.as_slice()).unwrap())
     }
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(shannon_entropy(&[]), 0.0);
+        assert_eq!(shannon_entropy(&[0x41; 1024]), 0.0);
+        let all = (0..=255u8).collect::<Vec<u8>>();
+        assert_eq!(shannon_entropy(&all), 8.0);
+    }
+
+    #[test]
+    fn test_elf_metadata() {
+        // test binary is an ELF file of the current architecture
+        let data = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let m = ElfMetadata::from_bytes(&data).unwrap();
+        assert_eq!(m.arch, std::env::consts::ARCH);
+        assert!(m.section_headers);
+        assert!(m.packer.is_none());
+
+        assert!(ElfMetadata::from_bytes(b"not an elf").is_none());
+        // truncated header
+        assert!(ElfMetadata::from_bytes(b"\x7fELF\x02\x01").is_none());
+    }
 }
@@ -0,0 +1,285 @@
+use gene::{FieldGetter, FieldValue};
+use gene_derive::FieldGetter;
+use object::{Object, ObjectSection, ReadCache, ReadRef};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+};
+
+/// Marker appended at the end of signed modules
+pub const MODULE_SIG_MAGIC: &[u8] = b"~Module signature appended~\n";
+
+/// Module images found in process memory bigger than this are not
+/// hashed, as hashing implies reading the whole image synchronously.
+pub const MAX_MEMORY_IMAGE_HASH_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Where module information has been read from
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleSource {
+    #[default]
+    File,
+    Buffer,
+    /// image given to init_module read in the memory of the loading task, on
+    /// a best effort basis as the task may have modified or released it since
+    ProcessMemory,
+}
+
+impl ModuleSource {
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Buffer => "buffer",
+            Self::ProcessMemory => "process_memory",
+        }
+    }
+}
+
+impl FieldGetter for ModuleSource {
+    fn get_from_iter(&self, i: core::slice::Iter<'_, std::string::String>) -> Option<FieldValue> {
+        if i.len() > 0 {
+            return None;
+        }
+        Some(self.as_str().into())
+    }
+}
+
+/// Metadata of a kernel module found in its .modinfo section
+#[derive(Debug, Default, Clone, PartialEq, FieldGetter, Serialize, Deserialize)]
+pub struct ModuleInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// authors separated by ;
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vermagic: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub srcversion: Option<String>,
+    /// a signature is appended to the module (an unsigned module taints
+    /// the kernel), the signature itself is not verified
+    pub signature_present: bool,
+    /// module taints the kernel because it was built out of kernel tree
+    pub out_of_tree: bool,
+    pub source: ModuleSource,
+}
+
+impl ModuleInfo {
+    const SEP: &'static str = ";";
+
+    /// Parses module information out of a module image (uncompressed .ko file).
+    /// It returns None if data is not an ELF file or if it has no .modinfo section.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        Self::parse(data, ModuleSource::Buffer)
+    }
+
+    /// Parses module information out of a module file, only headers
+    /// and .modinfo section are read.
+    pub fn from_file(f: &File) -> Option<Self> {
+        Self::parse(&ReadCache::new(f), ModuleSource::File)
+    }
+
+    /// Parses module information out of a module image loaded in the memory
+    /// of a process, only headers and .modinfo section are read. This is
+    /// best effort as the process may change its memory at any time.
+    pub fn from_process_memory(pid: i32, addr: u64, len: u64) -> Option<Self> {
+        Self::parse(
+            &ReadCache::new(ProcessMemory::new(pid, addr, len)),
+            ModuleSource::ProcessMemory,
+        )
+    }
+
+    fn parse<'data, R: ReadRef<'data>>(data: R, source: ModuleSource) -> Option<Self> {
+        let obj = object::read::File::parse(data).ok()?;
+        let modinfo = obj.section_by_name(".modinfo")?.data().ok()?;
+
+        let len = data.len().ok()?;
+        let sig_len = MODULE_SIG_MAGIC.len() as u64;
+        let signature_present = len >= sig_len
+            && data.read_bytes_at(len - sig_len, sig_len).ok() == Some(MODULE_SIG_MAGIC);
+
+        let mut mi = ModuleInfo {
+            signature_present,
+            out_of_tree: true,
+            source,
+            ..Default::default()
+        };
+
+        // .modinfo is a list of NUL terminated key=value strings
+        for (k, v) in modinfo
+            .split(|b| *b == 0)
+            .filter_map(|e| std::str::from_utf8(e).ok())
+            .filter_map(|e| e.split_once('='))
+        {
+            let field = match k {
+                "name" => &mut mi.name,
+                "version" => &mut mi.version,
+                "description" => &mut mi.description,
+                "license" => &mut mi.license,
+                "vermagic" => &mut mi.vermagic,
+                "srcversion" => &mut mi.srcversion,
+                "intree" => {
+                    mi.out_of_tree = v != "Y";
+                    continue;
+                }
+                // modules may have several authors
+                "author" => {
+                    match mi.author.as_mut() {
+                        Some(a) => {
+                            a.push_str(Self::SEP);
+                            a.push_str(v);
+                        }
+                        None => mi.author = Some(v.into()),
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if field.is_none() {
+                *field = Some(v.into());
+            }
+        }
+
+        Some(mi)
+    }
+}
+
+#[inline]
+fn read_process_memory_into(pid: i32, addr: u64, buf: &mut [u8]) -> io::Result<usize> {
+    let local = libc::iovec {
+        iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+    let remote = libc::iovec {
+        iov_base: addr as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+
+    let n = unsafe { libc::process_vm_readv(pid, &local, 1, &remote, 1, 0) };
+    if n < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(n as usize)
+}
+
+/// Reads len bytes at addr in the memory of process pid
+pub fn read_process_memory(pid: i32, addr: u64, len: usize) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; len];
+    let n = read_process_memory_into(pid, addr, &mut buf)?;
+    buf.truncate(n);
+    Ok(buf)
+}
+
+/// Reader over a memory area of a process
+pub struct ProcessMemory {
+    pid: i32,
+    addr: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl ProcessMemory {
+    pub fn new(pid: i32, addr: u64, len: u64) -> Self {
+        Self {
+            pid,
+            addr,
+            len,
+            pos: 0,
+        }
+    }
+}
+
+impl Read for ProcessMemory {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.len.saturating_sub(self.pos) as usize);
+        if n == 0 {
+            return Ok(0);
+        }
+        let n = read_process_memory_into(self.pid, self.addr + self.pos, &mut buf[..n])?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for ProcessMemory {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::End(o) => self.len.checked_add_signed(o),
+            SeekFrom::Current(o) => self.pos.checked_add_signed(o),
+        };
+
+        self.pos = pos
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek position"))?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_module_info() {
+        // a non module ELF has no .modinfo
+        let exe = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        assert_eq!(ModuleInfo::from_bytes(&exe), None);
+        assert_eq!(ModuleInfo::from_bytes(b"not an elf"), None);
+
+        // we test against a module of the running kernel if any is available
+        let u = crate::util::uname::Utsname::from_sys().unwrap();
+        let Some(ko) =
+            std::fs::read_dir(format!("/lib/modules/{}/kernel/fs", u.release().unwrap()))
+                .ok()
+                .and_then(|mut d| {
+                    d.find_map(|e| {
+                        let e = e.ok()?;
+                        std::fs::read_dir(e.path()).ok()?.find_map(|f| {
+                            let p = f.ok()?.path();
+                            (p.extension()? == "ko").then_some(p)
+                        })
+                    })
+                })
+        else {
+            return;
+        };
+
+        let mi = ModuleInfo::from_bytes(&std::fs::read(&ko).unwrap()).unwrap();
+        assert!(mi.vermagic.is_some());
+        assert!(!mi.out_of_tree);
+        assert_eq!(mi.source, ModuleSource::Buffer);
+        // parsing from file must give the same result
+        let f = std::fs::File::open(&ko).unwrap();
+        assert_eq!(
+            ModuleInfo::from_file(&f),
+            Some(ModuleInfo {
+                source: ModuleSource::File,
+                ..mi
+            })
+        );
+    }
+
+    #[test]
+    fn test_read_process_memory() {
+        let data = b"kunai reads its own memory".to_vec();
+        let out = read_process_memory(std::process::id() as i32, data.as_ptr() as u64, data.len())
+            .unwrap();
+        assert_eq!(out, data);
+
+        let mut out = vec![];
+        let mut r = ProcessMemory::new(std::process::id() as i32, data.as_ptr() as u64, 5);
+        r.seek(SeekFrom::Start(1)).unwrap();
+        r.read_to_end(&mut out).unwrap();
+        assert_eq!(out, b"unai");
+    }
+}